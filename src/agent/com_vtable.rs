@@ -0,0 +1,297 @@
+//! Declarative COM vtable hooks.
+//!
+//! A `VtableHook` describes one interface method: the vtable slot it lives in, the interface
+//! revisions that have it, its signature (the `F` function type) and the detour that formats the
+//! call. Every distinct implementation found in that slot gets its own inline detour, so
+//! `IDirectDraw` and `IDirectDraw7` (or ddraw and ddrawex) are traced side by side from a single
+//! description. Each of those detours runs its own copy of the detour function, which always
+//! calls back through its own trampoline whatever the interface's vtable says now.
+
+use crate::agent::detach::agent_detached;
+use crate::agent::hook_inventory::{InstallMethod, enable_tracked_detour};
+use crate::{
+    is_ptr_in_directdraw_runtime, is_readable_ptr, make_event, ptr_to_fn, send_event,
+    vtable_method_ptr,
+};
+use retour::{Function, GenericDetour};
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::ops::RangeInclusive;
+use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
+
+/// Distinct implementations one description can detour; each gets its own detour function.
+pub const DETOUR_SLOTS: usize = 4;
+
+/// Every vtable target patched so far, keyed by address, with the `Interface::Method` name that
+/// owns it. Two descriptions resolving to the same implementation never stack detours.
+static HOOKED_TARGETS: OnceLock<Mutex<HashMap<usize, &'static str>>> = OnceLock::new();
+/// Interface revision of every vtable hooks were installed from, keyed by vtable address.
+static VTABLE_REVISIONS: OnceLock<Mutex<HashMap<usize, u32>>> = OnceLock::new();
+#[cfg(target_pointer_width = "32")]
+static FORWARDING_STUBS: OnceLock<Mutex<HashMap<usize, usize>>> = OnceLock::new();
+
+/// Declares a `VtableHook` static together with its detour. The detour calls the original
+/// implementation, then evaluates `$report` to a `(summary, result)` pair for the event; the
+/// report expression can also read out parameters or install follow-up hooks.
+macro_rules! vtable_hook {
+    (
+        $vis:vis $hook:ident = $api:literal [$index:expr, $revisions:expr],
+        fn $detour:ident($this:ident $(, $arg:ident: $arg_ty:ty)* $(,)?) -> $ret:ty,
+        |$result:ident| $report:expr $(,)?
+    ) => {
+        $vis static $hook: $crate::agent::com_vtable::VtableHook<
+            unsafe extern "system" fn(*mut std::ffi::c_void $(, $arg_ty)*) -> $ret,
+        > = $crate::agent::com_vtable::VtableHook::new(
+            $api,
+            $index,
+            $revisions,
+            $crate::agent::com_vtable::detour_slots!($detour),
+        );
+
+        unsafe extern "system" fn $detour<const SLOT: usize>(
+            $this: *mut std::ffi::c_void,
+            $($arg: $arg_ty),*
+        ) -> $ret {
+            let _active = $crate::agent::detach::ActiveCall::enter();
+            let $result = unsafe { $hook.original(SLOT).call($this $(, $arg)*) };
+            let (summary, result) = $report;
+            $crate::send_event($crate::make_event($api, summary, result));
+            $result
+        }
+    };
+}
+pub(crate) use vtable_hook;
+
+/// One instance of a detour function per slot, for a detour declared with a `const SLOT: usize`
+/// parameter that calls `VtableHook::original(SLOT)`.
+macro_rules! detour_slots {
+    ($detour:ident) => {
+        [$detour::<0>, $detour::<1>, $detour::<2>, $detour::<3>]
+    };
+}
+pub(crate) use detour_slots;
+
+pub trait VtableHookEntry: Sync {
+    fn applies_to(&self, revision: u32) -> bool;
+    fn install(&'static self, instance: *mut c_void, revision: u32, source: &str, report_api: &str);
+    fn is_installed(&self) -> bool;
+}
+
+pub struct VtableHook<F: Function> {
+    name: &'static str,
+    index: usize,
+    revisions: RangeInclusive<u32>,
+    detours: [F; DETOUR_SLOTS],
+    /// The detour placed with each of `detours`, stored before it is enabled.
+    slots: [AtomicPtr<GenericDetour<F>>; DETOUR_SLOTS],
+    /// Every patched implementation with the slot detouring it.
+    targets: Mutex<Vec<(usize, usize)>>,
+}
+
+impl<F: Function> VtableHook<F> {
+    /// `revisions` lists the interface revisions whose vtable has this method at `index`; slots
+    /// whose signature changed between revisions get one description per signature.
+    pub const fn new(
+        name: &'static str,
+        index: usize,
+        revisions: RangeInclusive<u32>,
+        detours: [F; DETOUR_SLOTS],
+    ) -> Self {
+        Self {
+            name,
+            index,
+            revisions,
+            detours,
+            slots: [const { AtomicPtr::new(std::ptr::null_mut()) }; DETOUR_SLOTS],
+            targets: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the detour placed with `detours[slot]`, whose trampoline leads to the
+    /// implementation that detour function was patched into.
+    pub fn original(&self, slot: usize) -> &'static GenericDetour<F> {
+        let detour = self.slots[slot].load(Ordering::Acquire);
+        // Slots only ever hold leaked detours, and one is stored before its detour goes live.
+        match unsafe { detour.as_ref() } {
+            Some(detour) => detour,
+            None => panic!("{} detour slot {slot} not installed", self.name),
+        }
+    }
+
+    /// The implementation `this` dispatches to, reached through its trampoline when it is
+    /// detoured, so the agent can call methods without tracing its own calls.
+    pub fn untraced(&self, this: *mut c_void) -> Option<F> {
+        let method_ptr = vtable_method_ptr(this, self.index)?;
+        let called = resolve_forwarding_stub(method_ptr as usize);
+        let targets = self.targets.lock().unwrap_or_else(PoisonError::into_inner);
+        Some(match targets.iter().find(|(target, _)| *target == called) {
+            Some(&(_, slot)) => unsafe { F::from_ptr(self.original(slot).trampoline()) },
+            None => ptr_to_fn(method_ptr),
+        })
+    }
+}
+
+impl<F: Function> VtableHookEntry for VtableHook<F> {
+    fn applies_to(&self, revision: u32) -> bool {
+        self.revisions.contains(&revision)
+    }
+
+    fn install(
+        &'static self,
+        instance: *mut c_void,
+        revision: u32,
+        source: &str,
+        report_api: &str,
+    ) {
+        if instance.is_null() || agent_detached() {
+            return;
+        }
+        let Some(method_ptr) = vtable_method_ptr(instance, self.index) else {
+            return;
+        };
+        let method_addr = method_ptr as usize;
+
+        let hooked = HOOKED_TARGETS.get_or_init(|| Mutex::new(HashMap::new()));
+        let Ok(mut hooked) = hooked.lock() else {
+            return;
+        };
+        if hooked.contains_key(&method_addr) {
+            return;
+        }
+
+        let in_runtime = is_ptr_in_directdraw_runtime(method_ptr);
+        let summary = format!(
+            "source={source} method={} rev={revision} ptr={method_ptr:p} in_runtime={in_runtime}",
+            self.name
+        );
+
+        let Some(slot) = self
+            .slots
+            .iter()
+            .position(|slot| slot.load(Ordering::Acquire).is_null())
+        else {
+            send_event(make_event(
+                report_api,
+                summary,
+                format!("INIT_FAILED: all {DETOUR_SLOTS} detour slots in use"),
+            ));
+            return;
+        };
+
+        let target_fn: F = ptr_to_fn(method_ptr);
+        let detour: &'static GenericDetour<F> =
+            match unsafe { GenericDetour::new(target_fn, self.detours[slot]) } {
+                Ok(detour) => Box::leak(Box::new(detour)),
+                Err(error) => {
+                    send_event(make_event(
+                        report_api,
+                        summary,
+                        format!("INIT_FAILED: {error}"),
+                    ));
+                    return;
+                }
+            };
+
+        // Store before enabling so the detour can reach its trampoline as soon as it is live.
+        // Installs are serialized by `HOOKED_TARGETS`, so nothing else claims the slot meanwhile.
+        self.slots[slot].store(
+            detour as *const GenericDetour<F> as *mut GenericDetour<F>,
+            Ordering::Release,
+        );
+        match enable_tracked_detour(detour, target_fn, self.name, InstallMethod::Vtable) {
+            Ok(()) => {
+                hooked.insert(method_addr, self.name);
+                self.targets
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .push((method_addr, slot));
+                send_event(make_event(report_api, summary, "ENABLED".to_owned()));
+            }
+            Err(error) => {
+                self.slots[slot].store(std::ptr::null_mut(), Ordering::Release);
+                send_event(make_event(
+                    report_api,
+                    summary,
+                    format!("ENABLE_FAILED: {error}"),
+                ));
+            }
+        }
+    }
+
+    fn is_installed(&self) -> bool {
+        self.targets.lock().is_ok_and(|targets| !targets.is_empty())
+    }
+}
+
+/// Installs every hook in `hooks` that exists in `revision` of the interface, and remembers the
+/// revision of `instance`'s vtable for `interface_revision`.
+pub fn install_vtable_hooks(
+    hooks: &[&'static dyn VtableHookEntry],
+    instance: *mut c_void,
+    revision: u32,
+    source: &str,
+    report_api: &str,
+) {
+    if instance.is_null() {
+        return;
+    }
+
+    record_interface_revision(instance, revision);
+    for hook in hooks {
+        if hook.applies_to(revision) {
+            hook.install(instance, revision, source, report_api);
+        }
+    }
+}
+
+pub fn record_interface_revision(instance: *mut c_void, revision: u32) {
+    let Some(vtable) = read_vtable_ptr(instance) else {
+        return;
+    };
+    let revisions = VTABLE_REVISIONS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Ok(mut revisions) = revisions.lock() {
+        revisions.insert(vtable, revision);
+    }
+}
+
+/// Revision of the interface `instance` points at, if hooks were installed from its vtable.
+pub fn interface_revision(instance: *mut c_void) -> Option<u32> {
+    let vtable = read_vtable_ptr(instance)?;
+    let revisions = VTABLE_REVISIONS.get()?.lock().ok()?;
+    revisions.get(&vtable).copied()
+}
+
+fn read_vtable_ptr(instance: *mut c_void) -> Option<usize> {
+    if !is_readable_ptr(instance, std::mem::size_of::<usize>()) {
+        return None;
+    }
+
+    let vtable = unsafe { *(instance as *const usize) };
+    (vtable != 0).then_some(vtable)
+}
+
+/// Records a vtable-copy stub that forwards to `target`, so detours can still tell which
+/// implementation a patched interface dispatched to.
+#[cfg(target_pointer_width = "32")]
+pub fn register_forwarding_stub(stub: usize, target: usize) {
+    let stubs = FORWARDING_STUBS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Ok(mut stubs) = stubs.lock() {
+        stubs.insert(stub, target);
+    }
+}
+
+fn resolve_forwarding_stub(addr: usize) -> usize {
+    #[cfg(target_pointer_width = "32")]
+    if let Some(stubs) = FORWARDING_STUBS.get() {
+        if let Some(target) = stubs
+            .lock()
+            .ok()
+            .and_then(|stubs| stubs.get(&addr).copied())
+        {
+            return target;
+        }
+    }
+
+    addr
+}
@@ -0,0 +1,257 @@
+//! IDirect3D9/IDirect3D9Ex and IDirect3DDevice9/IDirect3DDevice9Ex vtable coverage.
+//!
+//! The Ex interfaces only append methods, so revision 1 is the base interface and revision 2 its
+//! Ex extension, mirroring how the DirectDraw families are described.
+
+use super::com_vtable::{VtableHookEntry, install_vtable_hooks, vtable_hook};
+use crate::decode::structs::{PresentParametersLayout, parse_present_parameters};
+use crate::{
+    describe_rect_ptr, describe_struct_ptr, hresult_result, hresult_succeeded, read_out_ptr,
+};
+use std::ffi::c_void;
+use windows_sys::Win32::Foundation::RECT;
+
+pub const DIRECT3D9_REVISION: u32 = 1;
+pub const DIRECT3D9EX_REVISION: u32 = 2;
+
+// IDirect3D9 vtable layout (IDirect3D9Ex appends 17-21).
+const D3D9_METHOD_CREATE_DEVICE_INDEX: usize = 16;
+const D3D9_METHOD_CREATE_DEVICE_EX_INDEX: usize = 20;
+
+// IDirect3DDevice9 vtable layout (IDirect3DDevice9Ex appends 119-133).
+const D3DDEV9_METHOD_TEST_COOPERATIVE_LEVEL_INDEX: usize = 3;
+const D3DDEV9_METHOD_RESET_INDEX: usize = 16;
+const D3DDEV9_METHOD_PRESENT_INDEX: usize = 17;
+const D3DDEV9_METHOD_CREATE_TEXTURE_INDEX: usize = 23;
+const D3DDEV9_METHOD_CREATE_RENDER_TARGET_INDEX: usize = 28;
+const D3DDEV9_METHOD_BEGIN_SCENE_INDEX: usize = 41;
+const D3DDEV9_METHOD_END_SCENE_INDEX: usize = 42;
+const D3DDEV9_METHOD_SET_RENDER_STATE_INDEX: usize = 57;
+const D3DDEV9_METHOD_PRESENT_EX_INDEX: usize = 121;
+const D3DDEV9_METHOD_RESET_EX_INDEX: usize = 132;
+
+static DIRECT3D9_VTABLE_HOOKS: &[&dyn VtableHookEntry] =
+    &[&D3D9_CREATE_DEVICE, &D3D9_CREATE_DEVICE_EX];
+static DIRECT3DDEVICE9_VTABLE_HOOKS: &[&dyn VtableHookEntry] = &[
+    &D3DDEV9_TEST_COOPERATIVE_LEVEL,
+    &D3DDEV9_RESET,
+    &D3DDEV9_PRESENT,
+    &D3DDEV9_CREATE_TEXTURE,
+    &D3DDEV9_CREATE_RENDER_TARGET,
+    &D3DDEV9_BEGIN_SCENE,
+    &D3DDEV9_END_SCENE,
+    &D3DDEV9_SET_RENDER_STATE,
+    &D3DDEV9_PRESENT_EX,
+    &D3DDEV9_RESET_EX,
+];
+
+pub fn try_install_direct3d9_hooks(direct3d: *mut c_void, revision: u32, source: &str) {
+    install_vtable_hooks(
+        DIRECT3D9_VTABLE_HOOKS,
+        direct3d,
+        revision,
+        source,
+        "Direct3D9HookInstall",
+    );
+}
+
+fn try_install_direct3d9_device_hooks(device: *mut c_void, revision: u32, source: &str) {
+    install_vtable_hooks(
+        DIRECT3DDEVICE9_VTABLE_HOOKS,
+        device,
+        revision,
+        source,
+        "Direct3D9HookInstall",
+    );
+}
+
+fn describe_present_parameters(params: *mut c_void) -> String {
+    let pointer_size = std::mem::size_of::<usize>();
+    let size = PresentParametersLayout::for_pointer_size(pointer_size).size;
+    describe_struct_ptr(params, size, |bytes| {
+        parse_present_parameters(bytes, pointer_size).map(|params| params.to_string())
+    })
+}
+
+vtable_hook! {
+    D3D9_CREATE_DEVICE = "IDirect3D9::CreateDevice" [D3D9_METHOD_CREATE_DEVICE_INDEX, 1..=2],
+    fn d3d9_create_device_detour(
+        this,
+        adapter: u32,
+        device_type: u32,
+        focus_window: isize,
+        behavior: u32,
+        params: *mut c_void,
+        device_out: *mut *mut c_void,
+    ) -> i32,
+    |result| {
+        let device = read_out_ptr(device_out);
+        if hresult_succeeded(result) {
+            try_install_direct3d9_device_hooks(device, DIRECT3D9_REVISION, "IDirect3D9::CreateDevice");
+        }
+        (
+            format!(
+                "this={this:p} adapter={adapter} type={device_type} focus=0x{focus_window:016X} behavior=0x{behavior:08X} {}",
+                describe_present_parameters(params)
+            ),
+            format!("{} device={device:p}", hresult_result(result)),
+        )
+    }
+}
+
+vtable_hook! {
+    D3D9_CREATE_DEVICE_EX = "IDirect3D9Ex::CreateDeviceEx" [D3D9_METHOD_CREATE_DEVICE_EX_INDEX, 2..=2],
+    fn d3d9_create_device_ex_detour(
+        this,
+        adapter: u32,
+        device_type: u32,
+        focus_window: isize,
+        behavior: u32,
+        params: *mut c_void,
+        fullscreen_mode: *mut c_void,
+        device_out: *mut *mut c_void,
+    ) -> i32,
+    |result| {
+        let device = read_out_ptr(device_out);
+        if hresult_succeeded(result) {
+            try_install_direct3d9_device_hooks(device, DIRECT3D9EX_REVISION, "IDirect3D9Ex::CreateDeviceEx");
+        }
+        (
+            format!(
+                "this={this:p} adapter={adapter} type={device_type} focus=0x{focus_window:016X} behavior=0x{behavior:08X} {} fullscreen_mode={fullscreen_mode:p}",
+                describe_present_parameters(params)
+            ),
+            format!("{} device={device:p}", hresult_result(result)),
+        )
+    }
+}
+
+vtable_hook! {
+    D3DDEV9_TEST_COOPERATIVE_LEVEL = "IDirect3DDevice9::TestCooperativeLevel" [D3DDEV9_METHOD_TEST_COOPERATIVE_LEVEL_INDEX, 1..=2],
+    fn d3ddev9_test_cooperative_level_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    D3DDEV9_RESET = "IDirect3DDevice9::Reset" [D3DDEV9_METHOD_RESET_INDEX, 1..=2],
+    fn d3ddev9_reset_detour(this, params: *mut c_void) -> i32,
+    |result| (
+        format!("this={this:p} {}", describe_present_parameters(params)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    D3DDEV9_PRESENT = "IDirect3DDevice9::Present" [D3DDEV9_METHOD_PRESENT_INDEX, 1..=2],
+    fn d3ddev9_present_detour(
+        this,
+        source_rect: *const RECT,
+        dest_rect: *const RECT,
+        override_window: isize,
+        dirty_region: *const c_void,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} src={} dst={} hwnd=0x{override_window:016X} dirty={dirty_region:p}",
+            describe_rect_ptr(source_rect),
+            describe_rect_ptr(dest_rect)
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    D3DDEV9_CREATE_TEXTURE = "IDirect3DDevice9::CreateTexture" [D3DDEV9_METHOD_CREATE_TEXTURE_INDEX, 1..=2],
+    fn d3ddev9_create_texture_detour(
+        this,
+        width: u32,
+        height: u32,
+        levels: u32,
+        usage: u32,
+        format: u32,
+        pool: u32,
+        texture_out: *mut *mut c_void,
+        shared_handle: *mut isize,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} width={width} height={height} levels={levels} usage=0x{usage:08X} format={format} pool={pool} shared={shared_handle:p}"
+        ),
+        format!("{} texture={:p}", hresult_result(result), read_out_ptr(texture_out)),
+    )
+}
+
+vtable_hook! {
+    D3DDEV9_CREATE_RENDER_TARGET = "IDirect3DDevice9::CreateRenderTarget" [D3DDEV9_METHOD_CREATE_RENDER_TARGET_INDEX, 1..=2],
+    fn d3ddev9_create_render_target_detour(
+        this,
+        width: u32,
+        height: u32,
+        format: u32,
+        multisample: u32,
+        multisample_quality: u32,
+        lockable: i32,
+        surface_out: *mut *mut c_void,
+        shared_handle: *mut isize,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} width={width} height={height} format={format} multisample={multisample} quality={multisample_quality} lockable={} shared={shared_handle:p}",
+            lockable != 0
+        ),
+        format!("{} surface={:p}", hresult_result(result), read_out_ptr(surface_out)),
+    )
+}
+
+vtable_hook! {
+    D3DDEV9_BEGIN_SCENE = "IDirect3DDevice9::BeginScene" [D3DDEV9_METHOD_BEGIN_SCENE_INDEX, 1..=2],
+    fn d3ddev9_begin_scene_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    D3DDEV9_END_SCENE = "IDirect3DDevice9::EndScene" [D3DDEV9_METHOD_END_SCENE_INDEX, 1..=2],
+    fn d3ddev9_end_scene_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    D3DDEV9_SET_RENDER_STATE = "IDirect3DDevice9::SetRenderState" [D3DDEV9_METHOD_SET_RENDER_STATE_INDEX, 1..=2],
+    fn d3ddev9_set_render_state_detour(this, state: u32, value: u32) -> i32,
+    |result| (
+        format!("this={this:p} state={state} value=0x{value:08X}"),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    D3DDEV9_PRESENT_EX = "IDirect3DDevice9Ex::PresentEx" [D3DDEV9_METHOD_PRESENT_EX_INDEX, 2..=2],
+    fn d3ddev9_present_ex_detour(
+        this,
+        source_rect: *const RECT,
+        dest_rect: *const RECT,
+        override_window: isize,
+        dirty_region: *const c_void,
+        flags: u32,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} src={} dst={} hwnd=0x{override_window:016X} dirty={dirty_region:p} flags=0x{flags:08X}",
+            describe_rect_ptr(source_rect),
+            describe_rect_ptr(dest_rect)
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    D3DDEV9_RESET_EX = "IDirect3DDevice9Ex::ResetEx" [D3DDEV9_METHOD_RESET_EX_INDEX, 2..=2],
+    fn d3ddev9_reset_ex_detour(this, params: *mut c_void, fullscreen_mode: *mut c_void) -> i32,
+    |result| (
+        format!(
+            "this={this:p} {} fullscreen_mode={fullscreen_mode:p}",
+            describe_present_parameters(params)
+        ),
+        hresult_result(result),
+    )
+}
@@ -0,0 +1,1510 @@
+//! IDirectDraw/2/4/7 and IDirectDrawSurface/2/3/4/7 vtable coverage.
+//!
+//! Later interface revisions only append methods, so one index table per interface family
+//! serves every revision; each hook lists the revisions whose vtable actually has the slot.
+
+use super::com_vtable::{VtableHookEntry, install_vtable_hooks, interface_revision, vtable_hook};
+use super::detach::ActiveCall;
+use super::surface_capture::on_present;
+use crate::decode::flags::{DDSCAPS_FLAGS, format_flags};
+use crate::decode::surface_desc::{PIXEL_FORMAT_SIZE, parse_pixel_format};
+use crate::{
+    CO_CREATE_INSTANCE_EX_HOOK, CO_CREATE_INSTANCE_HOOK, DIRECTDRAW_CREATE_CLIPPER_HOOK,
+    DIRECTDRAW_CREATE_EX_HOOK, DIRECTDRAW_CREATE_HOOK, DIRECTDRAW_ENUMERATE_A_HOOK,
+    DIRECTDRAW_ENUMERATE_EX_A_HOOK, DIRECTDRAW_ENUMERATE_EX_W_HOOK, DIRECTDRAW_ENUMERATE_W_HOOK,
+    Guid, IID_IDIRECTDRAW, IID_IDIRECTDRAW2, IID_IDIRECTDRAW4, IID_IDIRECTDRAW7,
+    describe_blt_fx_ptr, describe_dd_surface_desc, describe_guid_ptr, describe_rect_ptr,
+    describe_u32_out, guid_ptr_matches, hex_bytes, hresult_result, hresult_succeeded, make_event,
+    read_dd_surface_desc, read_out_ptr, send_event, try_read_bytes, try_read_u32_at,
+};
+#[cfg(target_pointer_width = "32")]
+use crate::{
+    DIRECTDRAW_PATCHED_INTERFACES, DIRECTDRAWSURFACE_PATCHED_INTERFACES,
+    try_patch_com_vtable_for_usage,
+};
+use std::ffi::c_void;
+use windows_sys::Win32::Foundation::RECT;
+use windows_sys::Win32::System::LibraryLoader::GetModuleHandleA;
+
+/// Revision used when an object came back as a plain `IUnknown`; only `QueryInterface` is safe
+/// to hook until it is queried for a real DirectDraw interface.
+pub const DIRECTDRAW_REVISION_UNKNOWN: u32 = 0;
+
+// IDirectDraw vtable layout (IDirectDraw2 appends 23, IDirectDraw4 24-27, IDirectDraw7 28-29).
+pub const DD_METHOD_QUERY_INTERFACE_INDEX: usize = 0;
+const DD_METHOD_ADD_REF_INDEX: usize = 1;
+pub const DD_METHOD_RELEASE_INDEX: usize = 2;
+const DD_METHOD_COMPACT_INDEX: usize = 3;
+const DD_METHOD_CREATE_CLIPPER_INDEX: usize = 4;
+const DD_METHOD_CREATE_PALETTE_INDEX: usize = 5;
+const DD_METHOD_CREATE_SURFACE_INDEX: usize = 6;
+const DD_METHOD_DUPLICATE_SURFACE_INDEX: usize = 7;
+const DD_METHOD_ENUM_DISPLAY_MODES_INDEX: usize = 8;
+const DD_METHOD_ENUM_SURFACES_INDEX: usize = 9;
+const DD_METHOD_FLIP_TO_GDI_SURFACE_INDEX: usize = 10;
+const DD_METHOD_GET_CAPS_INDEX: usize = 11;
+const DD_METHOD_GET_DISPLAY_MODE_INDEX: usize = 12;
+const DD_METHOD_GET_FOURCC_CODES_INDEX: usize = 13;
+const DD_METHOD_GET_GDI_SURFACE_INDEX: usize = 14;
+const DD_METHOD_GET_MONITOR_FREQUENCY_INDEX: usize = 15;
+const DD_METHOD_GET_SCAN_LINE_INDEX: usize = 16;
+const DD_METHOD_GET_VERTICAL_BLANK_STATUS_INDEX: usize = 17;
+const DD_METHOD_INITIALIZE_INDEX: usize = 18;
+const DD_METHOD_RESTORE_DISPLAY_MODE_INDEX: usize = 19;
+const DD_METHOD_SET_COOPERATIVE_LEVEL_INDEX: usize = 20;
+const DD_METHOD_SET_DISPLAY_MODE_INDEX: usize = 21;
+const DD_METHOD_WAIT_FOR_VERTICAL_BLANK_INDEX: usize = 22;
+const DD_METHOD_GET_AVAILABLE_VID_MEM_INDEX: usize = 23;
+const DD_METHOD_GET_SURFACE_FROM_DC_INDEX: usize = 24;
+const DD_METHOD_RESTORE_ALL_SURFACES_INDEX: usize = 25;
+const DD_METHOD_TEST_COOPERATIVE_LEVEL_INDEX: usize = 26;
+const DD_METHOD_GET_DEVICE_IDENTIFIER_INDEX: usize = 27;
+const DD_METHOD_START_MODE_TEST_INDEX: usize = 28;
+const DD_METHOD_EVALUATE_MODE_INDEX: usize = 29;
+
+// IDirectDrawSurface vtable layout (IDirectDrawSurface2 appends 36-38, IDirectDrawSurface3 39,
+// IDirectDrawSurface4 40-44, IDirectDrawSurface7 45-48).
+const DDS_METHOD_QUERY_INTERFACE_INDEX: usize = 0;
+const DDS_METHOD_ADD_REF_INDEX: usize = 1;
+const DDS_METHOD_RELEASE_INDEX: usize = 2;
+const DDS_METHOD_ADD_ATTACHED_SURFACE_INDEX: usize = 3;
+const DDS_METHOD_ADD_OVERLAY_DIRTY_RECT_INDEX: usize = 4;
+const DDS_METHOD_BLT_INDEX: usize = 5;
+const DDS_METHOD_BLT_BATCH_INDEX: usize = 6;
+const DDS_METHOD_BLTFAST_INDEX: usize = 7;
+const DDS_METHOD_DELETE_ATTACHED_SURFACE_INDEX: usize = 8;
+const DDS_METHOD_ENUM_ATTACHED_SURFACES_INDEX: usize = 9;
+const DDS_METHOD_ENUM_OVERLAY_Z_ORDERS_INDEX: usize = 10;
+const DDS_METHOD_FLIP_INDEX: usize = 11;
+const DDS_METHOD_GETATTACHED_INDEX: usize = 12;
+const DDS_METHOD_GET_BLT_STATUS_INDEX: usize = 13;
+const DDS_METHOD_GET_CAPS_INDEX: usize = 14;
+const DDS_METHOD_GET_CLIPPER_INDEX: usize = 15;
+const DDS_METHOD_GET_COLOR_KEY_INDEX: usize = 16;
+const DDS_METHOD_GETDC_INDEX: usize = 17;
+const DDS_METHOD_GET_FLIP_STATUS_INDEX: usize = 18;
+const DDS_METHOD_GET_OVERLAY_POSITION_INDEX: usize = 19;
+const DDS_METHOD_GET_PALETTE_INDEX: usize = 20;
+const DDS_METHOD_GET_PIXEL_FORMAT_INDEX: usize = 21;
+const DDS_METHOD_GETDESC_INDEX: usize = 22;
+const DDS_METHOD_INITIALIZE_INDEX: usize = 23;
+const DDS_METHOD_ISLOST_INDEX: usize = 24;
+const DDS_METHOD_LOCK_INDEX: usize = 25;
+const DDS_METHOD_RELEASEDC_INDEX: usize = 26;
+const DDS_METHOD_RESTORE_INDEX: usize = 27;
+const DDS_METHOD_SETCLIPPER_INDEX: usize = 28;
+const DDS_METHOD_SET_COLOR_KEY_INDEX: usize = 29;
+const DDS_METHOD_SET_OVERLAY_POSITION_INDEX: usize = 30;
+const DDS_METHOD_SETPALETTE_INDEX: usize = 31;
+const DDS_METHOD_UNLOCK_INDEX: usize = 32;
+const DDS_METHOD_UPDATE_OVERLAY_INDEX: usize = 33;
+const DDS_METHOD_UPDATE_OVERLAY_DISPLAY_INDEX: usize = 34;
+const DDS_METHOD_UPDATE_OVERLAY_Z_ORDER_INDEX: usize = 35;
+const DDS_METHOD_GET_DD_INTERFACE_INDEX: usize = 36;
+const DDS_METHOD_PAGE_LOCK_INDEX: usize = 37;
+const DDS_METHOD_PAGE_UNLOCK_INDEX: usize = 38;
+const DDS_METHOD_SET_SURFACE_DESC_INDEX: usize = 39;
+const DDS_METHOD_SET_PRIVATE_DATA_INDEX: usize = 40;
+const DDS_METHOD_GET_PRIVATE_DATA_INDEX: usize = 41;
+const DDS_METHOD_FREE_PRIVATE_DATA_INDEX: usize = 42;
+const DDS_METHOD_GET_UNIQUENESS_VALUE_INDEX: usize = 43;
+const DDS_METHOD_CHANGE_UNIQUENESS_VALUE_INDEX: usize = 44;
+const DDS_METHOD_SET_PRIORITY_INDEX: usize = 45;
+const DDS_METHOD_GET_PRIORITY_INDEX: usize = 46;
+const DDS_METHOD_SET_LOD_INDEX: usize = 47;
+const DDS_METHOD_GET_LOD_INDEX: usize = 48;
+
+// IDirectDrawPalette has a single revision.
+const DIRECTDRAWPALETTE_REVISION: u32 = 1;
+const DDP_METHOD_ADD_REF_INDEX: usize = 1;
+const DDP_METHOD_RELEASE_INDEX: usize = 2;
+const DDP_METHOD_GET_ENTRIES_INDEX: usize = 4;
+const DDP_METHOD_SET_ENTRIES_INDEX: usize = 6;
+
+const DDPCAPS_4BIT: u32 = 0x0000_0001;
+const DDPCAPS_8BITENTRIES: u32 = 0x0000_0002;
+const DDPCAPS_8BIT: u32 = 0x0000_0004;
+const DDPCAPS_1BIT: u32 = 0x0000_0100;
+const DDPCAPS_2BIT: u32 = 0x0000_0200;
+/// Size of a PALETTEENTRY: red, green, blue and flags bytes.
+const PALETTE_ENTRY_SIZE: usize = 4;
+
+const IID_IDIRECTDRAWSURFACE: Guid = Guid {
+    data1: 0x6C14DB81,
+    data2: 0xA733,
+    data3: 0x11CE,
+    data4: [0xA5, 0x21, 0x00, 0x20, 0xAF, 0x0B, 0xE5, 0x60],
+};
+const IID_IDIRECTDRAWSURFACE2: Guid = Guid {
+    data1: 0x57805885,
+    data2: 0x6EEC,
+    data3: 0x11CF,
+    data4: [0x94, 0x41, 0xA8, 0x23, 0x03, 0xC1, 0x0E, 0x27],
+};
+const IID_IDIRECTDRAWSURFACE3: Guid = Guid {
+    data1: 0xDA044E00,
+    data2: 0x69B2,
+    data3: 0x11D0,
+    data4: [0xA1, 0xD5, 0x00, 0xAA, 0x00, 0xB8, 0xDF, 0xBB],
+};
+const IID_IDIRECTDRAWSURFACE4: Guid = Guid {
+    data1: 0x0B2B8630,
+    data2: 0xAD35,
+    data3: 0x11D0,
+    data4: [0x8E, 0xA6, 0x00, 0x60, 0x97, 0x97, 0xEA, 0x5B],
+};
+const IID_IDIRECTDRAWSURFACE7: Guid = Guid {
+    data1: 0x06675A80,
+    data2: 0x3B9B,
+    data3: 0x11D2,
+    data4: [0xB9, 0x2F, 0x00, 0x60, 0x97, 0x97, 0xEA, 0x5B],
+};
+
+/// Cap on the modes listed in an `EnumDisplayModes` event; the count is always reported.
+const ENUMERATED_MODES_LIMIT: usize = 64;
+
+type FnDdEnumModesCallback = unsafe extern "system" fn(*mut c_void, *mut c_void) -> i32;
+type FnDdEnumDisplayModes = unsafe extern "system" fn(
+    *mut c_void,
+    u32,
+    *mut c_void,
+    *mut c_void,
+    Option<FnDdEnumModesCallback>,
+) -> i32;
+
+static DIRECTDRAW_VTABLE_HOOKS: &[&dyn VtableHookEntry] = &[
+    &DD_QUERY_INTERFACE,
+    &DD_ADD_REF,
+    &DD_RELEASE,
+    &DD_COMPACT,
+    &DD_CREATE_CLIPPER,
+    &DD_CREATE_PALETTE,
+    &DD_CREATE_SURFACE,
+    &DD_DUPLICATE_SURFACE,
+    &DD_ENUM_DISPLAY_MODES,
+    &DD_ENUM_SURFACES,
+    &DD_FLIP_TO_GDI_SURFACE,
+    &DD_GET_CAPS,
+    &DD_GET_DISPLAY_MODE,
+    &DD_GET_FOURCC_CODES,
+    &DD_GET_GDI_SURFACE,
+    &DD_GET_MONITOR_FREQUENCY,
+    &DD_GET_SCAN_LINE,
+    &DD_GET_VERTICAL_BLANK_STATUS,
+    &DD_INITIALIZE,
+    &DD_RESTORE_DISPLAY_MODE,
+    &DD_SET_COOPERATIVE_LEVEL,
+    &DD_SET_DISPLAY_MODE,
+    &DD_SET_DISPLAY_MODE_EX,
+    &DD_WAIT_FOR_VBLANK,
+    &DD_GET_AVAILABLE_VID_MEM,
+    &DD_GET_SURFACE_FROM_DC,
+    &DD_RESTORE_ALL_SURFACES,
+    &DD_TEST_COOPERATIVE_LEVEL,
+    &DD_GET_DEVICE_IDENTIFIER,
+    &DD_START_MODE_TEST,
+    &DD_EVALUATE_MODE,
+];
+static DIRECTDRAWPALETTE_VTABLE_HOOKS: &[&dyn VtableHookEntry] = &[
+    &DD_PALETTE_ADD_REF,
+    &DD_PALETTE_RELEASE,
+    &DD_PALETTE_GET_ENTRIES,
+    &DD_PALETTE_SET_ENTRIES,
+];
+static DIRECTDRAWSURFACE_VTABLE_HOOKS: &[&dyn VtableHookEntry] = &[
+    &DD_SURFACE_QUERY_INTERFACE,
+    &DD_SURFACE_ADD_REF,
+    &DD_SURFACE_RELEASE,
+    &DD_SURFACE_ADD_ATTACHED_SURFACE,
+    &DD_SURFACE_ADD_OVERLAY_DIRTY_RECT,
+    &DD_SURFACE_BLT,
+    &DD_SURFACE_BLT_BATCH,
+    &DD_SURFACE_BLTFAST,
+    &DD_SURFACE_DELETE_ATTACHED_SURFACE,
+    &DD_SURFACE_ENUM_ATTACHED_SURFACES,
+    &DD_SURFACE_ENUM_OVERLAY_Z_ORDERS,
+    &DD_SURFACE_FLIP,
+    &DD_SURFACE_GETATTACHED,
+    &DD_SURFACE_GET_BLT_STATUS,
+    &DD_SURFACE_GET_CAPS,
+    &DD_SURFACE_GET_CLIPPER,
+    &DD_SURFACE_GET_COLOR_KEY,
+    &DD_SURFACE_GETDC,
+    &DD_SURFACE_GET_FLIP_STATUS,
+    &DD_SURFACE_GET_OVERLAY_POSITION,
+    &DD_SURFACE_GET_PALETTE,
+    &DD_SURFACE_GET_PIXEL_FORMAT,
+    &DD_SURFACE_GETDESC,
+    &DD_SURFACE_INITIALIZE,
+    &DD_SURFACE_ISLOST,
+    &DD_SURFACE_LOCK,
+    &DD_SURFACE_RELEASEDC,
+    &DD_SURFACE_RESTORE,
+    &DD_SURFACE_SETCLIPPER,
+    &DD_SURFACE_SET_COLOR_KEY,
+    &DD_SURFACE_SET_OVERLAY_POSITION,
+    &DD_SURFACE_SETPALETTE,
+    &DD_SURFACE_UNLOCK,
+    &DD_SURFACE_UPDATE_OVERLAY,
+    &DD_SURFACE_UPDATE_OVERLAY_DISPLAY,
+    &DD_SURFACE_UPDATE_OVERLAY_Z_ORDER,
+    &DD_SURFACE_GET_DD_INTERFACE,
+    &DD_SURFACE_PAGE_LOCK,
+    &DD_SURFACE_PAGE_UNLOCK,
+    &DD_SURFACE_SET_SURFACE_DESC,
+    &DD_SURFACE_SET_PRIVATE_DATA,
+    &DD_SURFACE_GET_PRIVATE_DATA,
+    &DD_SURFACE_FREE_PRIVATE_DATA,
+    &DD_SURFACE_GET_UNIQUENESS_VALUE,
+    &DD_SURFACE_CHANGE_UNIQUENESS_VALUE,
+    &DD_SURFACE_SET_PRIORITY,
+    &DD_SURFACE_GET_PRIORITY,
+    &DD_SURFACE_SET_LOD,
+    &DD_SURFACE_GET_LOD,
+];
+
+pub fn try_install_directdraw_object_hooks(instance: *mut c_void, revision: u32, source: &str) {
+    install_vtable_hooks(
+        DIRECTDRAW_VTABLE_HOOKS,
+        instance,
+        revision,
+        source,
+        "DirectDrawHookInstall",
+    );
+
+    #[cfg(target_pointer_width = "32")]
+    try_patch_com_vtable_for_usage(instance, 1, 40, &DIRECTDRAW_PATCHED_INTERFACES);
+}
+
+pub fn try_install_directdraw_surface_hooks(surface: *mut c_void, revision: u32, source: &str) {
+    install_vtable_hooks(
+        DIRECTDRAWSURFACE_VTABLE_HOOKS,
+        surface,
+        revision,
+        source,
+        "DirectDrawHookInstall",
+    );
+
+    #[cfg(target_pointer_width = "32")]
+    try_patch_com_vtable_for_usage(surface, 2, 64, &DIRECTDRAWSURFACE_PATCHED_INTERFACES);
+}
+
+pub fn try_install_directdraw_palette_hooks(palette: *mut c_void, source: &str) {
+    if palette.is_null() {
+        return;
+    }
+    install_vtable_hooks(
+        DIRECTDRAWPALETTE_VTABLE_HOOKS,
+        palette,
+        DIRECTDRAWPALETTE_REVISION,
+        source,
+        "DirectDrawHookInstall",
+    );
+}
+
+pub fn directdraw_vtable_hooks_installed() -> bool {
+    DD_CREATE_SURFACE.is_installed()
+}
+
+/// Maps an `IDirectDraw*` IID to its interface revision (1, 2, 4 or 7).
+pub fn directdraw_interface_revision(iid: *const c_void) -> Option<u32> {
+    [
+        (&IID_IDIRECTDRAW, 1),
+        (&IID_IDIRECTDRAW2, 2),
+        (&IID_IDIRECTDRAW4, 4),
+        (&IID_IDIRECTDRAW7, 7),
+    ]
+    .into_iter()
+    .find_map(|(expected, revision)| guid_ptr_matches(iid, expected).then_some(revision))
+}
+
+/// Maps an `IDirectDrawSurface*` IID to its interface revision (1, 2, 3, 4 or 7).
+fn directdraw_surface_interface_revision(iid: *const c_void) -> Option<u32> {
+    [
+        (&IID_IDIRECTDRAWSURFACE, 1),
+        (&IID_IDIRECTDRAWSURFACE2, 2),
+        (&IID_IDIRECTDRAWSURFACE3, 3),
+        (&IID_IDIRECTDRAWSURFACE4, 4),
+        (&IID_IDIRECTDRAWSURFACE7, 7),
+    ]
+    .into_iter()
+    .find_map(|(expected, revision)| guid_ptr_matches(iid, expected).then_some(revision))
+}
+
+/// Surfaces created through IDirectDraw4/7 are IDirectDrawSurface4/7; older interfaces hand out
+/// the original IDirectDrawSurface.
+fn created_surface_revision(directdraw: *mut c_void) -> u32 {
+    match interface_revision(directdraw) {
+        Some(7) => 7,
+        Some(4) => 4,
+        _ => 1,
+    }
+}
+
+fn install_created_surface_hooks(directdraw: *mut c_void, surface: *mut c_void, source: &str) {
+    if !surface.is_null() {
+        try_install_directdraw_surface_hooks(surface, created_surface_revision(directdraw), source);
+    }
+}
+
+pub fn emit_directdraw_hook_status() {
+    let ddraw_loaded = unsafe { GetModuleHandleA(b"ddraw.dll\0".as_ptr()) };
+    let ddrawex_loaded = unsafe { GetModuleHandleA(b"ddrawex.dll\0".as_ptr()) };
+    let ole32_loaded = unsafe { GetModuleHandleA(b"ole32.dll\0".as_ptr()) };
+    let summary = format!(
+        "ddraw_loaded={} ddrawex_loaded={} ole32_loaded={} create={} create_ex={} clipper={} create_surface={}",
+        !ddraw_loaded.is_null(),
+        !ddrawex_loaded.is_null(),
+        !ole32_loaded.is_null(),
+        DIRECTDRAW_CREATE_HOOK.get().is_some(),
+        DIRECTDRAW_CREATE_EX_HOOK.get().is_some(),
+        DIRECTDRAW_CREATE_CLIPPER_HOOK.get().is_some(),
+        DD_CREATE_SURFACE.is_installed(),
+    );
+    let result = format!(
+        "enum_a={} enum_w={} enum_ex_a={} enum_ex_w={} cocreate={} cocreate_ex={} set_coop={} set_mode={} surf_blt={} surf_flip={} surf_dc={} surf_lock={}",
+        DIRECTDRAW_ENUMERATE_A_HOOK.get().is_some(),
+        DIRECTDRAW_ENUMERATE_W_HOOK.get().is_some(),
+        DIRECTDRAW_ENUMERATE_EX_A_HOOK.get().is_some(),
+        DIRECTDRAW_ENUMERATE_EX_W_HOOK.get().is_some(),
+        CO_CREATE_INSTANCE_HOOK.get().is_some(),
+        CO_CREATE_INSTANCE_EX_HOOK.get().is_some(),
+        DD_SET_COOPERATIVE_LEVEL.is_installed(),
+        DD_SET_DISPLAY_MODE.is_installed() || DD_SET_DISPLAY_MODE_EX.is_installed(),
+        DD_SURFACE_BLT.is_installed() || DD_SURFACE_BLTFAST.is_installed(),
+        DD_SURFACE_FLIP.is_installed(),
+        DD_SURFACE_GETDC.is_installed() || DD_SURFACE_RELEASEDC.is_installed(),
+        DD_SURFACE_LOCK.is_installed() || DD_SURFACE_UNLOCK.is_installed(),
+    );
+    send_event(make_event("DirectDrawHookStatus", summary, result));
+}
+
+fn describe_dd_caps(caps: *mut c_void) -> String {
+    if caps.is_null() {
+        return "null".to_owned();
+    }
+
+    // DDCAPS: dwSize, dwCaps, dwCaps2, ..., dwVidMemTotal at 60, dwVidMemFree at 64.
+    let size = try_read_u32_at(caps, 0);
+    let caps1 = try_read_u32_at(caps, 4);
+    let caps2 = try_read_u32_at(caps, 8);
+    let vid_mem_total = try_read_u32_at(caps, 60);
+    let vid_mem_free = try_read_u32_at(caps, 64);
+    match (size, caps1, caps2, vid_mem_total, vid_mem_free) {
+        (Some(size), Some(caps1), Some(caps2), Some(total), Some(free)) => format!(
+            "size={size} caps=0x{caps1:08X} caps2=0x{caps2:08X} vidmem_total={total} vidmem_free={free}"
+        ),
+        _ => "unreadable".to_owned(),
+    }
+}
+
+fn describe_dd_color_key(color_key: *mut c_void) -> String {
+    if color_key.is_null() {
+        return "null".to_owned();
+    }
+
+    match (try_read_u32_at(color_key, 0), try_read_u32_at(color_key, 4)) {
+        (Some(low), Some(high)) => format!("low=0x{low:08X} high=0x{high:08X}"),
+        _ => "unreadable".to_owned(),
+    }
+}
+
+fn describe_dd_pixel_format(pixel_format: *mut c_void) -> String {
+    if pixel_format.is_null() {
+        return "null".to_owned();
+    }
+
+    match try_read_bytes(pixel_format, PIXEL_FORMAT_SIZE)
+        .as_deref()
+        .and_then(parse_pixel_format)
+    {
+        Some(pixel_format) => pixel_format.to_string(),
+        None => "unreadable".to_owned(),
+    }
+}
+
+fn describe_dd_scaps(caps: *mut c_void) -> String {
+    match try_read_u32_at(caps, 0) {
+        Some(caps) => format_flags(caps, DDSCAPS_FLAGS),
+        None if caps.is_null() => "null".to_owned(),
+        None => "unreadable".to_owned(),
+    }
+}
+
+/// Formats the DDSURFACEDESC(2) handed to an `EnumDisplayModes` callback as
+/// `widthxheightxbpp@refresh`.
+fn describe_display_mode(desc: *mut c_void) -> String {
+    match read_dd_surface_desc(desc) {
+        Some(desc) => format!(
+            "{}x{}x{}@{}",
+            desc.width, desc.height, desc.pixel_format.bit_count, desc.refresh_rate
+        ),
+        None => "unreadable".to_owned(),
+    }
+}
+
+struct EnumModesContext {
+    callback: Option<FnDdEnumModesCallback>,
+    context: *mut c_void,
+    count: usize,
+    modes: Vec<String>,
+}
+
+unsafe extern "system" fn dd_enum_modes_callback(desc: *mut c_void, context: *mut c_void) -> i32 {
+    let enumeration = unsafe { &mut *(context as *mut EnumModesContext) };
+    enumeration.count += 1;
+    if enumeration.modes.len() < ENUMERATED_MODES_LIMIT {
+        enumeration.modes.push(describe_display_mode(desc));
+    }
+
+    match enumeration.callback {
+        Some(callback) => unsafe { callback(desc, enumeration.context) },
+        None => 0,
+    }
+}
+
+static DD_ENUM_DISPLAY_MODES: super::com_vtable::VtableHook<FnDdEnumDisplayModes> =
+    super::com_vtable::VtableHook::new(
+        "IDirectDraw::EnumDisplayModes",
+        DD_METHOD_ENUM_DISPLAY_MODES_INDEX,
+        1..=7,
+        super::com_vtable::detour_slots!(dd_enum_display_modes_detour),
+    );
+
+/// Forwards the game's callback through `dd_enum_modes_callback` so the enumerated modes end up in
+/// the event alongside the filter that was passed in.
+unsafe extern "system" fn dd_enum_display_modes_detour<const SLOT: usize>(
+    this: *mut c_void,
+    flags: u32,
+    desc: *mut c_void,
+    context: *mut c_void,
+    callback: Option<FnDdEnumModesCallback>,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let mut enumeration = EnumModesContext {
+        callback,
+        context,
+        count: 0,
+        modes: Vec::new(),
+    };
+    let result = unsafe {
+        DD_ENUM_DISPLAY_MODES.original(SLOT).call(
+            this,
+            flags,
+            desc,
+            (&mut enumeration as *mut EnumModesContext) as *mut c_void,
+            callback.map(|_| dd_enum_modes_callback as FnDdEnumModesCallback),
+        )
+    };
+
+    let filter = if desc.is_null() {
+        "none".to_owned()
+    } else {
+        describe_dd_surface_desc(desc)
+    };
+    send_event(make_event(
+        "IDirectDraw::EnumDisplayModes",
+        format!(
+            "this={this:p} flags=0x{flags:08X} filter=({filter}) callback={:p} modes=[{}]",
+            callback.map_or(std::ptr::null(), |callback| callback as *const c_void),
+            enumeration.modes.join(", "),
+        ),
+        format!("{} count={}", hresult_result(result), enumeration.count),
+    ));
+    result
+}
+
+vtable_hook! {
+    DD_QUERY_INTERFACE = "IDirectDraw::QueryInterface" [DD_METHOD_QUERY_INTERFACE_INDEX, 0..=7],
+    fn dd_query_interface_detour(this, riid: *const c_void, out_object: *mut *mut c_void) -> i32,
+    |result| {
+        let out_value = read_out_ptr(out_object);
+        let revision = directdraw_interface_revision(riid);
+        let installed = revision.filter(|_| hresult_succeeded(result) && !out_value.is_null());
+        if let Some(revision) = installed {
+            try_install_directdraw_object_hooks(
+                out_value,
+                revision,
+                "IDirectDraw::QueryInterface",
+            );
+            emit_directdraw_hook_status();
+        }
+        (
+            format!(
+                "this={this:p} riid={} out_ptr={out_object:p} out={out_value:p} directdraw_iid={}",
+                describe_guid_ptr(riid),
+                revision.is_some()
+            ),
+            hresult_result(result),
+        )
+    }
+}
+
+// AddRef and Release return the new reference count. Release may free `this`, so only the
+// pointer value is reported.
+vtable_hook! {
+    DD_ADD_REF = "IDirectDraw::AddRef" [DD_METHOD_ADD_REF_INDEX, 0..=7],
+    fn dd_add_ref_detour(this) -> u32,
+    |result| (format!("this={this:p}"), format!("refcount={result}"))
+}
+
+vtable_hook! {
+    DD_RELEASE = "IDirectDraw::Release" [DD_METHOD_RELEASE_INDEX, 0..=7],
+    fn dd_release_detour(this) -> u32,
+    |result| (format!("this={this:p}"), format!("refcount={result}"))
+}
+
+vtable_hook! {
+    DD_COMPACT = "IDirectDraw::Compact" [DD_METHOD_COMPACT_INDEX, 1..=7],
+    fn dd_compact_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_CREATE_CLIPPER = "IDirectDraw::CreateClipper" [DD_METHOD_CREATE_CLIPPER_INDEX, 1..=7],
+    fn dd_create_clipper_detour(
+        this,
+        flags: u32,
+        clipper_out: *mut *mut c_void,
+        unknown_outer: *mut c_void,
+    ) -> i32,
+    |result| (
+        format!("this={this:p} flags=0x{flags:08X} outer_ptr={unknown_outer:p}"),
+        format!("{} clipper={:p}", hresult_result(result), read_out_ptr(clipper_out)),
+    )
+}
+
+vtable_hook! {
+    DD_CREATE_PALETTE = "IDirectDraw::CreatePalette" [DD_METHOD_CREATE_PALETTE_INDEX, 1..=7],
+    fn dd_create_palette_detour(
+        this,
+        flags: u32,
+        entries: *mut c_void,
+        palette_out: *mut *mut c_void,
+        unknown_outer: *mut c_void,
+    ) -> i32,
+    |result| {
+        let palette = read_out_ptr(palette_out);
+        if hresult_succeeded(result) {
+            try_install_directdraw_palette_hooks(palette, "IDirectDraw::CreatePalette");
+        }
+        (
+            format!(
+                "this={this:p} flags=0x{flags:08X} entries={entries:p} colors={} outer_ptr={unknown_outer:p}",
+                describe_created_palette(entries, flags)
+            ),
+            format!("{} palette={palette:p}", hresult_result(result)),
+        )
+    }
+}
+
+vtable_hook! {
+    DD_CREATE_SURFACE = "IDirectDraw::CreateSurface" [DD_METHOD_CREATE_SURFACE_INDEX, 1..=7],
+    fn dd_create_surface_detour(
+        this,
+        surface_desc: *mut c_void,
+        surface_out: *mut *mut c_void,
+        unknown_outer: *mut c_void,
+    ) -> i32,
+    |result| {
+        let out_value = read_out_ptr(surface_out);
+        if hresult_succeeded(result) {
+            install_created_surface_hooks(this, out_value, "IDirectDraw::CreateSurface");
+        }
+        (
+            format!(
+                "this={this:p} desc_ptr={surface_desc:p} ({}) out_ptr={surface_out:p} outer_ptr={unknown_outer:p}",
+                describe_dd_surface_desc(surface_desc),
+            ),
+            format!("{} surface={out_value:p}", hresult_result(result)),
+        )
+    }
+}
+
+vtable_hook! {
+    DD_DUPLICATE_SURFACE = "IDirectDraw::DuplicateSurface" [DD_METHOD_DUPLICATE_SURFACE_INDEX, 1..=7],
+    fn dd_duplicate_surface_detour(
+        this,
+        source_surface: *mut c_void,
+        surface_out: *mut *mut c_void,
+    ) -> i32,
+    |result| {
+        let out_value = read_out_ptr(surface_out);
+        if hresult_succeeded(result) {
+            install_created_surface_hooks(this, out_value, "IDirectDraw::DuplicateSurface");
+        }
+        (
+            format!("this={this:p} source={source_surface:p}"),
+            format!("{} surface={out_value:p}", hresult_result(result)),
+        )
+    }
+}
+
+vtable_hook! {
+    DD_ENUM_SURFACES = "IDirectDraw::EnumSurfaces" [DD_METHOD_ENUM_SURFACES_INDEX, 1..=7],
+    fn dd_enum_surfaces_detour(
+        this,
+        flags: u32,
+        desc: *mut c_void,
+        context: *mut c_void,
+        callback: *mut c_void,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} flags=0x{flags:08X} desc_ptr={desc:p} context={context:p} callback={callback:p}"
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_FLIP_TO_GDI_SURFACE = "IDirectDraw::FlipToGDISurface" [DD_METHOD_FLIP_TO_GDI_SURFACE_INDEX, 1..=7],
+    fn dd_flip_to_gdi_surface_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_GET_CAPS = "IDirectDraw::GetCaps" [DD_METHOD_GET_CAPS_INDEX, 1..=7],
+    fn dd_get_caps_detour(this, driver_caps: *mut c_void, hel_caps: *mut c_void) -> i32,
+    |result| (
+        format!(
+            "this={this:p} driver=({}) hel=({})",
+            describe_dd_caps(driver_caps),
+            describe_dd_caps(hel_caps),
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_GET_DISPLAY_MODE = "IDirectDraw::GetDisplayMode" [DD_METHOD_GET_DISPLAY_MODE_INDEX, 1..=7],
+    fn dd_get_display_mode_detour(this, desc: *mut c_void) -> i32,
+    |result| (
+        format!("this={this:p} desc_ptr={desc:p} ({})", describe_dd_surface_desc(desc)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_GET_FOURCC_CODES = "IDirectDraw::GetFourCCCodes" [DD_METHOD_GET_FOURCC_CODES_INDEX, 1..=7],
+    fn dd_get_fourcc_codes_detour(this, count: *mut u32, codes: *mut u32) -> i32,
+    |result| (
+        format!("this={this:p} count={} codes={codes:p}", describe_u32_out(count)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_GET_GDI_SURFACE = "IDirectDraw::GetGDISurface" [DD_METHOD_GET_GDI_SURFACE_INDEX, 1..=7],
+    fn dd_get_gdi_surface_detour(this, surface_out: *mut *mut c_void) -> i32,
+    |result| {
+        let out_value = read_out_ptr(surface_out);
+        if hresult_succeeded(result) {
+            install_created_surface_hooks(this, out_value, "IDirectDraw::GetGDISurface");
+        }
+        (
+            format!("this={this:p}"),
+            format!("{} surface={out_value:p}", hresult_result(result)),
+        )
+    }
+}
+
+vtable_hook! {
+    DD_GET_MONITOR_FREQUENCY = "IDirectDraw::GetMonitorFrequency" [DD_METHOD_GET_MONITOR_FREQUENCY_INDEX, 1..=7],
+    fn dd_get_monitor_frequency_detour(this, frequency: *mut u32) -> i32,
+    |result| (
+        format!("this={this:p} frequency={}", describe_u32_out(frequency)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_GET_SCAN_LINE = "IDirectDraw::GetScanLine" [DD_METHOD_GET_SCAN_LINE_INDEX, 1..=7],
+    fn dd_get_scan_line_detour(this, scan_line: *mut u32) -> i32,
+    |result| (
+        format!("this={this:p} scan_line={}", describe_u32_out(scan_line)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_GET_VERTICAL_BLANK_STATUS = "IDirectDraw::GetVerticalBlankStatus" [DD_METHOD_GET_VERTICAL_BLANK_STATUS_INDEX, 1..=7],
+    fn dd_get_vertical_blank_status_detour(this, in_vblank: *mut i32) -> i32,
+    |result| (
+        format!("this={this:p} in_vblank={}", describe_u32_out(in_vblank as *mut u32)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_INITIALIZE = "IDirectDraw::Initialize" [DD_METHOD_INITIALIZE_INDEX, 1..=7],
+    fn dd_initialize_detour(this, guid: *const c_void) -> i32,
+    |result| (
+        format!("this={this:p} guid={}", describe_guid_ptr(guid)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_RESTORE_DISPLAY_MODE = "IDirectDraw::RestoreDisplayMode" [DD_METHOD_RESTORE_DISPLAY_MODE_INDEX, 1..=7],
+    fn dd_restore_display_mode_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SET_COOPERATIVE_LEVEL = "IDirectDraw::SetCooperativeLevel" [DD_METHOD_SET_COOPERATIVE_LEVEL_INDEX, 1..=7],
+    fn dd_set_cooperative_level_detour(this, hwnd: isize, flags: u32) -> i32,
+    |result| (
+        format!("this={this:p} hwnd=0x{hwnd:016X} flags=0x{flags:08X}"),
+        hresult_result(result),
+    )
+}
+
+// IDirectDraw::SetDisplayMode takes three arguments; IDirectDraw2 and later add refresh rate and
+// flags in the same slot.
+vtable_hook! {
+    DD_SET_DISPLAY_MODE = "IDirectDraw::SetDisplayMode" [DD_METHOD_SET_DISPLAY_MODE_INDEX, 1..=1],
+    fn dd_set_display_mode_detour(this, width: u32, height: u32, bpp: u32) -> i32,
+    |result| (
+        format!("this={this:p} width={width} height={height} bpp={bpp}"),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SET_DISPLAY_MODE_EX = "IDirectDraw7::SetDisplayMode" [DD_METHOD_SET_DISPLAY_MODE_INDEX, 2..=7],
+    fn dd_set_display_mode_ex_detour(
+        this,
+        width: u32,
+        height: u32,
+        bpp: u32,
+        refresh_rate: u32,
+        flags: u32,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} width={width} height={height} bpp={bpp} refresh={refresh_rate} flags=0x{flags:08X}"
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_WAIT_FOR_VBLANK = "IDirectDraw::WaitForVerticalBlank" [DD_METHOD_WAIT_FOR_VERTICAL_BLANK_INDEX, 1..=7],
+    fn dd_wait_for_vblank_detour(this, flags: u32, event: *mut c_void) -> i32,
+    |result| (
+        format!("this={this:p} flags=0x{flags:08X} event={event:p}"),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_GET_AVAILABLE_VID_MEM = "IDirectDraw::GetAvailableVidMem" [DD_METHOD_GET_AVAILABLE_VID_MEM_INDEX, 2..=7],
+    fn dd_get_available_vid_mem_detour(
+        this,
+        caps: *mut c_void,
+        total: *mut u32,
+        free: *mut u32,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} caps={} total={} free={}",
+            describe_dd_scaps(caps),
+            describe_u32_out(total),
+            describe_u32_out(free),
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_GET_SURFACE_FROM_DC = "IDirectDraw::GetSurfaceFromDC" [DD_METHOD_GET_SURFACE_FROM_DC_INDEX, 4..=7],
+    fn dd_get_surface_from_dc_detour(this, hdc: isize, surface_out: *mut *mut c_void) -> i32,
+    |result| {
+        let out_value = read_out_ptr(surface_out);
+        if hresult_succeeded(result) {
+            install_created_surface_hooks(this, out_value, "IDirectDraw::GetSurfaceFromDC");
+        }
+        (
+            format!("this={this:p} hdc=0x{:016X}", hdc as usize),
+            format!("{} surface={out_value:p}", hresult_result(result)),
+        )
+    }
+}
+
+vtable_hook! {
+    DD_RESTORE_ALL_SURFACES = "IDirectDraw::RestoreAllSurfaces" [DD_METHOD_RESTORE_ALL_SURFACES_INDEX, 4..=7],
+    fn dd_restore_all_surfaces_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_TEST_COOPERATIVE_LEVEL = "IDirectDraw::TestCooperativeLevel" [DD_METHOD_TEST_COOPERATIVE_LEVEL_INDEX, 4..=7],
+    fn dd_test_cooperative_level_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_GET_DEVICE_IDENTIFIER = "IDirectDraw::GetDeviceIdentifier" [DD_METHOD_GET_DEVICE_IDENTIFIER_INDEX, 4..=7],
+    fn dd_get_device_identifier_detour(this, identifier: *mut c_void, flags: u32) -> i32,
+    |result| (
+        // DDDEVICEIDENTIFIER(2) starts with two 512-byte strings: driver and description.
+        format!(
+            "this={this:p} flags=0x{flags:08X} driver={} description={}",
+            crate::read_c_string_lossy(identifier as *const u8),
+            crate::read_c_string_lossy((identifier as *const u8).wrapping_add(512)),
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_START_MODE_TEST = "IDirectDraw::StartModeTest" [DD_METHOD_START_MODE_TEST_INDEX, 7..=7],
+    fn dd_start_mode_test_detour(this, modes: *mut c_void, count: u32, flags: u32) -> i32,
+    |result| (
+        format!("this={this:p} modes={modes:p} count={count} flags=0x{flags:08X}"),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_EVALUATE_MODE = "IDirectDraw::EvaluateMode" [DD_METHOD_EVALUATE_MODE_INDEX, 7..=7],
+    fn dd_evaluate_mode_detour(this, flags: u32, timeout: *mut u32) -> i32,
+    |result| (
+        format!("this={this:p} flags=0x{flags:08X} timeout={}", describe_u32_out(timeout)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_QUERY_INTERFACE = "IDirectDrawSurface::QueryInterface" [DDS_METHOD_QUERY_INTERFACE_INDEX, 1..=7],
+    fn dd_surface_query_interface_detour(
+        this,
+        riid: *const c_void,
+        out_object: *mut *mut c_void,
+    ) -> i32,
+    |result| {
+        let out_value = read_out_ptr(out_object);
+        let revision = directdraw_surface_interface_revision(riid);
+        let installed = revision.filter(|_| hresult_succeeded(result) && !out_value.is_null());
+        if let Some(revision) = installed {
+            try_install_directdraw_surface_hooks(
+                out_value,
+                revision,
+                "IDirectDrawSurface::QueryInterface",
+            );
+        }
+        (
+            format!(
+                "this={this:p} riid={} out={out_value:p} surface_rev={}",
+                describe_guid_ptr(riid),
+                revision.map_or_else(|| "-".to_owned(), |revision| revision.to_string())
+            ),
+            hresult_result(result),
+        )
+    }
+}
+
+vtable_hook! {
+    DD_SURFACE_ADD_REF = "IDirectDrawSurface::AddRef" [DDS_METHOD_ADD_REF_INDEX, 1..=7],
+    fn dd_surface_add_ref_detour(this) -> u32,
+    |result| (format!("this={this:p}"), format!("refcount={result}"))
+}
+
+vtable_hook! {
+    DD_SURFACE_RELEASE = "IDirectDrawSurface::Release" [DDS_METHOD_RELEASE_INDEX, 1..=7],
+    fn dd_surface_release_detour(this) -> u32,
+    |result| (format!("this={this:p}"), format!("refcount={result}"))
+}
+
+vtable_hook! {
+    DD_SURFACE_ADD_ATTACHED_SURFACE = "IDirectDrawSurface::AddAttachedSurface" [DDS_METHOD_ADD_ATTACHED_SURFACE_INDEX, 1..=7],
+    fn dd_surface_add_attached_surface_detour(this, attached: *mut c_void) -> i32,
+    |result| (format!("this={this:p} attached={attached:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_ADD_OVERLAY_DIRTY_RECT = "IDirectDrawSurface::AddOverlayDirtyRect" [DDS_METHOD_ADD_OVERLAY_DIRTY_RECT_INDEX, 1..=7],
+    fn dd_surface_add_overlay_dirty_rect_detour(this, rect: *mut RECT) -> i32,
+    |result| (
+        format!("this={this:p} rect={}", describe_rect_ptr(rect)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_BLT = "IDirectDrawSurface::Blt" [DDS_METHOD_BLT_INDEX, 1..=7],
+    fn dd_surface_blt_detour(
+        this,
+        dst_rect: *mut RECT,
+        src_surface: *mut c_void,
+        src_rect: *mut RECT,
+        flags: u32,
+        fx: *mut c_void,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} dst_rect={} src_surface={src_surface:p} src_rect={} flags=0x{flags:08X} fx=[{}]",
+            describe_rect_ptr(dst_rect),
+            describe_rect_ptr(src_rect),
+            describe_blt_fx_ptr(fx)
+        ),
+        {
+            if result >= 0 {
+                on_present(this, "Blt");
+            }
+            hresult_result(result)
+        },
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_BLT_BATCH = "IDirectDrawSurface::BltBatch" [DDS_METHOD_BLT_BATCH_INDEX, 1..=7],
+    fn dd_surface_blt_batch_detour(this, batch: *mut c_void, count: u32, flags: u32) -> i32,
+    |result| (
+        format!("this={this:p} batch={batch:p} count={count} flags=0x{flags:08X}"),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_BLTFAST = "IDirectDrawSurface::BltFast" [DDS_METHOD_BLTFAST_INDEX, 1..=7],
+    fn dd_surface_bltfast_detour(
+        this,
+        x: u32,
+        y: u32,
+        src_surface: *mut c_void,
+        src_rect: *mut RECT,
+        trans: u32,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} x={x} y={y} src_surface={src_surface:p} src_rect={} trans=0x{trans:08X}",
+            describe_rect_ptr(src_rect)
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_DELETE_ATTACHED_SURFACE = "IDirectDrawSurface::DeleteAttachedSurface" [DDS_METHOD_DELETE_ATTACHED_SURFACE_INDEX, 1..=7],
+    fn dd_surface_delete_attached_surface_detour(this, flags: u32, attached: *mut c_void) -> i32,
+    |result| (
+        format!("this={this:p} flags=0x{flags:08X} attached={attached:p}"),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_ENUM_ATTACHED_SURFACES = "IDirectDrawSurface::EnumAttachedSurfaces" [DDS_METHOD_ENUM_ATTACHED_SURFACES_INDEX, 1..=7],
+    fn dd_surface_enum_attached_surfaces_detour(
+        this,
+        context: *mut c_void,
+        callback: *mut c_void,
+    ) -> i32,
+    |result| (
+        format!("this={this:p} context={context:p} callback={callback:p}"),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_ENUM_OVERLAY_Z_ORDERS = "IDirectDrawSurface::EnumOverlayZOrders" [DDS_METHOD_ENUM_OVERLAY_Z_ORDERS_INDEX, 1..=7],
+    fn dd_surface_enum_overlay_z_orders_detour(
+        this,
+        flags: u32,
+        context: *mut c_void,
+        callback: *mut c_void,
+    ) -> i32,
+    |result| (
+        format!("this={this:p} flags=0x{flags:08X} context={context:p} callback={callback:p}"),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_FLIP = "IDirectDrawSurface::Flip" [DDS_METHOD_FLIP_INDEX, 1..=7],
+    fn dd_surface_flip_detour(this, target_override: *mut c_void, flags: u32) -> i32,
+    |result| (
+        format!("this={this:p} target_override={target_override:p} flags=0x{flags:08X}"),
+        {
+            if result >= 0 {
+                on_present(this, "Flip");
+            }
+            hresult_result(result)
+        },
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_GETATTACHED = "IDirectDrawSurface::GetAttachedSurface" [DDS_METHOD_GETATTACHED_INDEX, 1..=7],
+    fn dd_surface_getattached_detour(
+        this,
+        caps: *mut c_void,
+        attached_out: *mut *mut c_void,
+    ) -> i32,
+    |result| {
+        let out_value = read_out_ptr(attached_out);
+        if hresult_succeeded(result) && !out_value.is_null() {
+            // Attached surfaces come back through the same interface revision as `this`.
+            try_install_directdraw_surface_hooks(
+                out_value,
+                interface_revision(this).unwrap_or(1),
+                "IDirectDrawSurface::GetAttachedSurface",
+            );
+        }
+        (
+            format!(
+                "this={this:p} caps={} out_ptr={attached_out:p} out={out_value:p}",
+                describe_dd_scaps(caps)
+            ),
+            hresult_result(result),
+        )
+    }
+}
+
+vtable_hook! {
+    DD_SURFACE_GET_BLT_STATUS = "IDirectDrawSurface::GetBltStatus" [DDS_METHOD_GET_BLT_STATUS_INDEX, 1..=7],
+    fn dd_surface_get_blt_status_detour(this, flags: u32) -> i32,
+    |result| (format!("this={this:p} flags=0x{flags:08X}"), hresult_result(result))
+}
+
+vtable_hook! {
+    pub(crate) DD_SURFACE_GET_CAPS = "IDirectDrawSurface::GetCaps" [DDS_METHOD_GET_CAPS_INDEX, 1..=7],
+    fn dd_surface_get_caps_detour(this, caps: *mut c_void) -> i32,
+    |result| (
+        format!("this={this:p} caps={}", describe_dd_scaps(caps)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_GET_CLIPPER = "IDirectDrawSurface::GetClipper" [DDS_METHOD_GET_CLIPPER_INDEX, 1..=7],
+    fn dd_surface_get_clipper_detour(this, clipper_out: *mut *mut c_void) -> i32,
+    |result| (
+        format!("this={this:p}"),
+        format!("{} clipper={:p}", hresult_result(result), read_out_ptr(clipper_out)),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_GET_COLOR_KEY = "IDirectDrawSurface::GetColorKey" [DDS_METHOD_GET_COLOR_KEY_INDEX, 1..=7],
+    fn dd_surface_get_color_key_detour(this, flags: u32, color_key: *mut c_void) -> i32,
+    |result| (
+        format!(
+            "this={this:p} flags=0x{flags:08X} key=({})",
+            describe_dd_color_key(color_key)
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_GETDC = "IDirectDrawSurface::GetDC" [DDS_METHOD_GETDC_INDEX, 1..=7],
+    fn dd_surface_getdc_detour(this, hdc_out: *mut isize) -> i32,
+    |result| {
+        let out_value = if hdc_out.is_null() {
+            0usize
+        } else {
+            unsafe { *hdc_out as usize }
+        };
+        (
+            format!("this={this:p} out_ptr={hdc_out:p} out=0x{out_value:016X}"),
+            hresult_result(result),
+        )
+    }
+}
+
+vtable_hook! {
+    DD_SURFACE_GET_FLIP_STATUS = "IDirectDrawSurface::GetFlipStatus" [DDS_METHOD_GET_FLIP_STATUS_INDEX, 1..=7],
+    fn dd_surface_get_flip_status_detour(this, flags: u32) -> i32,
+    |result| (format!("this={this:p} flags=0x{flags:08X}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_GET_OVERLAY_POSITION = "IDirectDrawSurface::GetOverlayPosition" [DDS_METHOD_GET_OVERLAY_POSITION_INDEX, 1..=7],
+    fn dd_surface_get_overlay_position_detour(this, x: *mut i32, y: *mut i32) -> i32,
+    |result| (
+        format!(
+            "this={this:p} x={} y={}",
+            describe_u32_out(x as *mut u32),
+            describe_u32_out(y as *mut u32),
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    pub(crate) DD_SURFACE_GET_PALETTE = "IDirectDrawSurface::GetPalette" [DDS_METHOD_GET_PALETTE_INDEX, 1..=7],
+    fn dd_surface_get_palette_detour(this, palette_out: *mut *mut c_void) -> i32,
+    |result| {
+        let palette = read_out_ptr(palette_out);
+        if hresult_succeeded(result) {
+            try_install_directdraw_palette_hooks(palette, "IDirectDrawSurface::GetPalette");
+        }
+        (
+            format!("this={this:p}"),
+            format!("{} palette={palette:p}", hresult_result(result)),
+        )
+    }
+}
+
+vtable_hook! {
+    DD_SURFACE_GET_PIXEL_FORMAT = "IDirectDrawSurface::GetPixelFormat" [DDS_METHOD_GET_PIXEL_FORMAT_INDEX, 1..=7],
+    fn dd_surface_get_pixel_format_detour(this, pixel_format: *mut c_void) -> i32,
+    |result| (
+        format!(
+            "this={this:p} format=({})",
+            describe_dd_pixel_format(pixel_format)
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_GETDESC = "IDirectDrawSurface::GetSurfaceDesc" [DDS_METHOD_GETDESC_INDEX, 1..=7],
+    fn dd_surface_getdesc_detour(this, desc: *mut c_void) -> i32,
+    |result| (
+        format!("this={this:p} desc_ptr={desc:p} ({})", describe_dd_surface_desc(desc)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_INITIALIZE = "IDirectDrawSurface::Initialize" [DDS_METHOD_INITIALIZE_INDEX, 1..=7],
+    fn dd_surface_initialize_detour(this, directdraw: *mut c_void, desc: *mut c_void) -> i32,
+    |result| (
+        format!("this={this:p} directdraw={directdraw:p} desc_ptr={desc:p}"),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_ISLOST = "IDirectDrawSurface::IsLost" [DDS_METHOD_ISLOST_INDEX, 1..=7],
+    fn dd_surface_islost_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    pub(crate) DD_SURFACE_LOCK = "IDirectDrawSurface::Lock" [DDS_METHOD_LOCK_INDEX, 1..=7],
+    fn dd_surface_lock_detour(
+        this,
+        rect: *mut RECT,
+        desc: *mut c_void,
+        flags: u32,
+        handle: *mut c_void,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} rect={} desc={desc:p} flags=0x{flags:08X} handle={handle:p}",
+            describe_rect_ptr(rect)
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_RELEASEDC = "IDirectDrawSurface::ReleaseDC" [DDS_METHOD_RELEASEDC_INDEX, 1..=7],
+    fn dd_surface_releasedc_detour(this, hdc: isize) -> i32,
+    |result| (
+        format!("this={this:p} hdc=0x{:016X}", hdc as usize),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_RESTORE = "IDirectDrawSurface::Restore" [DDS_METHOD_RESTORE_INDEX, 1..=7],
+    fn dd_surface_restore_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_SETCLIPPER = "IDirectDrawSurface::SetClipper" [DDS_METHOD_SETCLIPPER_INDEX, 1..=7],
+    fn dd_surface_setclipper_detour(this, clipper: *mut c_void) -> i32,
+    |result| (format!("this={this:p} clipper={clipper:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_SET_COLOR_KEY = "IDirectDrawSurface::SetColorKey" [DDS_METHOD_SET_COLOR_KEY_INDEX, 1..=7],
+    fn dd_surface_set_color_key_detour(this, flags: u32, color_key: *mut c_void) -> i32,
+    |result| (
+        format!(
+            "this={this:p} flags=0x{flags:08X} key=({})",
+            describe_dd_color_key(color_key)
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_SET_OVERLAY_POSITION = "IDirectDrawSurface::SetOverlayPosition" [DDS_METHOD_SET_OVERLAY_POSITION_INDEX, 1..=7],
+    fn dd_surface_set_overlay_position_detour(this, x: i32, y: i32) -> i32,
+    |result| (format!("this={this:p} x={x} y={y}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_SETPALETTE = "IDirectDrawSurface::SetPalette" [DDS_METHOD_SETPALETTE_INDEX, 1..=7],
+    fn dd_surface_setpalette_detour(this, palette: *mut c_void) -> i32,
+    |result| {
+        // Palettes created before injection are first seen here.
+        try_install_directdraw_palette_hooks(palette, "IDirectDrawSurface::SetPalette");
+        (format!("this={this:p} palette={palette:p}"), hresult_result(result))
+    }
+}
+
+// IDirectDrawSurface..3 pass the locked data pointer to Unlock; IDirectDrawSurface4/7 pass the
+// locked RECT instead. Both are a single pointer, so one description covers every revision.
+vtable_hook! {
+    pub(crate) DD_SURFACE_UNLOCK = "IDirectDrawSurface::Unlock" [DDS_METHOD_UNLOCK_INDEX, 1..=7],
+    fn dd_surface_unlock_detour(this, data: *mut c_void) -> i32,
+    |result| (format!("this={this:p} data={data:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_UPDATE_OVERLAY = "IDirectDrawSurface::UpdateOverlay" [DDS_METHOD_UPDATE_OVERLAY_INDEX, 1..=7],
+    fn dd_surface_update_overlay_detour(
+        this,
+        src_rect: *mut RECT,
+        dst_surface: *mut c_void,
+        dst_rect: *mut RECT,
+        flags: u32,
+        fx: *mut c_void,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} src_rect={} dst_surface={dst_surface:p} dst_rect={} flags=0x{flags:08X} fx={fx:p}",
+            describe_rect_ptr(src_rect),
+            describe_rect_ptr(dst_rect)
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_UPDATE_OVERLAY_DISPLAY = "IDirectDrawSurface::UpdateOverlayDisplay" [DDS_METHOD_UPDATE_OVERLAY_DISPLAY_INDEX, 1..=7],
+    fn dd_surface_update_overlay_display_detour(this, flags: u32) -> i32,
+    |result| (format!("this={this:p} flags=0x{flags:08X}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_UPDATE_OVERLAY_Z_ORDER = "IDirectDrawSurface::UpdateOverlayZOrder" [DDS_METHOD_UPDATE_OVERLAY_Z_ORDER_INDEX, 1..=7],
+    fn dd_surface_update_overlay_z_order_detour(this, flags: u32, reference: *mut c_void) -> i32,
+    |result| (
+        format!("this={this:p} flags=0x{flags:08X} reference={reference:p}"),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_GET_DD_INTERFACE = "IDirectDrawSurface::GetDDInterface" [DDS_METHOD_GET_DD_INTERFACE_INDEX, 2..=7],
+    fn dd_surface_get_dd_interface_detour(this, directdraw_out: *mut *mut c_void) -> i32,
+    |result| (
+        format!("this={this:p}"),
+        format!("{} directdraw={:p}", hresult_result(result), read_out_ptr(directdraw_out)),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_PAGE_LOCK = "IDirectDrawSurface::PageLock" [DDS_METHOD_PAGE_LOCK_INDEX, 2..=7],
+    fn dd_surface_page_lock_detour(this, flags: u32) -> i32,
+    |result| (format!("this={this:p} flags=0x{flags:08X}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_PAGE_UNLOCK = "IDirectDrawSurface::PageUnlock" [DDS_METHOD_PAGE_UNLOCK_INDEX, 2..=7],
+    fn dd_surface_page_unlock_detour(this, flags: u32) -> i32,
+    |result| (format!("this={this:p} flags=0x{flags:08X}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_SET_SURFACE_DESC = "IDirectDrawSurface::SetSurfaceDesc" [DDS_METHOD_SET_SURFACE_DESC_INDEX, 3..=7],
+    fn dd_surface_set_surface_desc_detour(this, desc: *mut c_void, flags: u32) -> i32,
+    |result| (
+        format!(
+            "this={this:p} desc_ptr={desc:p} ({}) flags=0x{flags:08X}",
+            describe_dd_surface_desc(desc)
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_SET_PRIVATE_DATA = "IDirectDrawSurface::SetPrivateData" [DDS_METHOD_SET_PRIVATE_DATA_INDEX, 4..=7],
+    fn dd_surface_set_private_data_detour(
+        this,
+        guid: *const c_void,
+        data: *mut c_void,
+        size: u32,
+        flags: u32,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} guid={} data={data:p} size={size} flags=0x{flags:08X}",
+            describe_guid_ptr(guid)
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_GET_PRIVATE_DATA = "IDirectDrawSurface::GetPrivateData" [DDS_METHOD_GET_PRIVATE_DATA_INDEX, 4..=7],
+    fn dd_surface_get_private_data_detour(
+        this,
+        guid: *const c_void,
+        data: *mut c_void,
+        size: *mut u32,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} guid={} data={data:p} size={}",
+            describe_guid_ptr(guid),
+            describe_u32_out(size)
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_FREE_PRIVATE_DATA = "IDirectDrawSurface::FreePrivateData" [DDS_METHOD_FREE_PRIVATE_DATA_INDEX, 4..=7],
+    fn dd_surface_free_private_data_detour(this, guid: *const c_void) -> i32,
+    |result| (
+        format!("this={this:p} guid={}", describe_guid_ptr(guid)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_GET_UNIQUENESS_VALUE = "IDirectDrawSurface::GetUniquenessValue" [DDS_METHOD_GET_UNIQUENESS_VALUE_INDEX, 4..=7],
+    fn dd_surface_get_uniqueness_value_detour(this, value: *mut u32) -> i32,
+    |result| (
+        format!("this={this:p} value={}", describe_u32_out(value)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_CHANGE_UNIQUENESS_VALUE = "IDirectDrawSurface::ChangeUniquenessValue" [DDS_METHOD_CHANGE_UNIQUENESS_VALUE_INDEX, 4..=7],
+    fn dd_surface_change_uniqueness_value_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_SET_PRIORITY = "IDirectDrawSurface::SetPriority" [DDS_METHOD_SET_PRIORITY_INDEX, 7..=7],
+    fn dd_surface_set_priority_detour(this, priority: u32) -> i32,
+    |result| (format!("this={this:p} priority={priority}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_GET_PRIORITY = "IDirectDrawSurface::GetPriority" [DDS_METHOD_GET_PRIORITY_INDEX, 7..=7],
+    fn dd_surface_get_priority_detour(this, priority: *mut u32) -> i32,
+    |result| (
+        format!("this={this:p} priority={}", describe_u32_out(priority)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_SURFACE_SET_LOD = "IDirectDrawSurface::SetLOD" [DDS_METHOD_SET_LOD_INDEX, 7..=7],
+    fn dd_surface_set_lod_detour(this, lod: u32) -> i32,
+    |result| (format!("this={this:p} lod={lod}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DD_SURFACE_GET_LOD = "IDirectDrawSurface::GetLOD" [DDS_METHOD_GET_LOD_INDEX, 7..=7],
+    fn dd_surface_get_lod_detour(this, lod: *mut u32) -> i32,
+    |result| (
+        format!("this={this:p} lod={}", describe_u32_out(lod)),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DD_PALETTE_ADD_REF = "IDirectDrawPalette::AddRef" [DDP_METHOD_ADD_REF_INDEX, 1..=1],
+    fn dd_palette_add_ref_detour(this) -> u32,
+    |result| (format!("this={this:p}"), format!("refcount={result}"))
+}
+
+vtable_hook! {
+    pub(crate) DD_PALETTE_RELEASE = "IDirectDrawPalette::Release" [DDP_METHOD_RELEASE_INDEX, 1..=1],
+    fn dd_palette_release_detour(this) -> u32,
+    |result| (format!("this={this:p}"), format!("refcount={result}"))
+}
+
+vtable_hook! {
+    pub(crate) DD_PALETTE_GET_ENTRIES = "IDirectDrawPalette::GetEntries" [DDP_METHOD_GET_ENTRIES_INDEX, 1..=1],
+    fn dd_palette_get_entries_detour(
+        this,
+        flags: u32,
+        base: u32,
+        count: u32,
+        entries: *mut c_void,
+    ) -> i32,
+    |result| (
+        format!("this={this:p} flags=0x{flags:08X} base={base} count={count}"),
+        if hresult_succeeded(result) {
+            format!(
+                "{} colors={}",
+                hresult_result(result),
+                describe_palette_entries(entries, count as usize)
+            )
+        } else {
+            hresult_result(result)
+        },
+    )
+}
+
+vtable_hook! {
+    DD_PALETTE_SET_ENTRIES = "IDirectDrawPalette::SetEntries" [DDP_METHOD_SET_ENTRIES_INDEX, 1..=1],
+    fn dd_palette_set_entries_detour(
+        this,
+        flags: u32,
+        start: u32,
+        count: u32,
+        entries: *mut c_void,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} flags=0x{flags:08X} start={start} count={count} colors={}",
+            describe_palette_entries(entries, count as usize)
+        ),
+        hresult_result(result),
+    )
+}
+
+/// Entries passed to `CreatePalette`. With `DDPCAPS_8BITENTRIES` they are byte indices into
+/// another palette rather than colors.
+fn describe_created_palette(entries: *mut c_void, flags: u32) -> String {
+    if flags & DDPCAPS_8BITENTRIES != 0 {
+        return "indexed".to_owned();
+    }
+    describe_palette_entries(entries, palette_entry_count(flags))
+}
+
+/// Number of entries a palette created with `flags` holds.
+fn palette_entry_count(flags: u32) -> usize {
+    if flags & DDPCAPS_8BIT != 0 {
+        256
+    } else if flags & DDPCAPS_4BIT != 0 {
+        16
+    } else if flags & DDPCAPS_2BIT != 0 {
+        4
+    } else if flags & DDPCAPS_1BIT != 0 {
+        2
+    } else {
+        0
+    }
+}
+
+/// `count` PALETTEENTRYs as hex, four bytes (red, green, blue, flags) per entry.
+fn describe_palette_entries(entries: *mut c_void, count: usize) -> String {
+    if entries.is_null() {
+        return "null".to_owned();
+    }
+    if count == 0 {
+        return "none".to_owned();
+    }
+    match try_read_bytes(entries, count * PALETTE_ENTRY_SIZE) {
+        Some(bytes) => hex_bytes(&bytes),
+        None => format!("{entries:p}"),
+    }
+}
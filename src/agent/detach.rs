@@ -0,0 +1,86 @@
+//! Detaching the agent from a live process so it can be unloaded without restarting the game.
+//!
+//! Detaching stops new installs, puts the subclassed window procedures and copied DirectDraw
+//! vtables back, disables every detour in the hook inventory and reports `AgentDetached`. Calls
+//! that entered agent code before it was unpatched can still be on a thread's stack, from a
+//! `Flip` waiting for vertical blank to a window procedure inside a modal loop. Every detour and
+//! subclassed window procedure counts its frame, and the agent stays loaded (but inert) if those
+//! frames do not unwind in time.
+
+use crate::agent::hook_groups::unregister_dll_notification;
+use crate::agent::hook_inventory::disable_tracked_detours;
+use crate::agent::timing::stop_timing_reporter;
+use crate::agent::window_messages::restore_window_procs;
+use crate::model::ipc::AGENT_DETACHED_API;
+#[cfg(target_pointer_width = "32")]
+use crate::restore_usage_vtables;
+use crate::{bool_result, make_event, send_event};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Covers the few instructions a detour runs before taking, or after dropping, its `ActiveCall`.
+const DETOUR_DRAIN: Duration = Duration::from_millis(200);
+/// How long counted agent frames get to unwind before the agent gives up on unloading.
+const ACTIVE_CALL_TIMEOUT: Duration = Duration::from_secs(2);
+const ACTIVE_CALL_POLL: Duration = Duration::from_millis(20);
+
+static DETACHED: AtomicBool = AtomicBool::new(false);
+static ACTIVE_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// Once set, nothing new is hooked and the remaining hooks stop reporting.
+pub fn agent_detached() -> bool {
+    DETACHED.load(Ordering::Acquire)
+}
+
+/// Marks a frame of agent code, so the agent is not unloaded underneath it. Taken first thing in
+/// every detour and window procedure.
+pub struct ActiveCall(());
+
+impl ActiveCall {
+    pub fn enter() -> Self {
+        ACTIVE_CALLS.fetch_add(1, Ordering::AcqRel);
+        Self(())
+    }
+}
+
+impl Drop for ActiveCall {
+    fn drop(&mut self) {
+        ACTIVE_CALLS.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Undoes every patch and reports `AgentDetached`. Returns whether the agent can be unloaded;
+/// when it cannot, a later request retries whatever is still patched.
+pub fn detach_agent() -> bool {
+    DETACHED.store(true, Ordering::Release);
+    unregister_dll_notification();
+    stop_timing_reporter();
+    let stuck_windows = restore_window_procs();
+    #[cfg(target_pointer_width = "32")]
+    restore_usage_vtables();
+    let (disabled, failures) = disable_tracked_detours();
+
+    std::thread::sleep(DETOUR_DRAIN);
+    let deadline = Instant::now() + ACTIVE_CALL_TIMEOUT;
+    while ACTIVE_CALLS.load(Ordering::Acquire) != 0 && Instant::now() < deadline {
+        std::thread::sleep(ACTIVE_CALL_POLL);
+    }
+    let active_calls = ACTIVE_CALLS.load(Ordering::Acquire);
+
+    // A detour that would not disable or a window that could not be unsubclassed still jumps
+    // into the agent.
+    let unload = active_calls == 0 && failures.is_empty() && stuck_windows == 0;
+    let mut summary = format!(
+        "detours_disabled={disabled} detour_failures={} stuck_windows={stuck_windows} active_calls={active_calls}",
+        failures.len()
+    );
+    if !failures.is_empty() {
+        summary.push_str(&format!(" failed=\"{}\"", failures.join(",")));
+    }
+    send_event(make_event(
+        AGENT_DETACHED_API,
+        summary,
+        format!("unload={}", bool_result(i32::from(unload))),
+    ));
+    unload
+}
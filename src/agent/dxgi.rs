@@ -0,0 +1,213 @@
+//! IDXGIFactory and IDXGISwapChain vtable coverage.
+//!
+//! Later factory and swap chain revisions only append methods, so one revision covers every
+//! interface a D3D10/11 runtime hands out. Factories come from `CreateDXGIFactory`/`1` or, for
+//! the usual D3D11 path, from walking a created device up to its adapter's parent.
+
+use super::com_vtable::{VtableHookEntry, install_vtable_hooks, vtable_hook};
+use crate::decode::structs::{
+    DXGI_MODE_DESC_SIZE, DxgiSwapChainDescLayout, parse_dxgi_mode_desc, parse_dxgi_swap_chain_desc,
+};
+use crate::{
+    Guid, describe_struct_ptr, hresult_result, hresult_succeeded, ptr_to_fn, read_out_ptr,
+    vtable_method_ptr,
+};
+use std::ffi::c_void;
+
+pub const DXGI_REVISION: u32 = 1;
+
+// IUnknown / IDXGIObject / IDXGIDevice slots used to find a device's factory.
+const UNKNOWN_METHOD_QUERY_INTERFACE_INDEX: usize = 0;
+const UNKNOWN_METHOD_RELEASE_INDEX: usize = 2;
+const DXGI_OBJECT_METHOD_GET_PARENT_INDEX: usize = 6;
+const DXGI_DEVICE_METHOD_GET_ADAPTER_INDEX: usize = 7;
+
+// IDXGIFactory vtable layout.
+const DXGI_FACTORY_METHOD_CREATE_SWAP_CHAIN_INDEX: usize = 10;
+
+// IDXGISwapChain vtable layout.
+const DXGI_SWAP_CHAIN_METHOD_PRESENT_INDEX: usize = 8;
+const DXGI_SWAP_CHAIN_METHOD_SET_FULLSCREEN_STATE_INDEX: usize = 10;
+const DXGI_SWAP_CHAIN_METHOD_RESIZE_BUFFERS_INDEX: usize = 13;
+const DXGI_SWAP_CHAIN_METHOD_RESIZE_TARGET_INDEX: usize = 14;
+
+const IID_IDXGIDEVICE: Guid = Guid {
+    data1: 0x54EC77FA,
+    data2: 0x1377,
+    data3: 0x44E6,
+    data4: [0x8C, 0x32, 0x88, 0xFD, 0x5F, 0x44, 0xC8, 0x4C],
+};
+const IID_IDXGIFACTORY: Guid = Guid {
+    data1: 0x7B7166EC,
+    data2: 0x21C7,
+    data3: 0x44AE,
+    data4: [0xB2, 0x1A, 0xC9, 0xAE, 0x32, 0x1A, 0xE3, 0x69],
+};
+
+type FnQueryInterface =
+    unsafe extern "system" fn(*mut c_void, *const c_void, *mut *mut c_void) -> i32;
+type FnRelease = unsafe extern "system" fn(*mut c_void) -> u32;
+type FnGetAdapter = unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> i32;
+
+static DXGI_FACTORY_VTABLE_HOOKS: &[&dyn VtableHookEntry] = &[&DXGI_FACTORY_CREATE_SWAP_CHAIN];
+static DXGI_SWAP_CHAIN_VTABLE_HOOKS: &[&dyn VtableHookEntry] = &[
+    &DXGI_SWAP_CHAIN_PRESENT,
+    &DXGI_SWAP_CHAIN_SET_FULLSCREEN_STATE,
+    &DXGI_SWAP_CHAIN_RESIZE_BUFFERS,
+    &DXGI_SWAP_CHAIN_RESIZE_TARGET,
+];
+
+pub fn try_install_dxgi_factory_hooks(factory: *mut c_void, source: &str) {
+    install_vtable_hooks(
+        DXGI_FACTORY_VTABLE_HOOKS,
+        factory,
+        DXGI_REVISION,
+        source,
+        "DXGIHookInstall",
+    );
+}
+
+pub fn try_install_dxgi_swap_chain_hooks(swap_chain: *mut c_void, source: &str) {
+    install_vtable_hooks(
+        DXGI_SWAP_CHAIN_VTABLE_HOOKS,
+        swap_chain,
+        DXGI_REVISION,
+        source,
+        "DXGIHookInstall",
+    );
+}
+
+/// Hooks the factory behind a D3D10/11 device (device -> IDXGIDevice -> adapter -> factory),
+/// which is where applications that never call `CreateDXGIFactory` get their swap chains from.
+pub fn try_install_dxgi_hooks_from_device(device: *mut c_void, source: &str) {
+    let Some(dxgi_device) = query_interface(device, &IID_IDXGIDEVICE) else {
+        return;
+    };
+    let adapter = call_out_method(dxgi_device, DXGI_DEVICE_METHOD_GET_ADAPTER_INDEX);
+    release(dxgi_device);
+    let Some(adapter) = adapter else {
+        return;
+    };
+    let factory = get_parent(adapter, &IID_IDXGIFACTORY);
+    release(adapter);
+    if let Some(factory) = factory {
+        try_install_dxgi_factory_hooks(factory, source);
+        release(factory);
+    }
+}
+
+fn query_interface(instance: *mut c_void, iid: &Guid) -> Option<*mut c_void> {
+    let method_ptr = vtable_method_ptr(instance, UNKNOWN_METHOD_QUERY_INTERFACE_INDEX)?;
+    let query_fn: FnQueryInterface = ptr_to_fn(method_ptr);
+    let mut out: *mut c_void = std::ptr::null_mut();
+    let hr = unsafe { query_fn(instance, iid as *const Guid as *const c_void, &mut out) };
+    (hresult_succeeded(hr) && !out.is_null()).then_some(out)
+}
+
+fn get_parent(instance: *mut c_void, iid: &Guid) -> Option<*mut c_void> {
+    let method_ptr = vtable_method_ptr(instance, DXGI_OBJECT_METHOD_GET_PARENT_INDEX)?;
+    let get_parent_fn: FnQueryInterface = ptr_to_fn(method_ptr);
+    let mut out: *mut c_void = std::ptr::null_mut();
+    let hr = unsafe { get_parent_fn(instance, iid as *const Guid as *const c_void, &mut out) };
+    (hresult_succeeded(hr) && !out.is_null()).then_some(out)
+}
+
+fn call_out_method(instance: *mut c_void, index: usize) -> Option<*mut c_void> {
+    let method_ptr = vtable_method_ptr(instance, index)?;
+    let method_fn: FnGetAdapter = ptr_to_fn(method_ptr);
+    let mut out: *mut c_void = std::ptr::null_mut();
+    let hr = unsafe { method_fn(instance, &mut out) };
+    (hresult_succeeded(hr) && !out.is_null()).then_some(out)
+}
+
+fn release(instance: *mut c_void) {
+    if let Some(method_ptr) = vtable_method_ptr(instance, UNKNOWN_METHOD_RELEASE_INDEX) {
+        let release_fn: FnRelease = ptr_to_fn(method_ptr);
+        let _ = unsafe { release_fn(instance) };
+    }
+}
+
+pub fn describe_swap_chain_desc(desc: *const c_void) -> String {
+    let pointer_size = std::mem::size_of::<usize>();
+    let size = DxgiSwapChainDescLayout::for_pointer_size(pointer_size).size;
+    describe_struct_ptr(desc, size, |bytes| {
+        parse_dxgi_swap_chain_desc(bytes, pointer_size).map(|desc| desc.to_string())
+    })
+}
+
+fn describe_mode_desc(desc: *const c_void) -> String {
+    describe_struct_ptr(desc, DXGI_MODE_DESC_SIZE, |bytes| {
+        parse_dxgi_mode_desc(bytes).map(|desc| desc.to_string())
+    })
+}
+
+vtable_hook! {
+    DXGI_FACTORY_CREATE_SWAP_CHAIN = "IDXGIFactory::CreateSwapChain" [DXGI_FACTORY_METHOD_CREATE_SWAP_CHAIN_INDEX, 1..=1],
+    fn dxgi_factory_create_swap_chain_detour(
+        this,
+        device: *mut c_void,
+        desc: *const c_void,
+        swap_chain_out: *mut *mut c_void,
+    ) -> i32,
+    |result| {
+        let swap_chain = read_out_ptr(swap_chain_out);
+        if hresult_succeeded(result) {
+            try_install_dxgi_swap_chain_hooks(swap_chain, "IDXGIFactory::CreateSwapChain");
+        }
+        (
+            format!(
+                "this={this:p} device={device:p} {}",
+                describe_swap_chain_desc(desc)
+            ),
+            format!("{} swap_chain={swap_chain:p}", hresult_result(result)),
+        )
+    }
+}
+
+vtable_hook! {
+    DXGI_SWAP_CHAIN_PRESENT = "IDXGISwapChain::Present" [DXGI_SWAP_CHAIN_METHOD_PRESENT_INDEX, 1..=1],
+    fn dxgi_swap_chain_present_detour(this, sync_interval: u32, flags: u32) -> i32,
+    |result| (
+        format!("this={this:p} sync_interval={sync_interval} flags=0x{flags:08X}"),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DXGI_SWAP_CHAIN_SET_FULLSCREEN_STATE = "IDXGISwapChain::SetFullscreenState" [DXGI_SWAP_CHAIN_METHOD_SET_FULLSCREEN_STATE_INDEX, 1..=1],
+    fn dxgi_swap_chain_set_fullscreen_state_detour(this, fullscreen: i32, target: *mut c_void) -> i32,
+    |result| (
+        format!(
+            "this={this:p} fullscreen={} target={target:p}",
+            if fullscreen != 0 { "TRUE" } else { "FALSE" }
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DXGI_SWAP_CHAIN_RESIZE_BUFFERS = "IDXGISwapChain::ResizeBuffers" [DXGI_SWAP_CHAIN_METHOD_RESIZE_BUFFERS_INDEX, 1..=1],
+    fn dxgi_swap_chain_resize_buffers_detour(
+        this,
+        buffer_count: u32,
+        width: u32,
+        height: u32,
+        format: u32,
+        flags: u32,
+    ) -> i32,
+    |result| (
+        format!(
+            "this={this:p} buffers={buffer_count} width={width} height={height} format={format} flags=0x{flags:08X}"
+        ),
+        hresult_result(result),
+    )
+}
+
+vtable_hook! {
+    DXGI_SWAP_CHAIN_RESIZE_TARGET = "IDXGISwapChain::ResizeTarget" [DXGI_SWAP_CHAIN_METHOD_RESIZE_TARGET_INDEX, 1..=1],
+    fn dxgi_swap_chain_resize_target_detour(this, mode: *const c_void) -> i32,
+    |result| (
+        format!("this={this:p} {}", describe_mode_desc(mode)),
+        hresult_result(result),
+    )
+}
@@ -0,0 +1,602 @@
+//! Opt-in file and registry hook group, for finding where a game keeps its settings.
+//!
+//! Covers `CreateFile`, `GetPrivateProfileString`, `RegOpenKeyEx` and `RegQueryValueEx` in both
+//! character sets. The UI turns it on through the `ConfigureFileRegistryTrace` export. Opened key
+//! handles are mapped to their full path, so value reads can name the key they come from.
+
+use crate::agent::detach::ActiveCall;
+use crate::{
+    hex_bytes, install_late_detour, make_event, read_c_string_lossy, read_out_ptr,
+    read_wide_string_lossy, send_event, try_read_bytes, try_read_u32_at,
+    try_resolve_proc_in_loaded_module,
+};
+use retour::GenericDetour;
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use windows_sys::Win32::Foundation::{GetLastError, SetLastError};
+
+const INVALID_HANDLE_VALUE: isize = -1;
+const ERROR_SUCCESS: i32 = 0;
+const REG_SZ: u32 = 1;
+const REG_EXPAND_SZ: u32 = 2;
+const REG_DWORD: u32 = 4;
+const REG_MULTI_SZ: u32 = 7;
+const REG_QWORD: u32 = 11;
+/// Longer values are cut; settings are short and a binary blob only needs its first bytes.
+const MAX_REG_DATA: usize = 256;
+const MAX_BINARY_DATA_SHOWN: usize = 16;
+const PREDEFINED_KEYS: [(u32, &str); 7] = [
+    (0x8000_0000, "HKEY_CLASSES_ROOT"),
+    (0x8000_0001, "HKEY_CURRENT_USER"),
+    (0x8000_0002, "HKEY_LOCAL_MACHINE"),
+    (0x8000_0003, "HKEY_USERS"),
+    (0x8000_0004, "HKEY_PERFORMANCE_DATA"),
+    (0x8000_0005, "HKEY_CURRENT_CONFIG"),
+    (0x8000_0006, "HKEY_DYN_DATA"),
+];
+
+type FnCreateFileW =
+    unsafe extern "system" fn(*const u16, u32, u32, *const c_void, u32, u32, isize) -> isize;
+type FnCreateFileA =
+    unsafe extern "system" fn(*const u8, u32, u32, *const c_void, u32, u32, isize) -> isize;
+type FnGetPrivateProfileStringW =
+    unsafe extern "system" fn(*const u16, *const u16, *const u16, *mut u16, u32, *const u16) -> u32;
+type FnGetPrivateProfileStringA =
+    unsafe extern "system" fn(*const u8, *const u8, *const u8, *mut u8, u32, *const u8) -> u32;
+type FnRegOpenKeyExW = unsafe extern "system" fn(isize, *const u16, u32, u32, *mut isize) -> i32;
+type FnRegOpenKeyExA = unsafe extern "system" fn(isize, *const u8, u32, u32, *mut isize) -> i32;
+type FnRegQueryValueExW =
+    unsafe extern "system" fn(isize, *const u16, *mut u32, *mut u32, *mut u8, *mut u32) -> i32;
+type FnRegQueryValueExA =
+    unsafe extern "system" fn(isize, *const u8, *mut u32, *mut u32, *mut u8, *mut u32) -> i32;
+type FnRegCloseKey = unsafe extern "system" fn(isize) -> i32;
+
+static CREATE_FILE_W_HOOK: OnceLock<GenericDetour<FnCreateFileW>> = OnceLock::new();
+static CREATE_FILE_A_HOOK: OnceLock<GenericDetour<FnCreateFileA>> = OnceLock::new();
+static GET_PRIVATE_PROFILE_STRING_W_HOOK: OnceLock<GenericDetour<FnGetPrivateProfileStringW>> =
+    OnceLock::new();
+static GET_PRIVATE_PROFILE_STRING_A_HOOK: OnceLock<GenericDetour<FnGetPrivateProfileStringA>> =
+    OnceLock::new();
+static REG_OPEN_KEY_EX_W_HOOK: OnceLock<GenericDetour<FnRegOpenKeyExW>> = OnceLock::new();
+static REG_OPEN_KEY_EX_A_HOOK: OnceLock<GenericDetour<FnRegOpenKeyExA>> = OnceLock::new();
+static REG_QUERY_VALUE_EX_W_HOOK: OnceLock<GenericDetour<FnRegQueryValueExW>> = OnceLock::new();
+static REG_QUERY_VALUE_EX_A_HOOK: OnceLock<GenericDetour<FnRegQueryValueExA>> = OnceLock::new();
+static REG_CLOSE_KEY_HOOK: OnceLock<GenericDetour<FnRegCloseKey>> = OnceLock::new();
+
+static TRACE_ENABLED: AtomicBool = AtomicBool::new(false);
+/// Full path of every key opened since the hooks went in, keyed by handle. Kept up to date while
+/// tracing is off so keys opened in between still resolve once it is back on.
+static KEY_PATHS: OnceLock<Mutex<HashMap<isize, String>>> = OnceLock::new();
+
+fn key_paths() -> &'static Mutex<HashMap<isize, String>> {
+    KEY_PATHS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+pub fn set_file_registry_trace(enabled: bool) {
+    TRACE_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn file_registry_trace_enabled() -> bool {
+    TRACE_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Installs the group once the UI has turned it on; safe to call again after every module load.
+pub fn try_install_file_registry_hooks() -> Result<(), String> {
+    if !file_registry_trace_enabled() {
+        return Ok(());
+    }
+    unsafe {
+        install_late_detour(
+            &CREATE_FILE_W_HOOK,
+            try_resolve_proc_in_loaded_module(b"kernel32.dll\0", b"CreateFileW\0"),
+            create_file_w_detour,
+            "CreateFileW",
+        )?;
+        install_late_detour(
+            &CREATE_FILE_A_HOOK,
+            try_resolve_proc_in_loaded_module(b"kernel32.dll\0", b"CreateFileA\0"),
+            create_file_a_detour,
+            "CreateFileA",
+        )?;
+        install_late_detour(
+            &GET_PRIVATE_PROFILE_STRING_W_HOOK,
+            try_resolve_proc_in_loaded_module(b"kernel32.dll\0", b"GetPrivateProfileStringW\0"),
+            get_private_profile_string_w_detour,
+            "GetPrivateProfileStringW",
+        )?;
+        install_late_detour(
+            &GET_PRIVATE_PROFILE_STRING_A_HOOK,
+            try_resolve_proc_in_loaded_module(b"kernel32.dll\0", b"GetPrivateProfileStringA\0"),
+            get_private_profile_string_a_detour,
+            "GetPrivateProfileStringA",
+        )?;
+        install_late_detour(
+            &REG_OPEN_KEY_EX_W_HOOK,
+            try_resolve_proc_in_loaded_module(b"advapi32.dll\0", b"RegOpenKeyExW\0"),
+            reg_open_key_ex_w_detour,
+            "RegOpenKeyExW",
+        )?;
+        install_late_detour(
+            &REG_OPEN_KEY_EX_A_HOOK,
+            try_resolve_proc_in_loaded_module(b"advapi32.dll\0", b"RegOpenKeyExA\0"),
+            reg_open_key_ex_a_detour,
+            "RegOpenKeyExA",
+        )?;
+        install_late_detour(
+            &REG_QUERY_VALUE_EX_W_HOOK,
+            try_resolve_proc_in_loaded_module(b"advapi32.dll\0", b"RegQueryValueExW\0"),
+            reg_query_value_ex_w_detour,
+            "RegQueryValueExW",
+        )?;
+        install_late_detour(
+            &REG_QUERY_VALUE_EX_A_HOOK,
+            try_resolve_proc_in_loaded_module(b"advapi32.dll\0", b"RegQueryValueExA\0"),
+            reg_query_value_ex_a_detour,
+            "RegQueryValueExA",
+        )?;
+        install_late_detour(
+            &REG_CLOSE_KEY_HOOK,
+            try_resolve_proc_in_loaded_module(b"advapi32.dll\0", b"RegCloseKey\0"),
+            reg_close_key_detour,
+            "RegCloseKey",
+        )?;
+    }
+    Ok(())
+}
+
+fn trace_enabled() -> bool {
+    TRACE_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Quotes `text` for a `key="..."` field; embedded quotes would end the field early.
+fn quoted(text: Option<String>) -> String {
+    match text {
+        Some(text) => format!("\"{}\"", text.replace('"', "'")),
+        None => "null".to_owned(),
+    }
+}
+
+fn wide_text(text: *const u16) -> Option<String> {
+    (!text.is_null()).then(|| read_wide_string_lossy(text))
+}
+
+fn ansi_text(text: *const u8) -> Option<String> {
+    (!text.is_null()).then(|| read_c_string_lossy(text))
+}
+
+fn predefined_key_name(hkey: isize) -> Option<&'static str> {
+    // Predefined handles are sign-extended on 64-bit, but some callers zero-extend them.
+    let low = u32::try_from(hkey)
+        .ok()
+        .or_else(|| i32::try_from(hkey).ok().map(|value| value as u32))?;
+    PREDEFINED_KEYS
+        .iter()
+        .find(|(value, _)| *value == low)
+        .map(|(_, name)| *name)
+}
+
+fn describe_hkey(hkey: isize) -> String {
+    predefined_key_name(hkey).map_or_else(|| format!("0x{hkey:X}"), str::to_owned)
+}
+
+fn key_path(hkey: isize) -> Option<String> {
+    if let Some(name) = predefined_key_name(hkey) {
+        return Some(name.to_owned());
+    }
+    key_paths()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&hkey)
+        .cloned()
+}
+
+fn describe_file_handle(handle: isize, error: u32) -> String {
+    if handle == INVALID_HANDLE_VALUE {
+        format!("handle=INVALID error={error}")
+    } else {
+        format!("handle=0x{handle:X}")
+    }
+}
+
+fn report_create_file(
+    api: &str,
+    path: Option<String>,
+    access: u32,
+    share: u32,
+    disposition: u32,
+    flags: u32,
+    result: String,
+) {
+    send_event(make_event(
+        api,
+        format!(
+            "path={} access=0x{access:08X} share=0x{share:08X} disposition={disposition} flags=0x{flags:08X}",
+            quoted(path)
+        ),
+        result,
+    ));
+}
+
+unsafe extern "system" fn create_file_w_detour(
+    path: *const u16,
+    access: u32,
+    share: u32,
+    security: *const c_void,
+    disposition: u32,
+    flags: u32,
+    template: isize,
+) -> isize {
+    let _active = ActiveCall::enter();
+    let handle = unsafe {
+        CREATE_FILE_W_HOOK
+            .get()
+            .expect("CreateFileW hook not installed")
+            .call(path, access, share, security, disposition, flags, template)
+    };
+    if trace_enabled() {
+        // Callers check GetLastError after a failed open; sending the event must not clobber it.
+        let error = unsafe { GetLastError() };
+        report_create_file(
+            "CreateFileW",
+            wide_text(path),
+            access,
+            share,
+            disposition,
+            flags,
+            describe_file_handle(handle, error),
+        );
+        unsafe { SetLastError(error) };
+    }
+    handle
+}
+
+unsafe extern "system" fn create_file_a_detour(
+    path: *const u8,
+    access: u32,
+    share: u32,
+    security: *const c_void,
+    disposition: u32,
+    flags: u32,
+    template: isize,
+) -> isize {
+    let _active = ActiveCall::enter();
+    let handle = unsafe {
+        CREATE_FILE_A_HOOK
+            .get()
+            .expect("CreateFileA hook not installed")
+            .call(path, access, share, security, disposition, flags, template)
+    };
+    if trace_enabled() {
+        let error = unsafe { GetLastError() };
+        report_create_file(
+            "CreateFileA",
+            ansi_text(path),
+            access,
+            share,
+            disposition,
+            flags,
+            describe_file_handle(handle, error),
+        );
+        unsafe { SetLastError(error) };
+    }
+    handle
+}
+
+fn report_profile_string(
+    api: &str,
+    file: Option<String>,
+    section: Option<String>,
+    key: Option<String>,
+    default: Option<String>,
+    chars: u32,
+    value: Option<String>,
+) {
+    // A null section or key asks for the list of names, which comes back NUL-separated; only
+    // the first one is shown.
+    send_event(make_event(
+        api,
+        format!(
+            "file={} section={} key={} default={}",
+            quoted(file),
+            quoted(section),
+            quoted(key),
+            quoted(default)
+        ),
+        format!("chars={chars} value={}", quoted(value)),
+    ));
+}
+
+unsafe extern "system" fn get_private_profile_string_w_detour(
+    section: *const u16,
+    key: *const u16,
+    default: *const u16,
+    returned: *mut u16,
+    size: u32,
+    file: *const u16,
+) -> u32 {
+    let _active = ActiveCall::enter();
+    let chars = unsafe {
+        GET_PRIVATE_PROFILE_STRING_W_HOOK
+            .get()
+            .expect("GetPrivateProfileStringW hook not installed")
+            .call(section, key, default, returned, size, file)
+    };
+    if trace_enabled() {
+        report_profile_string(
+            "GetPrivateProfileStringW",
+            wide_text(file),
+            wide_text(section),
+            wide_text(key),
+            wide_text(default),
+            chars,
+            wide_text(returned),
+        );
+    }
+    chars
+}
+
+unsafe extern "system" fn get_private_profile_string_a_detour(
+    section: *const u8,
+    key: *const u8,
+    default: *const u8,
+    returned: *mut u8,
+    size: u32,
+    file: *const u8,
+) -> u32 {
+    let _active = ActiveCall::enter();
+    let chars = unsafe {
+        GET_PRIVATE_PROFILE_STRING_A_HOOK
+            .get()
+            .expect("GetPrivateProfileStringA hook not installed")
+            .call(section, key, default, returned, size, file)
+    };
+    if trace_enabled() {
+        report_profile_string(
+            "GetPrivateProfileStringA",
+            ansi_text(file),
+            ansi_text(section),
+            ansi_text(key),
+            ansi_text(default),
+            chars,
+            ansi_text(returned),
+        );
+    }
+    chars
+}
+
+/// Records the opened key's path and reports the call.
+fn report_reg_open_key(
+    api: &str,
+    parent: isize,
+    subkey: Option<String>,
+    options: u32,
+    sam: u32,
+    key_out: *mut isize,
+    status: i32,
+) {
+    let path = key_path(parent).map(|parent_path| match subkey.as_deref() {
+        Some(subkey) if !subkey.is_empty() => format!("{parent_path}\\{subkey}"),
+        _ => parent_path,
+    });
+    let key = Some(read_out_ptr(key_out.cast()) as isize)
+        .filter(|key| status == ERROR_SUCCESS && *key != 0);
+    if let (Some(key), Some(path)) = (key, &path) {
+        key_paths()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, path.clone());
+    }
+    if !trace_enabled() {
+        return;
+    }
+
+    let mut result = format!("status={status}");
+    if let Some(key) = key {
+        result.push_str(&format!(" key=0x{key:X}"));
+    }
+    if let Some(path) = path {
+        result.push_str(&format!(" path={}", quoted(Some(path))));
+    }
+    send_event(make_event(
+        api,
+        format!(
+            "hkey={} subkey={} options=0x{options:X} sam=0x{sam:08X}",
+            describe_hkey(parent),
+            quoted(subkey)
+        ),
+        result,
+    ));
+}
+
+unsafe extern "system" fn reg_open_key_ex_w_detour(
+    hkey: isize,
+    subkey: *const u16,
+    options: u32,
+    sam: u32,
+    key_out: *mut isize,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let status = unsafe {
+        REG_OPEN_KEY_EX_W_HOOK
+            .get()
+            .expect("RegOpenKeyExW hook not installed")
+            .call(hkey, subkey, options, sam, key_out)
+    };
+    report_reg_open_key(
+        "RegOpenKeyExW",
+        hkey,
+        wide_text(subkey),
+        options,
+        sam,
+        key_out,
+        status,
+    );
+    status
+}
+
+unsafe extern "system" fn reg_open_key_ex_a_detour(
+    hkey: isize,
+    subkey: *const u8,
+    options: u32,
+    sam: u32,
+    key_out: *mut isize,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let status = unsafe {
+        REG_OPEN_KEY_EX_A_HOOK
+            .get()
+            .expect("RegOpenKeyExA hook not installed")
+            .call(hkey, subkey, options, sam, key_out)
+    };
+    report_reg_open_key(
+        "RegOpenKeyExA",
+        hkey,
+        ansi_text(subkey),
+        options,
+        sam,
+        key_out,
+        status,
+    );
+    status
+}
+
+/// Strings as text, DWORD/QWORD in hex, anything else as its first bytes.
+fn describe_reg_data(kind: u32, data: *const u8, size: u32, wide: bool) -> Option<String> {
+    let len = (size as usize).min(MAX_REG_DATA);
+    let bytes = try_read_bytes(data as *const c_void, len)?;
+    let text = |bytes: &[u8]| -> Vec<String> {
+        let text = if wide {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        } else {
+            String::from_utf8_lossy(bytes).into_owned()
+        };
+        text.split('\0')
+            .filter(|part| !part.is_empty())
+            .map(str::to_owned)
+            .collect()
+    };
+    Some(match kind {
+        REG_SZ | REG_EXPAND_SZ => quoted(Some(text(&bytes).into_iter().next().unwrap_or_default())),
+        REG_MULTI_SZ => quoted(Some(text(&bytes).join("|"))),
+        REG_DWORD if bytes.len() >= 4 => {
+            format!("0x{:08X}", u32::from_le_bytes(bytes[..4].try_into().ok()?))
+        }
+        REG_QWORD if bytes.len() >= 8 => {
+            format!("0x{:016X}", u64::from_le_bytes(bytes[..8].try_into().ok()?))
+        }
+        _ => {
+            let shown = bytes.len().min(MAX_BINARY_DATA_SHOWN);
+            let ellipsis = if bytes.len() > shown { "..." } else { "" };
+            format!("{}{ellipsis}", hex_bytes(&bytes[..shown]))
+        }
+    })
+}
+
+fn report_reg_query_value(api: &str, hkey: isize, value: Option<String>, result: String) {
+    let mut summary = format!("hkey={}", describe_hkey(hkey));
+    if let Some(path) = key_path(hkey) {
+        summary.push_str(&format!(" path={}", quoted(Some(path))));
+    }
+    // A null or empty name reads the key's default value.
+    summary.push_str(&format!(" value={}", quoted(value)));
+    send_event(make_event(api, summary, result));
+}
+
+/// `type` and `size` are only there when the caller asked for them; `data` only on success.
+fn describe_reg_query_result(
+    status: i32,
+    kind_out: *const u32,
+    data: *const u8,
+    size_out: *const u32,
+    wide: bool,
+) -> String {
+    let mut result = format!("status={status}");
+    let kind = try_read_u32_at(kind_out as *const c_void, 0);
+    let size = try_read_u32_at(size_out as *const c_void, 0);
+    if let Some(kind) = kind {
+        result.push_str(&format!(" type={kind}"));
+    }
+    if let Some(size) = size {
+        result.push_str(&format!(" size={size}"));
+    }
+    if status == ERROR_SUCCESS
+        && let (Some(kind), Some(size)) = (kind, size)
+        && let Some(data) = describe_reg_data(kind, data, size, wide)
+    {
+        result.push_str(&format!(" data={data}"));
+    }
+    result
+}
+
+unsafe extern "system" fn reg_query_value_ex_w_detour(
+    hkey: isize,
+    value: *const u16,
+    reserved: *mut u32,
+    kind_out: *mut u32,
+    data: *mut u8,
+    size_out: *mut u32,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let status = unsafe {
+        REG_QUERY_VALUE_EX_W_HOOK
+            .get()
+            .expect("RegQueryValueExW hook not installed")
+            .call(hkey, value, reserved, kind_out, data, size_out)
+    };
+    if trace_enabled() {
+        report_reg_query_value(
+            "RegQueryValueExW",
+            hkey,
+            wide_text(value),
+            describe_reg_query_result(status, kind_out, data, size_out, true),
+        );
+    }
+    status
+}
+
+unsafe extern "system" fn reg_query_value_ex_a_detour(
+    hkey: isize,
+    value: *const u8,
+    reserved: *mut u32,
+    kind_out: *mut u32,
+    data: *mut u8,
+    size_out: *mut u32,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let status = unsafe {
+        REG_QUERY_VALUE_EX_A_HOOK
+            .get()
+            .expect("RegQueryValueExA hook not installed")
+            .call(hkey, value, reserved, kind_out, data, size_out)
+    };
+    if trace_enabled() {
+        report_reg_query_value(
+            "RegQueryValueExA",
+            hkey,
+            ansi_text(value),
+            describe_reg_query_result(status, kind_out, data, size_out, false),
+        );
+    }
+    status
+}
+
+/// Not reported; only keeps the handle map from handing a recycled handle a stale path.
+unsafe extern "system" fn reg_close_key_detour(hkey: isize) -> i32 {
+    let _active = ActiveCall::enter();
+    key_paths()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(&hkey);
+    unsafe {
+        REG_CLOSE_KEY_HOOK
+            .get()
+            .expect("RegCloseKey hook not installed")
+            .call(hkey)
+    }
+}
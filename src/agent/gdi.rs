@@ -0,0 +1,363 @@
+//! GDI hook group: blits, DIB transfers, window DCs and device caps.
+//!
+//! Every DC in a summary is followed by the window it draws to (`0x0` for memory and printer
+//! DCs), so GDI fallbacks can be matched to the game window.
+
+use crate::agent::detach::ActiveCall;
+use crate::decode::structs::{BITMAPINFOHEADER_SIZE, Rect, parse_bitmap_info_header};
+use crate::{
+    bool_result, describe_struct_ptr, install_late_detour, make_event, read_out_ptr, send_event,
+    try_resolve_proc_in_loaded_module,
+};
+use retour::GenericDetour;
+use std::ffi::c_void;
+use std::sync::OnceLock;
+use windows_sys::Win32::Graphics::Gdi::WindowFromDC;
+
+type FnBitBlt = unsafe extern "system" fn(isize, i32, i32, i32, i32, isize, i32, i32, u32) -> i32;
+type FnStretchBlt =
+    unsafe extern "system" fn(isize, i32, i32, i32, i32, isize, i32, i32, i32, i32, u32) -> i32;
+type FnStretchDIBits = unsafe extern "system" fn(
+    isize,
+    i32,
+    i32,
+    i32,
+    i32,
+    i32,
+    i32,
+    i32,
+    i32,
+    *const c_void,
+    *const c_void,
+    u32,
+    u32,
+) -> i32;
+type FnSetDIBitsToDevice = unsafe extern "system" fn(
+    isize,
+    i32,
+    i32,
+    u32,
+    u32,
+    i32,
+    i32,
+    u32,
+    u32,
+    *const c_void,
+    *const c_void,
+    u32,
+) -> i32;
+type FnCreateDIBSection =
+    unsafe extern "system" fn(isize, *const c_void, u32, *mut *mut c_void, isize, u32) -> isize;
+type FnGetDC = unsafe extern "system" fn(isize) -> isize;
+type FnReleaseDC = unsafe extern "system" fn(isize, isize) -> i32;
+type FnGetDeviceCaps = unsafe extern "system" fn(isize, i32) -> i32;
+
+static BIT_BLT_HOOK: OnceLock<GenericDetour<FnBitBlt>> = OnceLock::new();
+static STRETCH_BLT_HOOK: OnceLock<GenericDetour<FnStretchBlt>> = OnceLock::new();
+static STRETCH_DI_BITS_HOOK: OnceLock<GenericDetour<FnStretchDIBits>> = OnceLock::new();
+static SET_DI_BITS_TO_DEVICE_HOOK: OnceLock<GenericDetour<FnSetDIBitsToDevice>> = OnceLock::new();
+static CREATE_DIB_SECTION_HOOK: OnceLock<GenericDetour<FnCreateDIBSection>> = OnceLock::new();
+static GET_DC_HOOK: OnceLock<GenericDetour<FnGetDC>> = OnceLock::new();
+static RELEASE_DC_HOOK: OnceLock<GenericDetour<FnReleaseDC>> = OnceLock::new();
+static GET_DEVICE_CAPS_HOOK: OnceLock<GenericDetour<FnGetDeviceCaps>> = OnceLock::new();
+
+/// Installs whatever part of the group is loaded; safe to call again after every module load.
+pub fn try_install_gdi_hooks() -> Result<(), String> {
+    unsafe {
+        install_late_detour(
+            &BIT_BLT_HOOK,
+            try_resolve_proc_in_loaded_module(b"gdi32.dll\0", b"BitBlt\0"),
+            bit_blt_detour,
+            "BitBlt",
+        )?;
+        install_late_detour(
+            &STRETCH_BLT_HOOK,
+            try_resolve_proc_in_loaded_module(b"gdi32.dll\0", b"StretchBlt\0"),
+            stretch_blt_detour,
+            "StretchBlt",
+        )?;
+        install_late_detour(
+            &STRETCH_DI_BITS_HOOK,
+            try_resolve_proc_in_loaded_module(b"gdi32.dll\0", b"StretchDIBits\0"),
+            stretch_di_bits_detour,
+            "StretchDIBits",
+        )?;
+        install_late_detour(
+            &SET_DI_BITS_TO_DEVICE_HOOK,
+            try_resolve_proc_in_loaded_module(b"gdi32.dll\0", b"SetDIBitsToDevice\0"),
+            set_di_bits_to_device_detour,
+            "SetDIBitsToDevice",
+        )?;
+        install_late_detour(
+            &CREATE_DIB_SECTION_HOOK,
+            try_resolve_proc_in_loaded_module(b"gdi32.dll\0", b"CreateDIBSection\0"),
+            create_dib_section_detour,
+            "CreateDIBSection",
+        )?;
+        install_late_detour(
+            &GET_DEVICE_CAPS_HOOK,
+            try_resolve_proc_in_loaded_module(b"gdi32.dll\0", b"GetDeviceCaps\0"),
+            get_device_caps_detour,
+            "GetDeviceCaps",
+        )?;
+        install_late_detour(
+            &GET_DC_HOOK,
+            try_resolve_proc_in_loaded_module(b"user32.dll\0", b"GetDC\0"),
+            get_dc_detour,
+            "GetDC",
+        )?;
+        install_late_detour(
+            &RELEASE_DC_HOOK,
+            try_resolve_proc_in_loaded_module(b"user32.dll\0", b"ReleaseDC\0"),
+            release_dc_detour,
+            "ReleaseDC",
+        )?;
+    }
+    Ok(())
+}
+
+/// `hdc=0x... hdc_hwnd=0x...` for a DC. Blits look the window up before the call, while the DC
+/// is known to be valid.
+fn describe_dc(key: &str, hdc: isize) -> String {
+    let hwnd = if hdc == 0 {
+        0
+    } else {
+        unsafe { WindowFromDC(hdc as _) as usize }
+    };
+    format!("{key}=0x{hdc:016X} {key}_hwnd=0x{hwnd:016X}")
+}
+
+fn describe_extent(x: i32, y: i32, width: i32, height: i32) -> String {
+    Rect {
+        left: x,
+        top: y,
+        right: x.saturating_add(width),
+        bottom: y.saturating_add(height),
+    }
+    .to_string()
+}
+
+fn describe_bitmap_info(bmi: *const c_void) -> String {
+    describe_struct_ptr(bmi, BITMAPINFOHEADER_SIZE, |bytes| {
+        parse_bitmap_info_header(bytes).map(|header| header.to_string())
+    })
+}
+
+unsafe extern "system" fn bit_blt_detour(
+    hdc: isize,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    hdc_src: isize,
+    x_src: i32,
+    y_src: i32,
+    rop: u32,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let dst = describe_dc("hdc", hdc);
+    let src = describe_dc("src_hdc", hdc_src);
+    let result = unsafe {
+        BIT_BLT_HOOK
+            .get()
+            .expect("BitBlt hook not installed")
+            .call(hdc, x, y, width, height, hdc_src, x_src, y_src, rop)
+    };
+    send_event(make_event(
+        "BitBlt",
+        format!(
+            "{dst} dst={} {src} src=({x_src},{y_src}) rop=0x{rop:08X}",
+            describe_extent(x, y, width, height)
+        ),
+        bool_result(result),
+    ));
+    result
+}
+
+unsafe extern "system" fn stretch_blt_detour(
+    hdc: isize,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    hdc_src: isize,
+    x_src: i32,
+    y_src: i32,
+    width_src: i32,
+    height_src: i32,
+    rop: u32,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let dst = describe_dc("hdc", hdc);
+    let src = describe_dc("src_hdc", hdc_src);
+    let result = unsafe {
+        STRETCH_BLT_HOOK
+            .get()
+            .expect("StretchBlt hook not installed")
+            .call(
+                hdc, x, y, width, height, hdc_src, x_src, y_src, width_src, height_src, rop,
+            )
+    };
+    send_event(make_event(
+        "StretchBlt",
+        format!(
+            "{dst} dst={} {src} src={} rop=0x{rop:08X}",
+            describe_extent(x, y, width, height),
+            describe_extent(x_src, y_src, width_src, height_src)
+        ),
+        bool_result(result),
+    ));
+    result
+}
+
+unsafe extern "system" fn stretch_di_bits_detour(
+    hdc: isize,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    x_src: i32,
+    y_src: i32,
+    width_src: i32,
+    height_src: i32,
+    bits: *const c_void,
+    bmi: *const c_void,
+    usage: u32,
+    rop: u32,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let dst = describe_dc("hdc", hdc);
+    let result = unsafe {
+        STRETCH_DI_BITS_HOOK
+            .get()
+            .expect("StretchDIBits hook not installed")
+            .call(
+                hdc, x, y, width, height, x_src, y_src, width_src, height_src, bits, bmi, usage,
+                rop,
+            )
+    };
+    // The result is the number of scan lines copied; zero means failure.
+    send_event(make_event(
+        "StretchDIBits",
+        format!(
+            "{dst} dst={} src={} bits={bits:p} {} usage={usage} rop=0x{rop:08X}",
+            describe_extent(x, y, width, height),
+            describe_extent(x_src, y_src, width_src, height_src),
+            describe_bitmap_info(bmi)
+        ),
+        format!("lines={result}"),
+    ));
+    result
+}
+
+unsafe extern "system" fn set_di_bits_to_device_detour(
+    hdc: isize,
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    x_src: i32,
+    y_src: i32,
+    start_scan: u32,
+    lines: u32,
+    bits: *const c_void,
+    bmi: *const c_void,
+    usage: u32,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let dst = describe_dc("hdc", hdc);
+    let result = unsafe {
+        SET_DI_BITS_TO_DEVICE_HOOK
+            .get()
+            .expect("SetDIBitsToDevice hook not installed")
+            .call(
+                hdc, x, y, width, height, x_src, y_src, start_scan, lines, bits, bmi, usage,
+            )
+    };
+    send_event(make_event(
+        "SetDIBitsToDevice",
+        format!(
+            "{dst} dst={} src=({x_src},{y_src}) start_scan={start_scan} scan_lines={lines} bits={bits:p} {} usage={usage}",
+            describe_extent(x, y, width as i32, height as i32),
+            describe_bitmap_info(bmi)
+        ),
+        format!("lines={result}"),
+    ));
+    result
+}
+
+unsafe extern "system" fn create_dib_section_detour(
+    hdc: isize,
+    bmi: *const c_void,
+    usage: u32,
+    bits_out: *mut *mut c_void,
+    section: isize,
+    offset: u32,
+) -> isize {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        CREATE_DIB_SECTION_HOOK
+            .get()
+            .expect("CreateDIBSection hook not installed")
+            .call(hdc, bmi, usage, bits_out, section, offset)
+    };
+    send_event(make_event(
+        "CreateDIBSection",
+        format!(
+            "{} {} usage={usage} section=0x{section:016X} offset={offset}",
+            describe_dc("hdc", hdc),
+            describe_bitmap_info(bmi)
+        ),
+        format!("hbitmap=0x{result:016X} bits={:p}", read_out_ptr(bits_out)),
+    ));
+    result
+}
+
+unsafe extern "system" fn get_dc_detour(hwnd: isize) -> isize {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        GET_DC_HOOK
+            .get()
+            .expect("GetDC hook not installed")
+            .call(hwnd)
+    };
+    // A null window means the whole screen.
+    send_event(make_event(
+        "GetDC",
+        format!("hwnd=0x{hwnd:016X}"),
+        format!("hdc=0x{result:016X}"),
+    ));
+    result
+}
+
+unsafe extern "system" fn release_dc_detour(hwnd: isize, hdc: isize) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        RELEASE_DC_HOOK
+            .get()
+            .expect("ReleaseDC hook not installed")
+            .call(hwnd, hdc)
+    };
+    send_event(make_event(
+        "ReleaseDC",
+        format!("hwnd=0x{hwnd:016X} hdc=0x{hdc:016X}"),
+        bool_result(result),
+    ));
+    result
+}
+
+unsafe extern "system" fn get_device_caps_detour(hdc: isize, index: i32) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        GET_DEVICE_CAPS_HOOK
+            .get()
+            .expect("GetDeviceCaps hook not installed")
+            .call(hdc, index)
+    };
+    send_event(make_event(
+        "GetDeviceCaps",
+        format!("{} index={index}", describe_dc("hdc", hdc)),
+        format!("value={result}"),
+    ));
+    result
+}
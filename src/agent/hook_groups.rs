@@ -0,0 +1,325 @@
+//! Optional hook groups, keyed by the module that provides them and installed as it loads.
+//!
+//! Each group/module pair starts out waiting and moves to installed (or failed) the first pass
+//! after its module is loaded and the group is enabled; every transition is reported once as a
+//! `HookGroupInstalled` event carrying the module path. Loads are seen twice: by the loader's
+//! DLL notification before `LoadLibrary*` returns, and by the `LoadLibrary*` detours afterwards,
+//! which also cover loads the notification had to skip.
+
+use crate::agent::detach::{ActiveCall, agent_detached};
+use crate::agent::file_registry::{file_registry_trace_enabled, try_install_file_registry_hooks};
+use crate::agent::gdi::try_install_gdi_hooks;
+use crate::agent::input::try_install_input_hooks;
+use crate::agent::opengl::try_install_opengl_hooks;
+use crate::agent::proc_address::try_install_get_proc_address_hook;
+use crate::agent::timing::{timing_trace_enabled, try_install_timing_hooks};
+use crate::{
+    get_module_file_name_lossy, install_optional_com_hooks, install_optional_d3d11_hooks,
+    install_optional_direct3d9_hooks, install_optional_directdraw_hooks,
+    install_optional_dxgi_hooks, make_event, send_event, try_resolve_proc_in_loaded_module,
+};
+use std::ffi::c_void;
+use std::sync::{Mutex, PoisonError, TryLockError};
+use windows_sys::Win32::Foundation::{HMODULE, UNICODE_STRING};
+use windows_sys::Win32::System::LibraryLoader::GetModuleHandleA;
+
+const LDR_DLL_NOTIFICATION_REASON_LOADED: u32 = 1;
+
+/// The leading fields of `LDR_DLL_LOADED_NOTIFICATION_DATA`, which is only read through a pointer.
+#[repr(C)]
+struct LdrDllNotificationData {
+    _flags: u32,
+    _full_dll_name: *const UNICODE_STRING,
+    base_dll_name: *const UNICODE_STRING,
+}
+
+type FnLdrDllNotification =
+    unsafe extern "system" fn(u32, *const LdrDllNotificationData, *mut c_void);
+type FnLdrRegisterDllNotification =
+    unsafe extern "system" fn(u32, FnLdrDllNotification, *mut c_void, *mut *mut c_void) -> i32;
+type FnLdrUnregisterDllNotification = unsafe extern "system" fn(*mut c_void) -> i32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GroupState {
+    Waiting,
+    Installed,
+    Failed,
+}
+
+struct HookGroup {
+    name: &'static str,
+    module: &'static [u8],
+    /// Opt-in groups stay waiting while the UI has them turned off.
+    enabled: fn() -> bool,
+    /// Installs whatever part of the group is loaded; idempotent.
+    install: fn() -> Result<(), String>,
+}
+
+fn always() -> bool {
+    true
+}
+
+/// A group that spans several modules has one entry per module, in install order.
+const HOOK_GROUPS: [HookGroup; 17] = [
+    HookGroup {
+        name: "directdraw",
+        module: b"ddraw.dll\0",
+        enabled: always,
+        install: install_optional_directdraw_hooks,
+    },
+    HookGroup {
+        name: "com",
+        module: b"ole32.dll\0",
+        enabled: always,
+        install: install_optional_com_hooks,
+    },
+    HookGroup {
+        name: "direct3d9",
+        module: b"d3d9.dll\0",
+        enabled: always,
+        install: install_optional_direct3d9_hooks,
+    },
+    HookGroup {
+        name: "dxgi",
+        module: b"dxgi.dll\0",
+        enabled: always,
+        install: install_optional_dxgi_hooks,
+    },
+    HookGroup {
+        name: "d3d11",
+        module: b"d3d11.dll\0",
+        enabled: always,
+        install: install_optional_d3d11_hooks,
+    },
+    HookGroup {
+        name: "opengl",
+        module: b"gdi32.dll\0",
+        enabled: always,
+        install: try_install_opengl_hooks,
+    },
+    HookGroup {
+        name: "opengl",
+        module: b"opengl32.dll\0",
+        enabled: always,
+        install: try_install_opengl_hooks,
+    },
+    HookGroup {
+        name: "gdi",
+        module: b"gdi32.dll\0",
+        enabled: always,
+        install: try_install_gdi_hooks,
+    },
+    HookGroup {
+        name: "gdi",
+        module: b"user32.dll\0",
+        enabled: always,
+        install: try_install_gdi_hooks,
+    },
+    HookGroup {
+        name: "input",
+        module: b"user32.dll\0",
+        enabled: always,
+        install: try_install_input_hooks,
+    },
+    HookGroup {
+        name: "input",
+        module: b"dinput.dll\0",
+        enabled: always,
+        install: try_install_input_hooks,
+    },
+    HookGroup {
+        name: "input",
+        module: b"dinput8.dll\0",
+        enabled: always,
+        install: try_install_input_hooks,
+    },
+    HookGroup {
+        name: "timing",
+        module: b"kernel32.dll\0",
+        enabled: timing_trace_enabled,
+        install: try_install_timing_hooks,
+    },
+    HookGroup {
+        name: "timing",
+        module: b"winmm.dll\0",
+        enabled: timing_trace_enabled,
+        install: try_install_timing_hooks,
+    },
+    HookGroup {
+        name: "file_registry",
+        module: b"kernel32.dll\0",
+        enabled: file_registry_trace_enabled,
+        install: try_install_file_registry_hooks,
+    },
+    HookGroup {
+        name: "file_registry",
+        module: b"advapi32.dll\0",
+        enabled: file_registry_trace_enabled,
+        install: try_install_file_registry_hooks,
+    },
+    HookGroup {
+        name: "get_proc_address",
+        module: b"kernel32.dll\0",
+        enabled: always,
+        install: try_install_get_proc_address_hook,
+    },
+];
+
+/// Held for a whole install pass, so two passes never detour the same target.
+static GROUP_STATES: Mutex<[GroupState; HOOK_GROUPS.len()]> =
+    Mutex::new([GroupState::Waiting; HOOK_GROUPS.len()]);
+static DLL_NOTIFICATION_COOKIE: Mutex<Option<usize>> = Mutex::new(None);
+
+/// Installs every enabled group whose module is loaded; `trigger` names what prompted the pass.
+/// Keeps going past a failing group and returns the first error.
+pub fn install_loaded_hook_groups(trigger: &str) -> Result<(), String> {
+    let mut states = GROUP_STATES
+        .lock()
+        .map_err(|_| "hook group lock poisoned".to_owned())?;
+    if agent_detached() {
+        return Ok(());
+    }
+    install_groups(&mut states[..], trigger, |_| true)
+}
+
+/// Like `install_loaded_hook_groups`, but returns `Ok(false)` instead of waiting when another
+/// pass is running. For callers that may hold the loader lock, which that pass can need.
+pub fn install_loaded_hook_groups_nonblocking(trigger: &str) -> Result<bool, String> {
+    let mut states = match GROUP_STATES.try_lock() {
+        Ok(states) => states,
+        Err(TryLockError::WouldBlock) => return Ok(false),
+        Err(TryLockError::Poisoned(_)) => return Err("hook group lock poisoned".to_owned()),
+    };
+    if agent_detached() {
+        return Ok(true);
+    }
+    install_groups(&mut states[..], trigger, |_| true).map(|()| true)
+}
+
+pub fn register_dll_notification() -> Result<(), String> {
+    let mut registered = DLL_NOTIFICATION_COOKIE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if registered.is_some() || agent_detached() {
+        return Ok(());
+    }
+    let register: FnLdrRegisterDllNotification = unsafe {
+        try_resolve_proc_in_loaded_module(b"ntdll.dll\0", b"LdrRegisterDllNotification\0")
+    }
+    .ok_or_else(|| "LdrRegisterDllNotification unavailable".to_owned())?;
+
+    let mut cookie = std::ptr::null_mut();
+    let status = unsafe { register(0, dll_notification, std::ptr::null_mut(), &mut cookie) };
+    if status < 0 {
+        return Err(format!(
+            "LdrRegisterDllNotification failed: status=0x{:08X}",
+            status as u32
+        ));
+    }
+    *registered = Some(cookie as usize);
+    Ok(())
+}
+
+/// Stops the notification and waits out any install pass in progress; passes that start after
+/// the agent detached install nothing.
+pub fn unregister_dll_notification() {
+    if let Some(cookie) = DLL_NOTIFICATION_COOKIE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+    {
+        let unregister: Option<FnLdrUnregisterDllNotification> = unsafe {
+            try_resolve_proc_in_loaded_module(b"ntdll.dll\0", b"LdrUnregisterDllNotification\0")
+        };
+        if let Some(unregister) = unregister {
+            unsafe { unregister(cookie as *mut c_void) };
+        }
+    }
+    drop(GROUP_STATES.lock());
+}
+
+fn install_groups(
+    states: &mut [GroupState],
+    trigger: &str,
+    wanted: impl Fn(&HookGroup) -> bool,
+) -> Result<(), String> {
+    let mut first_error = None;
+    for (group, state) in HOOK_GROUPS.iter().zip(states.iter_mut()) {
+        if *state == GroupState::Installed || !wanted(group) || !(group.enabled)() {
+            continue;
+        }
+        let module = unsafe { GetModuleHandleA(group.module.as_ptr()) };
+        if module.is_null() {
+            continue;
+        }
+
+        let result = (group.install)();
+        let next = if result.is_ok() {
+            GroupState::Installed
+        } else {
+            GroupState::Failed
+        };
+        if next != *state {
+            report_group(group, module, trigger, &result);
+            *state = next;
+        }
+        if let Err(error) = result {
+            first_error.get_or_insert(error);
+        }
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+fn report_group(group: &HookGroup, module: HMODULE, trigger: &str, result: &Result<(), String>) {
+    let summary = format!(
+        "group={} module=\"{}\" trigger={trigger}",
+        group.name,
+        get_module_file_name_lossy(module)
+    );
+    let result = match result {
+        Ok(()) => "OK".to_owned(),
+        Err(error) => error.clone(),
+    };
+    send_event(make_event("HookGroupInstalled", summary, result));
+}
+
+fn unicode_string_lossy(text: *const UNICODE_STRING) -> String {
+    if text.is_null() {
+        return String::new();
+    }
+    let text = unsafe { &*text };
+    if text.Buffer.is_null() {
+        return String::new();
+    }
+    let units = unsafe { std::slice::from_raw_parts(text.Buffer, usize::from(text.Length) / 2) };
+    String::from_utf16_lossy(units)
+}
+
+fn module_matches(group: &HookGroup, base_name: &str) -> bool {
+    let module = group.module.strip_suffix(b"\0").unwrap_or(group.module);
+    base_name.as_bytes().eq_ignore_ascii_case(module)
+}
+
+/// Runs under the loader lock, after the module is mapped but before its `DllMain`.
+unsafe extern "system" fn dll_notification(
+    reason: u32,
+    data: *const LdrDllNotificationData,
+    _context: *mut c_void,
+) {
+    let _active = ActiveCall::enter();
+    if reason != LDR_DLL_NOTIFICATION_REASON_LOADED || data.is_null() {
+        return;
+    }
+    let base_name = unicode_string_lossy(unsafe { (*data).base_dll_name });
+    // A pass on another thread may be waiting for the loader lock this thread holds, so never
+    // wait for it; the `LoadLibrary*` detours run another pass once the load returns.
+    let Ok(mut states) = GROUP_STATES.try_lock() else {
+        return;
+    };
+    if agent_detached() {
+        return;
+    }
+    let _ = install_groups(&mut states[..], "LdrDllNotification", |group| {
+        module_matches(group, &base_name)
+    });
+}
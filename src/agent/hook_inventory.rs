@@ -0,0 +1,262 @@
+//! Inventory of every hook the agent placed, for working out why a hook never fires.
+//!
+//! Hooks are recorded as they are enabled, with the bytes at their target before and after
+//! patching. `ReportHookInventory` re-reads every target, so a hook that another overlay or
+//! injector has since patched over reports different current bytes. Calls are counted from the
+//! events each hook sends; hooks that aggregate or filter their events count calls themselves.
+//! Detaching disables the recorded detours through the same list.
+
+use crate::agent::detach::agent_detached;
+use crate::model::ipc::{HOOK_INVENTORY_API, HOOK_INVENTORY_ENTRY_API};
+use crate::{
+    bool_result, get_module_file_name_lossy, hex_bytes, make_event, send_event, try_read_bytes,
+};
+use retour::{Function, GenericDetour};
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::sync::{Mutex, PoisonError};
+use windows_sys::Win32::System::Memory::{MEMORY_BASIC_INFORMATION, VirtualQuery};
+
+/// Enough to cover the jump an inline hook writes on either architecture.
+const PROLOGUE_LEN: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallMethod {
+    /// An export patched in place.
+    Inline,
+    /// A COM method implementation found through a vtable slot, patched in place.
+    Vtable,
+    /// One interface pointed at a copied vtable; its target is the interface itself.
+    #[cfg(target_pointer_width = "32")]
+    VtableCopy,
+}
+
+impl InstallMethod {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Inline => "inline",
+            Self::Vtable => "vtable",
+            #[cfg(target_pointer_width = "32")]
+            Self::VtableCopy => "vtable_copy",
+        }
+    }
+
+    fn patch_len(self) -> usize {
+        match self {
+            Self::Inline | Self::Vtable => PROLOGUE_LEN,
+            #[cfg(target_pointer_width = "32")]
+            Self::VtableCopy => std::mem::size_of::<usize>(),
+        }
+    }
+}
+
+/// The parts of a `GenericDetour` the inventory needs, independent of its signature.
+trait DetourControl: Sync {
+    fn is_enabled(&self) -> bool;
+    fn disable(&self) -> Result<(), retour::Error>;
+}
+
+impl<F: Function> DetourControl for GenericDetour<F> {
+    fn is_enabled(&self) -> bool {
+        GenericDetour::is_enabled(self)
+    }
+
+    fn disable(&self) -> Result<(), retour::Error> {
+        unsafe { GenericDetour::disable(self) }
+    }
+}
+
+struct TrackedHook {
+    name: String,
+    method: InstallMethod,
+    target: usize,
+    /// The bytes at `target` before the hook was placed.
+    original: Vec<u8>,
+    /// The bytes at `target` right after it was placed.
+    patched: Vec<u8>,
+    /// `None` for vtable copies, which only detaching restores.
+    detour: Option<&'static dyn DetourControl>,
+}
+
+impl TrackedHook {
+    fn enabled(&self, current: &[u8]) -> bool {
+        match self.detour {
+            Some(detour) => detour.is_enabled(),
+            None => current == self.patched,
+        }
+    }
+}
+
+/// In install order. Held while a detour is enabled, so detaching never misses one.
+static HOOKS: Mutex<Vec<TrackedHook>> = Mutex::new(Vec::new());
+static CALL_COUNTS: Mutex<Option<HashMap<String, u64>>> = Mutex::new(None);
+
+/// Enables `detour` on `target` and records it. Fails once the agent has detached.
+pub fn enable_tracked_detour<F: Function>(
+    detour: &'static GenericDetour<F>,
+    target: F,
+    name: &str,
+    method: InstallMethod,
+) -> Result<(), String> {
+    let mut hooks = HOOKS.lock().unwrap_or_else(PoisonError::into_inner);
+    if agent_detached() {
+        return Err("agent detached".to_owned());
+    }
+    let target = target.to_ptr() as usize;
+    let original = read_target(target, method);
+    unsafe { detour.enable() }.map_err(|error| error.to_string())?;
+    hooks.push(TrackedHook {
+        name: name.to_owned(),
+        method,
+        target,
+        original,
+        patched: read_target(target, method),
+        detour: Some(detour),
+    });
+    Ok(())
+}
+
+/// Records an interface that was pointed at a copied vtable.
+#[cfg(target_pointer_width = "32")]
+pub fn track_vtable_copy(
+    name: &str,
+    instance: usize,
+    original_vtable: usize,
+    copied_vtable: usize,
+) {
+    HOOKS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(TrackedHook {
+            name: name.to_owned(),
+            method: InstallMethod::VtableCopy,
+            target: instance,
+            original: original_vtable.to_le_bytes().to_vec(),
+            patched: copied_vtable.to_le_bytes().to_vec(),
+            detour: None,
+        });
+}
+
+/// Disables every recorded detour that is still enabled, newest first since a later detour may
+/// sit on top of an earlier one's target. Returns how many were disabled and the names of those
+/// that would not.
+pub fn disable_tracked_detours() -> (usize, Vec<String>) {
+    let hooks = HOOKS.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut disabled = 0;
+    let mut failures = Vec::new();
+    for hook in hooks.iter().rev() {
+        let Some(detour) = hook.detour.filter(|detour| detour.is_enabled()) else {
+            continue;
+        };
+        match detour.disable() {
+            Ok(()) => disabled += 1,
+            Err(_) => failures.push(hook.name.clone()),
+        }
+    }
+    (disabled, failures)
+}
+
+/// Counts one call of the hook named `name`. Every event sent counts under its API name, so only
+/// hooks whose calls do not map one-to-one onto their events call this themselves.
+pub fn count_hook_call(name: &str) {
+    count_hook_calls(name, 1);
+}
+
+pub fn count_hook_calls(name: &str, calls: u64) {
+    if calls == 0 {
+        return;
+    }
+    let mut counts = CALL_COUNTS.lock().unwrap_or_else(PoisonError::into_inner);
+    let counts = counts.get_or_insert_with(HashMap::new);
+    match counts.get_mut(name) {
+        Some(count) => *count += calls,
+        None => {
+            counts.insert(name.to_owned(), calls);
+        }
+    }
+}
+
+/// Sends a `HookInventory` header followed by one `HookInventoryEntry` per hook:
+/// `name="X" method=inline target=0x.. module="path"` /
+/// `state=active enabled=TRUE calls=N original=8BFF55.. current=E9....`.
+pub fn report_hook_inventory() {
+    let hooks = HOOKS.lock().unwrap_or_else(PoisonError::into_inner);
+    let counts = CALL_COUNTS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+        .unwrap_or_default();
+
+    let entries: Vec<(String, String)> = hooks
+        .iter()
+        .map(|hook| describe_hook(hook, counts.get(&hook.name).copied().unwrap_or(0)))
+        .collect();
+    let overwritten = entries
+        .iter()
+        .filter(|(_, result)| result.starts_with("state=overwritten"))
+        .count();
+    send_event(make_event(
+        HOOK_INVENTORY_API,
+        format!("hooks={} overwritten={overwritten}", entries.len()),
+        "OK".to_owned(),
+    ));
+    for (summary, result) in entries {
+        send_event(make_event(HOOK_INVENTORY_ENTRY_API, summary, result));
+    }
+}
+
+fn describe_hook(hook: &TrackedHook, calls: u64) -> (String, String) {
+    let current = read_target(hook.target, hook.method);
+    let enabled = hook.enabled(&current);
+    // An enabled hook whose bytes changed was patched over; a disabled one whose target no
+    // longer matches the original was patched by someone else after we let go.
+    let state = match (enabled, current == hook.patched, current == hook.original) {
+        (true, true, _) => "active",
+        (true, false, _) => "overwritten",
+        (false, _, true) => "disabled",
+        (false, _, false) => "modified",
+    };
+    let summary = format!(
+        "name=\"{}\" method={} target=0x{:X} module=\"{}\"",
+        hook.name,
+        hook.method.label(),
+        hook.target,
+        get_module_file_name_lossy(allocation_base(hook.target))
+    );
+    let result = format!(
+        "state={state} enabled={} calls={calls} original={} current={}",
+        bool_result(i32::from(enabled)),
+        describe_bytes(&hook.original),
+        describe_bytes(&current)
+    );
+    (summary, result)
+}
+
+/// Empty when the target is no longer readable.
+fn read_target(target: usize, method: InstallMethod) -> Vec<u8> {
+    try_read_bytes(target as *const c_void, method.patch_len()).unwrap_or_default()
+}
+
+fn describe_bytes(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        "unreadable".to_owned()
+    } else {
+        hex_bytes(bytes)
+    }
+}
+
+/// The module an address belongs to, or null for heap and stub memory.
+fn allocation_base(address: usize) -> *mut c_void {
+    let mut mbi: MEMORY_BASIC_INFORMATION = unsafe { std::mem::zeroed() };
+    let queried = unsafe {
+        VirtualQuery(
+            address as *const c_void,
+            &mut mbi,
+            std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
+        )
+    };
+    if queried == 0 {
+        return std::ptr::null_mut();
+    }
+    mbi.AllocationBase
+}
@@ -0,0 +1,462 @@
+//! Input hook group: cursor clipping and capture, raw input registration and DirectInput.
+//!
+//! DirectInput interfaces are reached the same way as DirectDraw's: the creation exports return
+//! an `IDirectInput*` whose `CreateDevice` hands out the devices. The revision is the DirectInput
+//! interface version (1, 7 or 8); only IDirectInput7 has `CreateDeviceEx` in slot 9.
+
+use super::com_vtable::{VtableHookEntry, install_vtable_hooks, vtable_hook};
+use super::detach::ActiveCall;
+use crate::decode::structs::parse_raw_input_devices;
+use crate::{
+    Guid, bool_result, describe_guid_ptr, describe_rect_ptr, guid_ptr_matches, hresult_result,
+    hresult_succeeded, install_late_detour, make_event, read_out_ptr, send_event, try_read_bytes,
+    try_resolve_proc_in_loaded_module,
+};
+use retour::GenericDetour;
+use std::ffi::c_void;
+use std::sync::OnceLock;
+use windows_sys::Win32::Foundation::RECT;
+
+const DIRECTINPUT_REVISION: u32 = 1;
+const DIRECTINPUT7_REVISION: u32 = 7;
+const DIRECTINPUT8_REVISION: u32 = 8;
+/// Every device interface has the methods we hook in the same slots.
+const DIRECTINPUTDEVICE_REVISION: u32 = 1;
+/// More entries than any game registers; keeps a bogus count from reading far.
+const MAX_RAW_INPUT_DEVICES: u32 = 16;
+
+// IDirectInput vtable layout (IDirectInput7 appends CreateDeviceEx at 9).
+const DI_METHOD_CREATE_DEVICE_INDEX: usize = 3;
+const DI7_METHOD_CREATE_DEVICE_EX_INDEX: usize = 9;
+
+// IDirectInputDevice vtable layout, shared by every revision up to IDirectInputDevice8.
+const DIDEV_METHOD_ACQUIRE_INDEX: usize = 7;
+const DIDEV_METHOD_UNACQUIRE_INDEX: usize = 8;
+const DIDEV_METHOD_SET_COOPERATIVE_LEVEL_INDEX: usize = 13;
+
+const IID_IDIRECTINPUT7A: Guid = Guid {
+    data1: 0x9A4CB684,
+    data2: 0x236D,
+    data3: 0x11D3,
+    data4: [0x8E, 0x9D, 0x00, 0xC0, 0x4F, 0x68, 0x44, 0xAE],
+};
+const IID_IDIRECTINPUT7W: Guid = Guid {
+    data1: 0x9A4CB685,
+    data2: 0x236D,
+    data3: 0x11D3,
+    data4: [0x8E, 0x9D, 0x00, 0xC0, 0x4F, 0x68, 0x44, 0xAE],
+};
+
+type FnClipCursor = unsafe extern "system" fn(*const RECT) -> i32;
+type FnSetCursorPos = unsafe extern "system" fn(i32, i32) -> i32;
+type FnShowCursor = unsafe extern "system" fn(i32) -> i32;
+type FnSetCapture = unsafe extern "system" fn(isize) -> isize;
+type FnRegisterRawInputDevices = unsafe extern "system" fn(*const c_void, u32, u32) -> i32;
+type FnDirectInputCreate =
+    unsafe extern "system" fn(isize, u32, *mut *mut c_void, *mut c_void) -> i32;
+type FnDirectInputCreateEx =
+    unsafe extern "system" fn(isize, u32, *const c_void, *mut *mut c_void, *mut c_void) -> i32;
+
+static CLIP_CURSOR_HOOK: OnceLock<GenericDetour<FnClipCursor>> = OnceLock::new();
+static SET_CURSOR_POS_HOOK: OnceLock<GenericDetour<FnSetCursorPos>> = OnceLock::new();
+static SHOW_CURSOR_HOOK: OnceLock<GenericDetour<FnShowCursor>> = OnceLock::new();
+static SET_CAPTURE_HOOK: OnceLock<GenericDetour<FnSetCapture>> = OnceLock::new();
+static REGISTER_RAW_INPUT_DEVICES_HOOK: OnceLock<GenericDetour<FnRegisterRawInputDevices>> =
+    OnceLock::new();
+static DIRECT_INPUT_CREATE_A_HOOK: OnceLock<GenericDetour<FnDirectInputCreate>> = OnceLock::new();
+static DIRECT_INPUT_CREATE_W_HOOK: OnceLock<GenericDetour<FnDirectInputCreate>> = OnceLock::new();
+static DIRECT_INPUT_CREATE_EX_HOOK: OnceLock<GenericDetour<FnDirectInputCreateEx>> =
+    OnceLock::new();
+static DIRECT_INPUT8_CREATE_HOOK: OnceLock<GenericDetour<FnDirectInputCreateEx>> = OnceLock::new();
+
+static DIRECTINPUT_VTABLE_HOOKS: &[&dyn VtableHookEntry] =
+    &[&DI_CREATE_DEVICE, &DI7_CREATE_DEVICE_EX];
+static DIRECTINPUTDEVICE_VTABLE_HOOKS: &[&dyn VtableHookEntry] = &[
+    &DIDEV_ACQUIRE,
+    &DIDEV_UNACQUIRE,
+    &DIDEV_SET_COOPERATIVE_LEVEL,
+];
+
+/// Installs whatever part of the group is loaded; safe to call again after every module load.
+pub fn try_install_input_hooks() -> Result<(), String> {
+    unsafe {
+        install_late_detour(
+            &CLIP_CURSOR_HOOK,
+            try_resolve_proc_in_loaded_module(b"user32.dll\0", b"ClipCursor\0"),
+            clip_cursor_detour,
+            "ClipCursor",
+        )?;
+        install_late_detour(
+            &SET_CURSOR_POS_HOOK,
+            try_resolve_proc_in_loaded_module(b"user32.dll\0", b"SetCursorPos\0"),
+            set_cursor_pos_detour,
+            "SetCursorPos",
+        )?;
+        install_late_detour(
+            &SHOW_CURSOR_HOOK,
+            try_resolve_proc_in_loaded_module(b"user32.dll\0", b"ShowCursor\0"),
+            show_cursor_detour,
+            "ShowCursor",
+        )?;
+        install_late_detour(
+            &SET_CAPTURE_HOOK,
+            try_resolve_proc_in_loaded_module(b"user32.dll\0", b"SetCapture\0"),
+            set_capture_detour,
+            "SetCapture",
+        )?;
+        install_late_detour(
+            &REGISTER_RAW_INPUT_DEVICES_HOOK,
+            try_resolve_proc_in_loaded_module(b"user32.dll\0", b"RegisterRawInputDevices\0"),
+            register_raw_input_devices_detour,
+            "RegisterRawInputDevices",
+        )?;
+        install_late_detour(
+            &DIRECT_INPUT_CREATE_A_HOOK,
+            try_resolve_proc_in_loaded_module(b"dinput.dll\0", b"DirectInputCreateA\0"),
+            direct_input_create_a_detour,
+            "DirectInputCreateA",
+        )?;
+        install_late_detour(
+            &DIRECT_INPUT_CREATE_W_HOOK,
+            try_resolve_proc_in_loaded_module(b"dinput.dll\0", b"DirectInputCreateW\0"),
+            direct_input_create_w_detour,
+            "DirectInputCreateW",
+        )?;
+        install_late_detour(
+            &DIRECT_INPUT_CREATE_EX_HOOK,
+            try_resolve_proc_in_loaded_module(b"dinput.dll\0", b"DirectInputCreateEx\0"),
+            direct_input_create_ex_detour,
+            "DirectInputCreateEx",
+        )?;
+        install_late_detour(
+            &DIRECT_INPUT8_CREATE_HOOK,
+            try_resolve_proc_in_loaded_module(b"dinput8.dll\0", b"DirectInput8Create\0"),
+            direct_input8_create_detour,
+            "DirectInput8Create",
+        )?;
+    }
+    Ok(())
+}
+
+fn try_install_direct_input_hooks(direct_input: *mut c_void, revision: u32, source: &str) {
+    install_vtable_hooks(
+        DIRECTINPUT_VTABLE_HOOKS,
+        direct_input,
+        revision,
+        source,
+        "DirectInputHookInstall",
+    );
+}
+
+fn try_install_direct_input_device_hooks(device: *mut c_void, source: &str) {
+    install_vtable_hooks(
+        DIRECTINPUTDEVICE_VTABLE_HOOKS,
+        device,
+        DIRECTINPUTDEVICE_REVISION,
+        source,
+        "DirectInputHookInstall",
+    );
+}
+
+fn describe_raw_input_devices(devices: *const c_void, count: u32, size: u32) -> String {
+    if devices.is_null() {
+        return "null".to_owned();
+    }
+    let shown = count.min(MAX_RAW_INPUT_DEVICES) as usize;
+    let decoded = try_read_bytes(devices, shown * size as usize).and_then(|bytes| {
+        parse_raw_input_devices(&bytes, shown, size as usize, std::mem::size_of::<usize>())
+    });
+    match decoded {
+        Some(devices) => {
+            let devices: Vec<String> = devices.iter().map(ToString::to_string).collect();
+            format!("[{}]", devices.join(","))
+        }
+        None => format!("{devices:p}"),
+    }
+}
+
+unsafe extern "system" fn clip_cursor_detour(rect: *const RECT) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        CLIP_CURSOR_HOOK
+            .get()
+            .expect("ClipCursor hook not installed")
+            .call(rect)
+    };
+    // A null rectangle releases the clip.
+    send_event(make_event(
+        "ClipCursor",
+        format!("rect={}", describe_rect_ptr(rect)),
+        bool_result(result),
+    ));
+    result
+}
+
+unsafe extern "system" fn set_cursor_pos_detour(x: i32, y: i32) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        SET_CURSOR_POS_HOOK
+            .get()
+            .expect("SetCursorPos hook not installed")
+            .call(x, y)
+    };
+    send_event(make_event(
+        "SetCursorPos",
+        format!("x={x} y={y}"),
+        bool_result(result),
+    ));
+    result
+}
+
+unsafe extern "system" fn show_cursor_detour(show: i32) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        SHOW_CURSOR_HOOK
+            .get()
+            .expect("ShowCursor hook not installed")
+            .call(show)
+    };
+    // The cursor is visible while the display count is non-negative.
+    send_event(make_event(
+        "ShowCursor",
+        format!("show={}", bool_result(show)),
+        format!("count={result}"),
+    ));
+    result
+}
+
+unsafe extern "system" fn set_capture_detour(hwnd: isize) -> isize {
+    let _active = ActiveCall::enter();
+    let previous = unsafe {
+        SET_CAPTURE_HOOK
+            .get()
+            .expect("SetCapture hook not installed")
+            .call(hwnd)
+    };
+    send_event(make_event(
+        "SetCapture",
+        format!("hwnd=0x{hwnd:016X}"),
+        format!("previous=0x{previous:016X}"),
+    ));
+    previous
+}
+
+unsafe extern "system" fn register_raw_input_devices_detour(
+    devices: *const c_void,
+    count: u32,
+    size: u32,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        REGISTER_RAW_INPUT_DEVICES_HOOK
+            .get()
+            .expect("RegisterRawInputDevices hook not installed")
+            .call(devices, count, size)
+    };
+    send_event(make_event(
+        "RegisterRawInputDevices",
+        format!(
+            "count={count} size={size} devices={}",
+            describe_raw_input_devices(devices, count, size)
+        ),
+        bool_result(result),
+    ));
+    result
+}
+
+fn report_direct_input_create(
+    api: &str,
+    instance: isize,
+    version: u32,
+    iid: Option<*const c_void>,
+    out: *mut *mut c_void,
+    result: i32,
+) {
+    let direct_input = read_out_ptr(out);
+    let iid_text = iid
+        .map(|iid| format!(" iid={}", describe_guid_ptr(iid)))
+        .unwrap_or_default();
+    send_event(make_event(
+        api,
+        format!("instance=0x{instance:016X} version=0x{version:04X}{iid_text}"),
+        format!("{} direct_input={direct_input:p}", hresult_result(result)),
+    ));
+    if !hresult_succeeded(result) {
+        return;
+    }
+    let is_direct_input7 = iid.is_some_and(|iid| {
+        guid_ptr_matches(iid, &IID_IDIRECTINPUT7A) || guid_ptr_matches(iid, &IID_IDIRECTINPUT7W)
+    });
+    let revision = if api == "DirectInput8Create" {
+        DIRECTINPUT8_REVISION
+    } else if is_direct_input7 {
+        DIRECTINPUT7_REVISION
+    } else {
+        DIRECTINPUT_REVISION
+    };
+    try_install_direct_input_hooks(direct_input, revision, api);
+}
+
+unsafe extern "system" fn direct_input_create_a_detour(
+    instance: isize,
+    version: u32,
+    direct_input_out: *mut *mut c_void,
+    outer: *mut c_void,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        DIRECT_INPUT_CREATE_A_HOOK
+            .get()
+            .expect("DirectInputCreateA hook not installed")
+            .call(instance, version, direct_input_out, outer)
+    };
+    report_direct_input_create(
+        "DirectInputCreateA",
+        instance,
+        version,
+        None,
+        direct_input_out,
+        result,
+    );
+    result
+}
+
+unsafe extern "system" fn direct_input_create_w_detour(
+    instance: isize,
+    version: u32,
+    direct_input_out: *mut *mut c_void,
+    outer: *mut c_void,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        DIRECT_INPUT_CREATE_W_HOOK
+            .get()
+            .expect("DirectInputCreateW hook not installed")
+            .call(instance, version, direct_input_out, outer)
+    };
+    report_direct_input_create(
+        "DirectInputCreateW",
+        instance,
+        version,
+        None,
+        direct_input_out,
+        result,
+    );
+    result
+}
+
+unsafe extern "system" fn direct_input_create_ex_detour(
+    instance: isize,
+    version: u32,
+    iid: *const c_void,
+    direct_input_out: *mut *mut c_void,
+    outer: *mut c_void,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        DIRECT_INPUT_CREATE_EX_HOOK
+            .get()
+            .expect("DirectInputCreateEx hook not installed")
+            .call(instance, version, iid, direct_input_out, outer)
+    };
+    report_direct_input_create(
+        "DirectInputCreateEx",
+        instance,
+        version,
+        Some(iid),
+        direct_input_out,
+        result,
+    );
+    result
+}
+
+unsafe extern "system" fn direct_input8_create_detour(
+    instance: isize,
+    version: u32,
+    iid: *const c_void,
+    direct_input_out: *mut *mut c_void,
+    outer: *mut c_void,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        DIRECT_INPUT8_CREATE_HOOK
+            .get()
+            .expect("DirectInput8Create hook not installed")
+            .call(instance, version, iid, direct_input_out, outer)
+    };
+    report_direct_input_create(
+        "DirectInput8Create",
+        instance,
+        version,
+        Some(iid),
+        direct_input_out,
+        result,
+    );
+    result
+}
+
+vtable_hook! {
+    DI_CREATE_DEVICE = "IDirectInput::CreateDevice" [DI_METHOD_CREATE_DEVICE_INDEX, 1..=8],
+    fn di_create_device_detour(
+        this,
+        guid: *const c_void,
+        device_out: *mut *mut c_void,
+        outer: *mut c_void,
+    ) -> i32,
+    |result| {
+        let device = read_out_ptr(device_out);
+        if hresult_succeeded(result) {
+            try_install_direct_input_device_hooks(device, "IDirectInput::CreateDevice");
+        }
+        (
+            format!("this={this:p} guid={} outer={outer:p}", describe_guid_ptr(guid)),
+            format!("{} device={device:p}", hresult_result(result)),
+        )
+    }
+}
+
+vtable_hook! {
+    DI7_CREATE_DEVICE_EX = "IDirectInput7::CreateDeviceEx" [DI7_METHOD_CREATE_DEVICE_EX_INDEX, 7..=7],
+    fn di7_create_device_ex_detour(
+        this,
+        guid: *const c_void,
+        iid: *const c_void,
+        device_out: *mut *mut c_void,
+        outer: *mut c_void,
+    ) -> i32,
+    |result| {
+        let device = read_out_ptr(device_out);
+        if hresult_succeeded(result) {
+            try_install_direct_input_device_hooks(device, "IDirectInput7::CreateDeviceEx");
+        }
+        (
+            format!(
+                "this={this:p} guid={} iid={} outer={outer:p}",
+                describe_guid_ptr(guid),
+                describe_guid_ptr(iid)
+            ),
+            format!("{} device={device:p}", hresult_result(result)),
+        )
+    }
+}
+
+vtable_hook! {
+    DIDEV_ACQUIRE = "IDirectInputDevice::Acquire" [DIDEV_METHOD_ACQUIRE_INDEX, 1..=1],
+    fn didev_acquire_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DIDEV_UNACQUIRE = "IDirectInputDevice::Unacquire" [DIDEV_METHOD_UNACQUIRE_INDEX, 1..=1],
+    fn didev_unacquire_detour(this) -> i32,
+    |result| (format!("this={this:p}"), hresult_result(result))
+}
+
+vtable_hook! {
+    DIDEV_SET_COOPERATIVE_LEVEL = "IDirectInputDevice::SetCooperativeLevel" [DIDEV_METHOD_SET_COOPERATIVE_LEVEL_INDEX, 1..=1],
+    fn didev_set_cooperative_level_detour(this, hwnd: isize, flags: u32) -> i32,
+    |result| (
+        format!("this={this:p} hwnd=0x{hwnd:016X} flags=0x{flags:08X}"),
+        hresult_result(result),
+    )
+}
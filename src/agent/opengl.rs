@@ -0,0 +1,234 @@
+//! OpenGL/WGL hook group: context creation, pixel format selection and buffer swaps.
+//!
+//! `wglSwapIntervalEXT` is not exported; it is detoured when `wglGetProcAddress` hands it out.
+
+use crate::agent::detach::ActiveCall;
+use crate::decode::structs::{PIXELFORMATDESCRIPTOR_SIZE, parse_pixel_format_descriptor};
+use crate::{
+    bool_result, describe_struct_ptr, install_late_detour, make_event, ptr_to_fn,
+    read_c_string_lossy, send_event, try_resolve_proc_in_loaded_module,
+};
+use retour::GenericDetour;
+use std::ffi::c_void;
+use std::sync::OnceLock;
+
+type FnWglCreateContext = unsafe extern "system" fn(isize) -> isize;
+type FnWglMakeCurrent = unsafe extern "system" fn(isize, isize) -> i32;
+type FnWglGetProcAddress = unsafe extern "system" fn(*const u8) -> *const c_void;
+type FnWglSwapBuffers = unsafe extern "system" fn(isize) -> i32;
+type FnSwapBuffers = unsafe extern "system" fn(isize) -> i32;
+type FnChoosePixelFormat = unsafe extern "system" fn(isize, *const c_void) -> i32;
+type FnSetPixelFormat = unsafe extern "system" fn(isize, i32, *const c_void) -> i32;
+type FnWglSwapIntervalExt = unsafe extern "system" fn(i32) -> i32;
+
+static WGL_CREATE_CONTEXT_HOOK: OnceLock<GenericDetour<FnWglCreateContext>> = OnceLock::new();
+static WGL_MAKE_CURRENT_HOOK: OnceLock<GenericDetour<FnWglMakeCurrent>> = OnceLock::new();
+static WGL_GET_PROC_ADDRESS_HOOK: OnceLock<GenericDetour<FnWglGetProcAddress>> = OnceLock::new();
+static WGL_SWAP_BUFFERS_HOOK: OnceLock<GenericDetour<FnWglSwapBuffers>> = OnceLock::new();
+static SWAP_BUFFERS_HOOK: OnceLock<GenericDetour<FnSwapBuffers>> = OnceLock::new();
+static CHOOSE_PIXEL_FORMAT_HOOK: OnceLock<GenericDetour<FnChoosePixelFormat>> = OnceLock::new();
+static SET_PIXEL_FORMAT_HOOK: OnceLock<GenericDetour<FnSetPixelFormat>> = OnceLock::new();
+static WGL_SWAP_INTERVAL_EXT_HOOK: OnceLock<GenericDetour<FnWglSwapIntervalExt>> = OnceLock::new();
+
+/// Installs whatever part of the group is loaded; safe to call again after every module load.
+pub fn try_install_opengl_hooks() -> Result<(), String> {
+    unsafe {
+        install_late_detour(
+            &CHOOSE_PIXEL_FORMAT_HOOK,
+            try_resolve_proc_in_loaded_module(b"gdi32.dll\0", b"ChoosePixelFormat\0"),
+            choose_pixel_format_detour,
+            "ChoosePixelFormat",
+        )?;
+        install_late_detour(
+            &SET_PIXEL_FORMAT_HOOK,
+            try_resolve_proc_in_loaded_module(b"gdi32.dll\0", b"SetPixelFormat\0"),
+            set_pixel_format_detour,
+            "SetPixelFormat",
+        )?;
+        install_late_detour(
+            &SWAP_BUFFERS_HOOK,
+            try_resolve_proc_in_loaded_module(b"gdi32.dll\0", b"SwapBuffers\0"),
+            swap_buffers_detour,
+            "SwapBuffers",
+        )?;
+        install_late_detour(
+            &WGL_CREATE_CONTEXT_HOOK,
+            try_resolve_proc_in_loaded_module(b"opengl32.dll\0", b"wglCreateContext\0"),
+            wgl_create_context_detour,
+            "wglCreateContext",
+        )?;
+        install_late_detour(
+            &WGL_MAKE_CURRENT_HOOK,
+            try_resolve_proc_in_loaded_module(b"opengl32.dll\0", b"wglMakeCurrent\0"),
+            wgl_make_current_detour,
+            "wglMakeCurrent",
+        )?;
+        install_late_detour(
+            &WGL_GET_PROC_ADDRESS_HOOK,
+            try_resolve_proc_in_loaded_module(b"opengl32.dll\0", b"wglGetProcAddress\0"),
+            wgl_get_proc_address_detour,
+            "wglGetProcAddress",
+        )?;
+        install_late_detour(
+            &WGL_SWAP_BUFFERS_HOOK,
+            try_resolve_proc_in_loaded_module(b"opengl32.dll\0", b"wglSwapBuffers\0"),
+            wgl_swap_buffers_detour,
+            "wglSwapBuffers",
+        )?;
+    }
+    Ok(())
+}
+
+fn describe_pixel_format_descriptor(pfd: *const c_void) -> String {
+    describe_struct_ptr(pfd, PIXELFORMATDESCRIPTOR_SIZE, |bytes| {
+        parse_pixel_format_descriptor(bytes).map(|pfd| pfd.to_string())
+    })
+}
+
+unsafe extern "system" fn choose_pixel_format_detour(hdc: isize, pfd: *const c_void) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        CHOOSE_PIXEL_FORMAT_HOOK
+            .get()
+            .expect("ChoosePixelFormat hook not installed")
+            .call(hdc, pfd)
+    };
+    // Zero means no format matched.
+    send_event(make_event(
+        "ChoosePixelFormat",
+        format!("hdc=0x{hdc:016X} {}", describe_pixel_format_descriptor(pfd)),
+        format!("format={result}"),
+    ));
+    result
+}
+
+unsafe extern "system" fn set_pixel_format_detour(
+    hdc: isize,
+    format: i32,
+    pfd: *const c_void,
+) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        SET_PIXEL_FORMAT_HOOK
+            .get()
+            .expect("SetPixelFormat hook not installed")
+            .call(hdc, format, pfd)
+    };
+    send_event(make_event(
+        "SetPixelFormat",
+        format!(
+            "hdc=0x{hdc:016X} format={format} {}",
+            describe_pixel_format_descriptor(pfd)
+        ),
+        bool_result(result),
+    ));
+    result
+}
+
+unsafe extern "system" fn swap_buffers_detour(hdc: isize) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        SWAP_BUFFERS_HOOK
+            .get()
+            .expect("SwapBuffers hook not installed")
+            .call(hdc)
+    };
+    send_event(make_event(
+        "SwapBuffers",
+        format!("hdc=0x{hdc:016X}"),
+        bool_result(result),
+    ));
+    result
+}
+
+unsafe extern "system" fn wgl_create_context_detour(hdc: isize) -> isize {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        WGL_CREATE_CONTEXT_HOOK
+            .get()
+            .expect("wglCreateContext hook not installed")
+            .call(hdc)
+    };
+    send_event(make_event(
+        "wglCreateContext",
+        format!("hdc=0x{hdc:016X}"),
+        format!("hglrc=0x{result:016X}"),
+    ));
+    result
+}
+
+unsafe extern "system" fn wgl_make_current_detour(hdc: isize, hglrc: isize) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        WGL_MAKE_CURRENT_HOOK
+            .get()
+            .expect("wglMakeCurrent hook not installed")
+            .call(hdc, hglrc)
+    };
+    send_event(make_event(
+        "wglMakeCurrent",
+        format!("hdc=0x{hdc:016X} hglrc=0x{hglrc:016X}"),
+        bool_result(result),
+    ));
+    result
+}
+
+unsafe extern "system" fn wgl_get_proc_address_detour(name: *const u8) -> *const c_void {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        WGL_GET_PROC_ADDRESS_HOOK
+            .get()
+            .expect("wglGetProcAddress hook not installed")
+            .call(name)
+    };
+    let name = read_c_string_lossy(name);
+    send_event(make_event(
+        "wglGetProcAddress",
+        format!("name={name}"),
+        format!("proc={result:p}"),
+    ));
+    if name == "wglSwapIntervalEXT" && !result.is_null() {
+        let target: FnWglSwapIntervalExt = ptr_to_fn(result);
+        if let Err(error) = install_late_detour(
+            &WGL_SWAP_INTERVAL_EXT_HOOK,
+            Some(target),
+            wgl_swap_interval_ext_detour,
+            "wglSwapIntervalEXT",
+        ) {
+            send_event(make_event("OpenGLHookInstall", name, error));
+        }
+    }
+    result
+}
+
+unsafe extern "system" fn wgl_swap_buffers_detour(hdc: isize) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        WGL_SWAP_BUFFERS_HOOK
+            .get()
+            .expect("wglSwapBuffers hook not installed")
+            .call(hdc)
+    };
+    send_event(make_event(
+        "wglSwapBuffers",
+        format!("hdc=0x{hdc:016X}"),
+        bool_result(result),
+    ));
+    result
+}
+
+unsafe extern "system" fn wgl_swap_interval_ext_detour(interval: i32) -> i32 {
+    let _active = ActiveCall::enter();
+    let result = unsafe {
+        WGL_SWAP_INTERVAL_EXT_HOOK
+            .get()
+            .expect("wglSwapIntervalEXT hook not installed")
+            .call(interval)
+    };
+    send_event(make_event(
+        "wglSwapIntervalEXT",
+        format!("interval={interval}"),
+        bool_result(result),
+    ));
+    result
+}
@@ -0,0 +1,134 @@
+//! `GetProcAddress` tracing and hooking of graphics entry points resolved at run time.
+//!
+//! Games often load ddraw.dll or d3d11.dll themselves and look the entry point up, sometimes
+//! through paths the `LoadLibrary*` hooks never see. When a resolved export is one a hook group
+//! knows, the groups are given another chance to install before the caller gets the address,
+//! unless another thread is already installing them.
+//! The agent resolves its own targets through the trampoline so they stay out of the trace.
+
+use crate::agent::detach::ActiveCall;
+use crate::agent::hook_groups::install_loaded_hook_groups_nonblocking;
+use crate::{
+    get_module_file_name_lossy, install_late_detour, make_event, read_c_string_lossy, send_event,
+    try_resolve_proc_in_loaded_module,
+};
+use retour::GenericDetour;
+use std::ffi::c_void;
+use std::sync::OnceLock;
+use windows_sys::Win32::Foundation::{FARPROC, GetLastError, HMODULE, SetLastError};
+use windows_sys::Win32::System::LibraryLoader::GetProcAddress;
+
+type FnGetProcAddress = unsafe extern "system" fn(HMODULE, *const u8) -> FARPROC;
+
+static GET_PROC_ADDRESS_HOOK: OnceLock<GenericDetour<FnGetProcAddress>> = OnceLock::new();
+
+/// Exports the hook groups detour when their module is loaded.
+const AUTO_HOOK_EXPORTS: [&str; 29] = [
+    "DirectDrawCreate",
+    "DirectDrawCreateEx",
+    "DirectDrawCreateClipper",
+    "DirectDrawEnumerateA",
+    "DirectDrawEnumerateW",
+    "DirectDrawEnumerateExA",
+    "DirectDrawEnumerateExW",
+    "CoCreateInstance",
+    "CoCreateInstanceEx",
+    "Direct3DCreate9",
+    "Direct3DCreate9Ex",
+    "CreateDXGIFactory",
+    "CreateDXGIFactory1",
+    "D3D11CreateDevice",
+    "D3D11CreateDeviceAndSwapChain",
+    "wglCreateContext",
+    "wglMakeCurrent",
+    "wglGetProcAddress",
+    "wglSwapBuffers",
+    "ChoosePixelFormat",
+    "SetPixelFormat",
+    "SwapBuffers",
+    "DirectInputCreateA",
+    "DirectInputCreateW",
+    "DirectInputCreateEx",
+    "DirectInput8Create",
+    "timeGetTime",
+    "timeBeginPeriod",
+    "RegisterRawInputDevices",
+];
+
+/// `GetProcAddress` without tracing, for the agent's own lookups.
+pub fn get_proc_address_untraced(module: HMODULE, name: *const u8) -> FARPROC {
+    match GET_PROC_ADDRESS_HOOK.get() {
+        Some(hook) => unsafe { hook.call(module, name) },
+        None => unsafe { GetProcAddress(module, name) },
+    }
+}
+
+pub fn try_install_get_proc_address_hook() -> Result<(), String> {
+    unsafe {
+        install_late_detour(
+            &GET_PROC_ADDRESS_HOOK,
+            try_resolve_proc_in_loaded_module(b"kernel32.dll\0", b"GetProcAddress\0"),
+            get_proc_address_detour,
+            "GetProcAddress",
+        )
+    }
+}
+
+/// Names below 0x10000 are ordinals.
+fn describe_symbol(name: *const u8) -> (Option<String>, String) {
+    if (name as usize) <= 0xFFFF {
+        return (None, format!("ordinal={}", name as usize));
+    }
+    let symbol = read_c_string_lossy(name);
+    let described = format!("symbol=\"{symbol}\"");
+    (Some(symbol), described)
+}
+
+fn describe_module(module: HMODULE) -> String {
+    let path = get_module_file_name_lossy(module);
+    let name = path.rsplit(['\\', '/']).next().unwrap_or_default();
+    if name.is_empty() {
+        format!("module=0x{:X}", module as usize)
+    } else {
+        format!("module=0x{:X} module_name=\"{name}\"", module as usize)
+    }
+}
+
+unsafe extern "system" fn get_proc_address_detour(module: HMODULE, name: *const u8) -> FARPROC {
+    let _active = ActiveCall::enter();
+    let proc = unsafe {
+        GET_PROC_ADDRESS_HOOK
+            .get()
+            .expect("GetProcAddress hook not installed")
+            .call(module, name)
+    };
+    // Callers check GetLastError when the lookup fails; the event must not clobber it.
+    let error = unsafe { GetLastError() };
+
+    let (symbol, described) = describe_symbol(name);
+    let mut result = match proc {
+        Some(proc) => format!("address={:p}", proc as *const c_void),
+        None => format!("address=null error={error}"),
+    };
+    if proc.is_some()
+        && let Some(symbol) = symbol
+        && AUTO_HOOK_EXPORTS.contains(&symbol.as_str())
+    {
+        // The address handed back is patched in place, so the caller ends up in the detour.
+        // Lookups from DllMain hold the loader lock, so a pass already running elsewhere is left
+        // to finish; the `LoadLibrary*` detours run another pass once the load returns.
+        match install_loaded_hook_groups_nonblocking("GetProcAddress") {
+            Ok(true) => result.push_str(" auto_hook=OK"),
+            Ok(false) => result.push_str(" auto_hook=BUSY"),
+            Err(install_error) => result.push_str(&format!(" auto_hook=\"{install_error}\"")),
+        }
+    }
+    send_event(make_event(
+        "GetProcAddress",
+        format!("{} {described}", describe_module(module)),
+        result,
+    ));
+
+    unsafe { SetLastError(error) };
+    proc
+}
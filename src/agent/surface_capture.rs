@@ -0,0 +1,243 @@
+//! Opt-in read-back of the primary surface when a frame is presented.
+//!
+//! The UI turns capture on through the `ConfigureSurfaceCapture` and `RequestSurfaceCapture`
+//! exports. Captures are downscaled in the surface's own pixel format and sent as a
+//! `SurfaceCapture` event; the UI converts them to RGBA.
+
+use super::ddraw::{
+    DD_PALETTE_GET_ENTRIES, DD_PALETTE_RELEASE, DD_SURFACE_GET_CAPS, DD_SURFACE_GET_PALETTE,
+    DD_SURFACE_LOCK, DD_SURFACE_UNLOCK,
+};
+use crate::agent::com_vtable::interface_revision;
+use crate::decode::surface_desc::{
+    DDPF_PALETTEINDEXED8, DDPF_RGB, SurfaceDescLayout, parse_surface_desc,
+};
+use crate::{hex_bytes, hresult_result, is_readable_ptr, make_event, send_event};
+use std::ffi::c_void;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+
+pub const SURFACE_CAPTURE_API: &str = "SurfaceCapture";
+
+// Keeps a 32-bit thumbnail, hex encoded, well inside one UDP datagram.
+const THUMBNAIL_MAX_WIDTH: usize = 80;
+const THUMBNAIL_MAX_HEIGHT: usize = 60;
+
+const DDSCAPS_PRIMARYSURFACE: u32 = 0x0000_0200;
+const DDLOCK_WAIT: u32 = 0x0000_0001;
+const DDLOCK_READONLY: u32 = 0x0000_0010;
+const DDLOCK_NOSYSLOCK: u32 = 0x0000_0800;
+const PALETTE_ENTRY_COUNT: usize = 256;
+
+/// Capture every N presented frames; zero turns periodic capture off.
+static CAPTURE_INTERVAL: AtomicU32 = AtomicU32::new(0);
+static CAPTURE_REQUESTED: AtomicBool = AtomicBool::new(false);
+static PRESENTED_FRAMES: AtomicU64 = AtomicU64::new(0);
+
+pub fn set_capture_interval(frames: u32) {
+    CAPTURE_INTERVAL.store(frames, Ordering::Relaxed);
+}
+
+/// Captures the next presented frame regardless of the interval.
+pub fn request_capture() {
+    CAPTURE_REQUESTED.store(true, Ordering::Relaxed);
+}
+
+/// Called after a successful `Flip` of `surface`, or a `Blt` into it. Blts only count as a
+/// presented frame when they target the primary surface.
+pub fn on_present(surface: *mut c_void, trigger: &str) {
+    let interval = CAPTURE_INTERVAL.load(Ordering::Relaxed);
+    if interval == 0 && !CAPTURE_REQUESTED.load(Ordering::Relaxed) {
+        return;
+    }
+    if trigger != "Flip" && !is_primary_surface(surface) {
+        return;
+    }
+
+    let frame = PRESENTED_FRAMES.fetch_add(1, Ordering::Relaxed) + 1;
+    let requested = CAPTURE_REQUESTED.swap(false, Ordering::Relaxed);
+    if requested || (interval != 0 && frame.is_multiple_of(u64::from(interval))) {
+        capture(surface, trigger, frame);
+    }
+}
+
+fn is_primary_surface(surface: *mut c_void) -> bool {
+    let Some(get_caps) = DD_SURFACE_GET_CAPS.untraced(surface) else {
+        return false;
+    };
+    // Large enough for DDSCAPS2; DDSCAPS is its first DWORD.
+    let mut caps = [0u32; 4];
+    let hr = unsafe { get_caps(surface, caps.as_mut_ptr().cast()) };
+    hr >= 0 && caps[0] & DDSCAPS_PRIMARYSURFACE != 0
+}
+
+struct Thumbnail {
+    source_width: usize,
+    source_height: usize,
+    width: usize,
+    height: usize,
+    bit_count: u32,
+    palettized: bool,
+    /// `format=pal8` or `format=rgb` with the channel masks.
+    format: String,
+    pixels: Vec<u8>,
+}
+
+fn capture(surface: *mut c_void, trigger: &str, frame: u64) {
+    let summary = format!("surface={surface:p} trigger={trigger} frame={frame}");
+    let (Some(lock), Some(unlock)) = (
+        DD_SURFACE_LOCK.untraced(surface),
+        DD_SURFACE_UNLOCK.untraced(surface),
+    ) else {
+        return;
+    };
+
+    let pointer_size = std::mem::size_of::<usize>();
+    let layout = SurfaceDescLayout::for_pointer_size(pointer_size);
+    let desc_size = if interface_revision(surface).is_some_and(|revision| revision >= 4) {
+        layout.desc2_size
+    } else {
+        layout.desc_size
+    };
+    let mut desc = vec![0u8; layout.desc2_size];
+    desc[..4].copy_from_slice(&(desc_size as u32).to_le_bytes());
+
+    let flags = DDLOCK_WAIT | DDLOCK_READONLY | DDLOCK_NOSYSLOCK;
+    let hr = unsafe {
+        lock(
+            surface,
+            std::ptr::null_mut(),
+            desc.as_mut_ptr().cast(),
+            flags,
+            std::ptr::null_mut(),
+        )
+    };
+    if hr < 0 {
+        send_event(make_event(SURFACE_CAPTURE_API, summary, hresult_result(hr)));
+        return;
+    }
+    let mut data = [0u8; std::mem::size_of::<usize>()];
+    data.copy_from_slice(&desc[layout.surface..layout.surface + pointer_size]);
+    let thumbnail = downscale(&desc, usize::from_le_bytes(data) as *const u8);
+    let _ = unsafe { unlock(surface, std::ptr::null_mut()) };
+
+    let Some(thumbnail) = thumbnail else {
+        send_event(make_event(
+            SURFACE_CAPTURE_API,
+            summary,
+            format!("{} UNSUPPORTED_FORMAT", hresult_result(hr)),
+        ));
+        return;
+    };
+    let mut result = format!(
+        "{} pixels={}",
+        hresult_result(hr),
+        hex_bytes(&thumbnail.pixels)
+    );
+    if thumbnail.palettized
+        && let Some(palette) = read_palette(surface)
+    {
+        let _ = write!(result, " palette={}", hex_bytes(&palette));
+    }
+    send_event(make_event(
+        SURFACE_CAPTURE_API,
+        format!(
+            "{summary} width={} height={} thumb_width={} thumb_height={} bpp={} {}",
+            thumbnail.source_width,
+            thumbnail.source_height,
+            thumbnail.width,
+            thumbnail.height,
+            thumbnail.bit_count,
+            thumbnail.format
+        ),
+        result,
+    ));
+}
+
+/// Samples the locked surface down to at most the thumbnail size, keeping its pixel format.
+fn downscale(desc: &[u8], data: *const u8) -> Option<Thumbnail> {
+    let desc = parse_surface_desc(desc, std::mem::size_of::<usize>())?;
+    let format = desc.pixel_format;
+    let bit_count = format.bit_count;
+    let palettized = format.flags & DDPF_PALETTEINDEXED8 != 0 && bit_count == 8;
+    let format_label = if palettized {
+        "format=pal8".to_owned()
+    } else if format.flags & DDPF_RGB != 0 && matches!(bit_count, 16 | 24 | 32) {
+        format!(
+            "format=rgb r=0x{:08X} g=0x{:08X} b=0x{:08X} a=0x{:08X}",
+            format.red_mask, format.green_mask, format.blue_mask, format.alpha_mask
+        )
+    } else {
+        return None;
+    };
+
+    let bytes_per_pixel = bit_count as usize / 8;
+    let (source_width, source_height) = (desc.width as usize, desc.height as usize);
+    let pitch = desc.pitch as i32 as isize;
+    if data.is_null() || source_width == 0 || source_height == 0 {
+        return None;
+    }
+    let (width, height) = thumbnail_size(source_width, source_height);
+
+    let mut pixels = Vec::with_capacity(width * height * bytes_per_pixel);
+    for y in 0..height {
+        let row = unsafe { data.offset((y * source_height / height) as isize * pitch) };
+        if !is_readable_ptr(row.cast(), source_width * bytes_per_pixel) {
+            return None;
+        }
+        for x in 0..width {
+            let offset = (x * source_width / width) * bytes_per_pixel;
+            let pixel = unsafe { std::slice::from_raw_parts(row.add(offset), bytes_per_pixel) };
+            pixels.extend_from_slice(pixel);
+        }
+    }
+
+    Some(Thumbnail {
+        source_width,
+        source_height,
+        width,
+        height,
+        bit_count,
+        palettized,
+        format: format_label,
+        pixels,
+    })
+}
+
+/// Fits the surface inside the thumbnail bounds, keeping its aspect ratio.
+fn thumbnail_size(width: usize, height: usize) -> (usize, usize) {
+    if width * THUMBNAIL_MAX_HEIGHT >= height * THUMBNAIL_MAX_WIDTH {
+        let thumb_width = width.min(THUMBNAIL_MAX_WIDTH);
+        (thumb_width, (height * thumb_width / width).max(1))
+    } else {
+        let thumb_height = height.min(THUMBNAIL_MAX_HEIGHT);
+        ((width * thumb_height / height).max(1), thumb_height)
+    }
+}
+
+/// Reads the 256 `PALETTEENTRY`s of the palette attached to `surface`.
+fn read_palette(surface: *mut c_void) -> Option<Vec<u8>> {
+    let get_palette = DD_SURFACE_GET_PALETTE.untraced(surface)?;
+    let mut palette = std::ptr::null_mut();
+    let hr = unsafe { get_palette(surface, &mut palette) };
+    if hr < 0 || palette.is_null() {
+        return None;
+    }
+
+    let mut entries = vec![0u8; PALETTE_ENTRY_COUNT * 4];
+    let read = DD_PALETTE_GET_ENTRIES
+        .untraced(palette)
+        .is_some_and(|get_entries| unsafe {
+            get_entries(
+                palette,
+                0,
+                0,
+                PALETTE_ENTRY_COUNT as u32,
+                entries.as_mut_ptr().cast(),
+            ) >= 0
+        });
+    if let Some(release) = DD_PALETTE_RELEASE.untraced(palette) {
+        let _ = unsafe { release(palette) };
+    }
+    read.then_some(entries)
+}
@@ -0,0 +1,327 @@
+//! Opt-in timing hook group: aggregates timer and sleep calls instead of tracing each one.
+//!
+//! Games call `QueryPerformanceCounter` and `timeGetTime` thousands of times a second, so the
+//! detours only bump counters. A reporter thread sends them as one `TimingStats` event per
+//! window; the UI lines the windows up with presented frames. The UI turns the group on through
+//! the `ConfigureTimingTrace` export; nothing is detoured until then.
+
+use crate::agent::detach::{ActiveCall, agent_detached};
+use crate::agent::hook_inventory::count_hook_calls;
+use crate::{install_late_detour, make_event, send_event, try_resolve_proc_in_loaded_module};
+use retour::GenericDetour;
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+pub const TIMING_STATS_API: &str = "TimingStats";
+
+/// Upper bounds (inclusive, in ms) of the `Sleep` argument buckets; longer sleeps share the last.
+const SLEEP_BUCKET_LIMITS: [u32; 8] = [0, 1, 2, 5, 10, 16, 33, 100];
+const SLEEP_BUCKET_COUNT: usize = SLEEP_BUCKET_LIMITS.len() + 1;
+/// How often the reporter looks at the interval while the group is off.
+const IDLE_POLL: Duration = Duration::from_millis(250);
+
+type FnTimeGetTime = unsafe extern "system" fn() -> u32;
+type FnQueryPerformanceCounter = unsafe extern "system" fn(*mut i64) -> i32;
+type FnGetTickCount = unsafe extern "system" fn() -> u32;
+type FnSleep = unsafe extern "system" fn(u32);
+type FnTimeBeginPeriod = unsafe extern "system" fn(u32) -> u32;
+
+static TIME_GET_TIME_HOOK: OnceLock<GenericDetour<FnTimeGetTime>> = OnceLock::new();
+static QUERY_PERFORMANCE_COUNTER_HOOK: OnceLock<GenericDetour<FnQueryPerformanceCounter>> =
+    OnceLock::new();
+static GET_TICK_COUNT_HOOK: OnceLock<GenericDetour<FnGetTickCount>> = OnceLock::new();
+static SLEEP_HOOK: OnceLock<GenericDetour<FnSleep>> = OnceLock::new();
+static TIME_BEGIN_PERIOD_HOOK: OnceLock<GenericDetour<FnTimeBeginPeriod>> = OnceLock::new();
+
+/// Report window in milliseconds; zero means the group is off.
+static REPORT_INTERVAL_MS: AtomicU32 = AtomicU32::new(0);
+/// The reporter thread, joined when the agent detaches.
+static REPORTER: Mutex<Option<JoinHandle<()>>> = Mutex::new(None);
+
+static TIME_GET_TIME_CALLS: AtomicU64 = AtomicU64::new(0);
+static QUERY_PERFORMANCE_COUNTER_CALLS: AtomicU64 = AtomicU64::new(0);
+static GET_TICK_COUNT_CALLS: AtomicU64 = AtomicU64::new(0);
+static SLEEP_CALLS: [AtomicU64; SLEEP_BUCKET_COUNT] =
+    [const { AtomicU64::new(0) }; SLEEP_BUCKET_COUNT];
+static SLEEP_TOTAL_MS: AtomicU64 = AtomicU64::new(0);
+/// `timeBeginPeriod` argument to the number of calls with it.
+static TIMER_PERIODS: Mutex<BTreeMap<u32, u64>> = Mutex::new(BTreeMap::new());
+
+thread_local! {
+    /// Set while the agent itself reads the clock or sleeps, so it does not count its own calls.
+    static UNTIMED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn timing_enabled() -> bool {
+    REPORT_INTERVAL_MS.load(Ordering::Relaxed) != 0 && !UNTIMED.with(Cell::get)
+}
+
+/// Runs `f` without its timer calls showing up in the aggregates.
+pub fn untimed<R>(f: impl FnOnce() -> R) -> R {
+    let previous = UNTIMED.with(|untimed| untimed.replace(true));
+    let result = f();
+    UNTIMED.with(|untimed| untimed.set(previous));
+    result
+}
+
+pub fn timing_trace_enabled() -> bool {
+    REPORT_INTERVAL_MS.load(Ordering::Relaxed) != 0
+}
+
+/// Turns aggregation on with a report every `interval_ms`, or off when it is zero.
+pub fn set_timing_interval(interval_ms: u32) {
+    if interval_ms != 0 {
+        // Start the first window clean; calls counted before a previous stop are stale.
+        take_counters();
+        let mut reporter = REPORTER.lock().unwrap_or_else(PoisonError::into_inner);
+        if reporter.is_none() && !agent_detached() {
+            *reporter = Some(std::thread::spawn(report_loop));
+        }
+    }
+    REPORT_INTERVAL_MS.store(interval_ms, Ordering::Relaxed);
+}
+
+/// Wakes the reporter so it sends the window in progress, then waits for its thread to exit.
+pub fn stop_timing_reporter() {
+    let reporter = REPORTER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+    if let Some(reporter) = reporter {
+        reporter.thread().unpark();
+        let _ = reporter.join();
+    }
+}
+
+/// Installs whatever part of the group is loaded once the UI has turned it on; safe to call
+/// again after every module load.
+pub fn try_install_timing_hooks() -> Result<(), String> {
+    if !timing_trace_enabled() {
+        return Ok(());
+    }
+    unsafe {
+        install_late_detour(
+            &QUERY_PERFORMANCE_COUNTER_HOOK,
+            try_resolve_proc_in_loaded_module(b"kernel32.dll\0", b"QueryPerformanceCounter\0"),
+            query_performance_counter_detour,
+            "QueryPerformanceCounter",
+        )?;
+        install_late_detour(
+            &GET_TICK_COUNT_HOOK,
+            try_resolve_proc_in_loaded_module(b"kernel32.dll\0", b"GetTickCount\0"),
+            get_tick_count_detour,
+            "GetTickCount",
+        )?;
+        install_late_detour(
+            &SLEEP_HOOK,
+            try_resolve_proc_in_loaded_module(b"kernel32.dll\0", b"Sleep\0"),
+            sleep_detour,
+            "Sleep",
+        )?;
+        install_late_detour(
+            &TIME_GET_TIME_HOOK,
+            try_resolve_proc_in_loaded_module(b"winmm.dll\0", b"timeGetTime\0"),
+            time_get_time_detour,
+            "timeGetTime",
+        )?;
+        install_late_detour(
+            &TIME_BEGIN_PERIOD_HOOK,
+            try_resolve_proc_in_loaded_module(b"winmm.dll\0", b"timeBeginPeriod\0"),
+            time_begin_period_detour,
+            "timeBeginPeriod",
+        )?;
+    }
+    Ok(())
+}
+
+struct TimingCounters {
+    time_get_time: u64,
+    query_performance_counter: u64,
+    get_tick_count: u64,
+    sleep_buckets: [u64; SLEEP_BUCKET_COUNT],
+    sleep_total_ms: u64,
+    timer_periods: BTreeMap<u32, u64>,
+}
+
+fn take_counters() -> TimingCounters {
+    TimingCounters {
+        time_get_time: TIME_GET_TIME_CALLS.swap(0, Ordering::Relaxed),
+        query_performance_counter: QUERY_PERFORMANCE_COUNTER_CALLS.swap(0, Ordering::Relaxed),
+        get_tick_count: GET_TICK_COUNT_CALLS.swap(0, Ordering::Relaxed),
+        sleep_buckets: std::array::from_fn(|index| SLEEP_CALLS[index].swap(0, Ordering::Relaxed)),
+        sleep_total_ms: SLEEP_TOTAL_MS.swap(0, Ordering::Relaxed),
+        timer_periods: std::mem::take(
+            &mut *TIMER_PERIODS.lock().unwrap_or_else(PoisonError::into_inner),
+        ),
+    }
+}
+
+fn report_loop() {
+    UNTIMED.with(|untimed| untimed.set(true));
+    while !agent_detached() {
+        let interval_ms = REPORT_INTERVAL_MS.load(Ordering::Relaxed);
+        if interval_ms == 0 {
+            std::thread::park_timeout(IDLE_POLL);
+            continue;
+        }
+        // Parked rather than asleep so detaching can end the window early.
+        let window_start = Instant::now();
+        std::thread::park_timeout(Duration::from_millis(u64::from(interval_ms)));
+        // Turned off mid-window: drop the partial window rather than report it.
+        if REPORT_INTERVAL_MS.load(Ordering::Relaxed) == 0 {
+            continue;
+        }
+        let window_ms = u32::try_from(window_start.elapsed().as_millis()).unwrap_or(u32::MAX);
+        let counters = take_counters();
+        count_timing_calls(&counters);
+        send_event(make_event(
+            TIMING_STATS_API,
+            describe_counters(window_ms, &counters),
+            "OK".to_owned(),
+        ));
+    }
+}
+
+/// The detours only bump counters, so their calls reach the hook inventory from here.
+fn count_timing_calls(counters: &TimingCounters) {
+    count_hook_calls("timeGetTime", counters.time_get_time);
+    count_hook_calls(
+        "QueryPerformanceCounter",
+        counters.query_performance_counter,
+    );
+    count_hook_calls("GetTickCount", counters.get_tick_count);
+    count_hook_calls("Sleep", counters.sleep_buckets.iter().sum());
+    count_hook_calls("timeBeginPeriod", counters.timer_periods.values().sum());
+}
+
+/// `window_ms=1000 timeGetTime=N QueryPerformanceCounter=N GetTickCount=N Sleep=N
+/// sleep_total_ms=N sleep_ms=0:3,1:57,101+:1 timeBeginPeriod=1:1`, with `-` for empty lists.
+fn describe_counters(window_ms: u32, counters: &TimingCounters) -> String {
+    let sleep_calls: u64 = counters.sleep_buckets.iter().sum();
+    let mut sleeps = String::new();
+    for (index, count) in counters.sleep_buckets.iter().enumerate() {
+        if *count == 0 {
+            continue;
+        }
+        if !sleeps.is_empty() {
+            sleeps.push(',');
+        }
+        let _ = write!(sleeps, "{}:{count}", sleep_bucket_label(index));
+    }
+    let periods: Vec<String> = counters
+        .timer_periods
+        .iter()
+        .map(|(period, count)| format!("{period}:{count}"))
+        .collect();
+    format!(
+        "window_ms={window_ms} timeGetTime={} QueryPerformanceCounter={} GetTickCount={} Sleep={sleep_calls} sleep_total_ms={} sleep_ms={} timeBeginPeriod={}",
+        counters.time_get_time,
+        counters.query_performance_counter,
+        counters.get_tick_count,
+        counters.sleep_total_ms,
+        if sleeps.is_empty() { "-" } else { &sleeps },
+        if periods.is_empty() {
+            "-".to_owned()
+        } else {
+            periods.join(",")
+        },
+    )
+}
+
+/// `0`, `1`, `3-5`, ..., `101+`.
+fn sleep_bucket_label(index: usize) -> String {
+    let low = match index {
+        0 => 0,
+        _ => SLEEP_BUCKET_LIMITS[index - 1] + 1,
+    };
+    match SLEEP_BUCKET_LIMITS.get(index) {
+        Some(&high) if high == low => high.to_string(),
+        Some(&high) => format!("{low}-{high}"),
+        None => format!("{low}+"),
+    }
+}
+
+fn sleep_bucket(milliseconds: u32) -> usize {
+    SLEEP_BUCKET_LIMITS
+        .iter()
+        .position(|limit| milliseconds <= *limit)
+        .unwrap_or(SLEEP_BUCKET_LIMITS.len())
+}
+
+unsafe extern "system" fn time_get_time_detour() -> u32 {
+    let _active = ActiveCall::enter();
+    if timing_enabled() {
+        TIME_GET_TIME_CALLS.fetch_add(1, Ordering::Relaxed);
+    }
+    unsafe {
+        TIME_GET_TIME_HOOK
+            .get()
+            .expect("timeGetTime hook not installed")
+            .call()
+    }
+}
+
+unsafe extern "system" fn query_performance_counter_detour(counter: *mut i64) -> i32 {
+    let _active = ActiveCall::enter();
+    if timing_enabled() {
+        QUERY_PERFORMANCE_COUNTER_CALLS.fetch_add(1, Ordering::Relaxed);
+    }
+    unsafe {
+        QUERY_PERFORMANCE_COUNTER_HOOK
+            .get()
+            .expect("QueryPerformanceCounter hook not installed")
+            .call(counter)
+    }
+}
+
+unsafe extern "system" fn get_tick_count_detour() -> u32 {
+    let _active = ActiveCall::enter();
+    if timing_enabled() {
+        GET_TICK_COUNT_CALLS.fetch_add(1, Ordering::Relaxed);
+    }
+    unsafe {
+        GET_TICK_COUNT_HOOK
+            .get()
+            .expect("GetTickCount hook not installed")
+            .call()
+    }
+}
+
+unsafe extern "system" fn sleep_detour(milliseconds: u32) {
+    let _active = ActiveCall::enter();
+    // INFINITE would swamp the total; it still lands in the last bucket.
+    if timing_enabled() {
+        SLEEP_CALLS[sleep_bucket(milliseconds)].fetch_add(1, Ordering::Relaxed);
+        if milliseconds != u32::MAX {
+            SLEEP_TOTAL_MS.fetch_add(u64::from(milliseconds), Ordering::Relaxed);
+        }
+    }
+    unsafe {
+        SLEEP_HOOK
+            .get()
+            .expect("Sleep hook not installed")
+            .call(milliseconds)
+    }
+}
+
+unsafe extern "system" fn time_begin_period_detour(period: u32) -> u32 {
+    let _active = ActiveCall::enter();
+    if timing_enabled() {
+        *TIMER_PERIODS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(period)
+            .or_default() += 1;
+    }
+    unsafe {
+        TIME_BEGIN_PERIOD_HOOK
+            .get()
+            .expect("timeBeginPeriod hook not installed")
+            .call(period)
+    }
+}
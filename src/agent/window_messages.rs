@@ -0,0 +1,133 @@
+//! Window message tracing by subclassing top-level windows created through `CreateWindowExW`.
+//!
+//! The system sends activation, sizing and display messages straight to the window procedure,
+//! bypassing `DispatchMessageW`, so the procedure itself is wrapped. Messages sent while the
+//! window is being created arrive before the subclass and are not seen.
+
+use crate::agent::detach::{ActiveCall, agent_detached};
+use crate::decode::messages::{WM_NCDESTROY, describe_message_params};
+use crate::{
+    SET_WINDOW_LONG_PTR_W_HOOK, SET_WINDOW_LONG_W_HOOK, make_event, ptr_to_fn, send_event,
+};
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::sync::{Mutex, OnceLock, PoisonError};
+#[cfg(target_pointer_width = "64")]
+use windows_sys::Win32::UI::WindowsAndMessaging::GetWindowLongPtrW;
+#[cfg(target_pointer_width = "32")]
+use windows_sys::Win32::UI::WindowsAndMessaging::GetWindowLongW;
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    CallWindowProcW, DefWindowProcW, IsWindow, WNDPROC,
+};
+
+const GWLP_WNDPROC: i32 = -4;
+
+/// Window handle to the procedure it had before we subclassed it.
+static ORIGINAL_WINDOW_PROCS: OnceLock<Mutex<HashMap<isize, isize>>> = OnceLock::new();
+
+fn original_window_procs() -> &'static Mutex<HashMap<isize, isize>> {
+    ORIGINAL_WINDOW_PROCS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Replaces the window procedure through the `SetWindowLong*` trampolines, so the subclass does
+/// not show up as the application's own call.
+fn swap_window_proc(hwnd: isize, proc: isize) -> isize {
+    if let Some(hook) = SET_WINDOW_LONG_PTR_W_HOOK.get() {
+        unsafe { hook.call(hwnd, GWLP_WNDPROC, proc) }
+    } else if let Some(hook) = SET_WINDOW_LONG_W_HOOK.get() {
+        unsafe { hook.call(hwnd, GWLP_WNDPROC, proc as i32) as isize }
+    } else {
+        0
+    }
+}
+
+fn current_window_proc(hwnd: isize) -> isize {
+    #[cfg(target_pointer_width = "64")]
+    let proc = unsafe { GetWindowLongPtrW(hwnd as _, GWLP_WNDPROC) };
+    #[cfg(target_pointer_width = "32")]
+    let proc = unsafe { GetWindowLongW(hwnd as _, GWLP_WNDPROC) } as isize;
+    proc
+}
+
+pub fn subclass_window(hwnd: isize) {
+    if hwnd == 0 || agent_detached() {
+        return;
+    }
+    let mut procs = original_window_procs()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if procs.contains_key(&hwnd) {
+        return;
+    }
+    // Only the owning thread runs the procedure, and it is busy here, so no message can arrive
+    // between the swap and the insert.
+    let traced = traced_window_proc as *const () as isize;
+    let original = swap_window_proc(hwnd, traced);
+    if original != 0 {
+        procs.insert(hwnd, original);
+    }
+}
+
+/// Puts the original procedures back and returns how many windows still point at ours because
+/// something subclassed them after us. Those keep forwarding without reporting.
+pub fn restore_window_procs() -> usize {
+    let mut procs = original_window_procs()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let traced = traced_window_proc as *const () as isize;
+    procs.retain(|&hwnd, &mut original| {
+        if unsafe { IsWindow(hwnd as _) } == 0 {
+            return false;
+        }
+        if current_window_proc(hwnd) != traced {
+            return true;
+        }
+        swap_window_proc(hwnd, original);
+        false
+    });
+    procs.len()
+}
+
+unsafe extern "system" fn traced_window_proc(
+    hwnd: isize,
+    msg: u32,
+    wparam: usize,
+    lparam: isize,
+) -> isize {
+    // Modal loops keep this frame on the stack for as long as a window is dragged or a dialog
+    // is open.
+    let _active = ActiveCall::enter();
+    let original = original_window_procs()
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(&hwnd)
+        .copied();
+    let result = match original {
+        Some(original) => {
+            let original: WNDPROC = ptr_to_fn(original as *const c_void);
+            unsafe { CallWindowProcW(original, hwnd as _, msg, wparam, lparam) }
+        }
+        None => unsafe { DefWindowProcW(hwnd as _, msg, wparam, lparam) },
+    };
+    if msg == WM_NCDESTROY {
+        original_window_procs()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&hwnd);
+    }
+
+    if agent_detached() {
+        return result;
+    }
+    let params = describe_message_params(msg, wparam, lparam)
+        .map(|params| format!(" {params}"))
+        .unwrap_or_default();
+    send_event(make_event(
+        "WindowMessage",
+        format!(
+            "hwnd=0x{hwnd:016X} msg=0x{msg:04X} wparam=0x{wparam:016X} lparam=0x{lparam:016X}{params}"
+        ),
+        format!("lresult=0x{result:016X}"),
+    ));
+    result
+}
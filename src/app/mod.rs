@@ -1,10 +1,21 @@
-use crate::hook::injector::inject_agent_dll;
+use crate::hook::injector::{call_agent_export, inject_agent_dll};
 use crate::hook::udp_listener::start_udp_event_listener;
 use crate::hook::{HookManager, trigger_smoke_test_call};
+use crate::model::config_sources::ConfigSources;
+use crate::model::ddraw_objects::DdObjectGraph;
+use crate::model::display_modes::DisplayModeHistory;
 use crate::model::dll::LoadedDlls;
 use crate::model::event::Event;
 use crate::model::filters::{EventFilters, EventSortColumn};
+use crate::model::hook_inventory::HookInventory;
+use crate::model::ipc::SHUTDOWN_AGENT_UNLOADED;
+use crate::model::palettes::PaletteTracker;
 use crate::model::process::{ProcessEntry, enumerate_processes};
+use crate::model::refcounts::RefcountLedger;
+use crate::model::surface_captures::SurfaceCaptures;
+use crate::model::surface_loss::SurfaceLossAnalysis;
+use crate::model::timing::TimingHistory;
+use crate::model::windows::TrackedWindows;
 use crate::util::process_launch::launch_target_exe_suspended;
 use eframe::egui;
 use std::sync::mpsc::{self, Receiver, Sender};
@@ -12,16 +23,36 @@ use std::time::Duration;
 use windows_sys::Win32::System::Threading::GetCurrentProcessId;
 
 pub mod widgets {
+    pub mod config_sources;
+    pub mod ddraw_graph;
     pub mod details_panel;
+    pub mod display_modes;
     pub mod dll_table;
     pub mod event_table;
+    pub mod hooks;
     pub mod left_panel;
+    pub mod lost_surfaces;
+    pub mod palettes;
+    pub mod refcounts;
+    pub mod surface_captures;
+    pub mod timing;
+    pub mod window_table;
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum MainTab {
     Events,
     Dlls,
+    Windows,
+    DisplayModes,
+    DirectDrawObjects,
+    Refcounts,
+    SurfaceCaptures,
+    Palettes,
+    LostSurfaces,
+    Timing,
+    ConfigSources,
+    Hooks,
 }
 
 pub struct WinApiTraceApp {
@@ -34,6 +65,24 @@ pub struct WinApiTraceApp {
     main_tab: MainTab,
     dlls: LoadedDlls,
     dll_query: String,
+    windows: TrackedWindows,
+    window_query: String,
+    display_modes: DisplayModeHistory,
+    ddraw_objects: DdObjectGraph,
+    refcounts: RefcountLedger,
+    surface_captures: SurfaceCaptures,
+    capture_view: widgets::surface_captures::CaptureViewState,
+    palettes: PaletteTracker,
+    palette_view: widgets::palettes::PaletteViewState,
+    surface_loss: SurfaceLossAnalysis,
+    timing: TimingHistory,
+    timing_view: widgets::timing::TimingViewState,
+    config_sources: ConfigSources,
+    config_view: widgets::config_sources::ConfigViewState,
+    hook_inventory: HookInventory,
+    hooks_view: widgets::hooks::HooksViewState,
+    /// PID the agent was last injected into, for calling its control exports.
+    attached_pid: Option<u32>,
     hook_manager: HookManager,
     event_tx: Sender<Event>,
     event_rx: Receiver<Event>,
@@ -55,6 +104,23 @@ impl Default for WinApiTraceApp {
             main_tab: MainTab::Events,
             dlls: LoadedDlls::default(),
             dll_query: String::new(),
+            windows: TrackedWindows::default(),
+            window_query: String::new(),
+            display_modes: DisplayModeHistory::default(),
+            ddraw_objects: DdObjectGraph::default(),
+            refcounts: RefcountLedger::default(),
+            surface_captures: SurfaceCaptures::default(),
+            capture_view: Default::default(),
+            palettes: PaletteTracker::default(),
+            palette_view: Default::default(),
+            surface_loss: SurfaceLossAnalysis::default(),
+            timing: TimingHistory::default(),
+            timing_view: Default::default(),
+            config_sources: ConfigSources::default(),
+            config_view: Default::default(),
+            hook_inventory: HookInventory::default(),
+            hooks_view: Default::default(),
+            attached_pid: None,
             hook_manager: HookManager::default(),
             event_tx,
             event_rx,
@@ -136,6 +202,7 @@ impl WinApiTraceApp {
         match inject_agent_dll(pid) {
             Ok(dll_path) => {
                 self.attach_status = format!("Injected agent into PID {pid} using {dll_path}");
+                self.attached_pid = Some(pid);
                 self.refresh_process_list();
             }
             Err(error) => {
@@ -175,6 +242,7 @@ impl WinApiTraceApp {
                     if Self::should_retry_attach_after_resume(&error) {
                         match self.retry_attach_after_resume(pid, 16) {
                             Ok((attempt, dll_path)) => {
+                                self.attached_pid = Some(pid);
                                 self.attach_status = format!(
                                     "Initial suspended attach failed, but retry succeeded after resume (attempt {attempt}) using {dll_path}"
                                 );
@@ -190,6 +258,7 @@ impl WinApiTraceApp {
                             format!("Process PID {pid} resumed, but attach failed: {error}");
                     }
                 } else {
+                    self.attached_pid = Some(pid);
                     self.attach_status = format!(
                         "Attached to PID {pid} before resume. Early graphics init calls should now be visible."
                     );
@@ -203,9 +272,93 @@ impl WinApiTraceApp {
         }
     }
 
+    fn handle_detach_request(&mut self) {
+        let Some(pid) = self.attached_pid else {
+            return;
+        };
+        match call_agent_export(pid, "ShutdownAgent", 0) {
+            Ok(SHUTDOWN_AGENT_UNLOADED) => {
+                self.attached_pid = None;
+                self.attach_status = format!("Detached agent from PID {pid}.");
+            }
+            Ok(_) => {
+                // The agent is inert but still loaded; keep the PID so Detach can retry.
+                self.attach_status = format!(
+                    "Hooks removed from PID {pid}, agent left loaded; Detach again to retry."
+                );
+            }
+            Err(error) => {
+                self.attach_status = format!("Detach failed for PID {pid}: {error}");
+            }
+        }
+    }
+
+    fn handle_capture_request(&mut self, request: widgets::surface_captures::CaptureRequest) {
+        let Some(pid) = self.attached_pid else {
+            return;
+        };
+        let (export_name, param) = match request {
+            widgets::surface_captures::CaptureRequest::SetInterval(interval) => {
+                ("ConfigureSurfaceCapture", interval as usize)
+            }
+            widgets::surface_captures::CaptureRequest::CaptureNextFrame => {
+                ("RequestSurfaceCapture", 0)
+            }
+        };
+        if let Err(error) = call_agent_export(pid, export_name, param) {
+            self.attach_status = format!("{export_name} failed for PID {pid}: {error}");
+        }
+    }
+
+    fn handle_timing_request(&mut self, request: widgets::timing::TimingRequest) {
+        let Some(pid) = self.attached_pid else {
+            return;
+        };
+        let widgets::timing::TimingRequest::SetInterval(interval_ms) = request;
+        if let Err(error) = call_agent_export(pid, "ConfigureTimingTrace", interval_ms as usize) {
+            self.attach_status = format!("ConfigureTimingTrace failed for PID {pid}: {error}");
+        }
+    }
+
+    fn handle_config_trace_request(
+        &mut self,
+        request: widgets::config_sources::ConfigTraceRequest,
+    ) {
+        let Some(pid) = self.attached_pid else {
+            return;
+        };
+        let widgets::config_sources::ConfigTraceRequest::SetEnabled(enabled) = request;
+        if let Err(error) =
+            call_agent_export(pid, "ConfigureFileRegistryTrace", usize::from(enabled))
+        {
+            self.attach_status =
+                format!("ConfigureFileRegistryTrace failed for PID {pid}: {error}");
+        }
+    }
+
+    fn handle_hook_inventory_request(&mut self, request: widgets::hooks::HookInventoryRequest) {
+        let Some(pid) = self.attached_pid else {
+            return;
+        };
+        let widgets::hooks::HookInventoryRequest::Refresh = request;
+        if let Err(error) = call_agent_export(pid, "ReportHookInventory", 0) {
+            self.attach_status = format!("ReportHookInventory failed for PID {pid}: {error}");
+        }
+    }
+
     fn drain_live_events(&mut self) {
-        while let Ok(event) = self.event_rx.try_recv() {
+        while let Ok(mut event) = self.event_rx.try_recv() {
+            self.surface_captures.observe_event(&mut event);
+            self.palettes.observe_event(&mut event);
             self.dlls.observe_event(&event);
+            self.windows.observe_event(&event);
+            self.display_modes.observe_event(&event);
+            self.ddraw_objects.observe_event(&event);
+            self.refcounts.observe_event(&event);
+            self.surface_loss.observe_event(&event);
+            self.timing.observe_event(&event);
+            self.config_sources.observe_event(&event);
+            self.hook_inventory.observe_event(&event);
             self.events.push(event);
         }
     }
@@ -257,6 +410,7 @@ impl eframe::App for WinApiTraceApp {
             &mut self.pid_input,
             &mut self.launch_exe_path,
             &self.attach_status,
+            self.attached_pid.is_some(),
             &self.processes,
             &mut self.selected_process,
         ) {
@@ -270,6 +424,7 @@ impl eframe::App for WinApiTraceApp {
                 widgets::left_panel::LeftPanelAction::LaunchAndAttach => {
                     self.handle_launch_and_attach_request()
                 }
+                widgets::left_panel::LeftPanelAction::Detach => self.handle_detach_request(),
             }
         }
 
@@ -283,6 +438,24 @@ impl eframe::App for WinApiTraceApp {
             ui.horizontal(|ui| {
                 ui.selectable_value(&mut self.main_tab, MainTab::Events, "Events");
                 ui.selectable_value(&mut self.main_tab, MainTab::Dlls, "DLLs");
+                ui.selectable_value(&mut self.main_tab, MainTab::Windows, "Windows");
+                ui.selectable_value(&mut self.main_tab, MainTab::DisplayModes, "Display Modes");
+                ui.selectable_value(
+                    &mut self.main_tab,
+                    MainTab::DirectDrawObjects,
+                    "DirectDraw Objects",
+                );
+                ui.selectable_value(&mut self.main_tab, MainTab::Refcounts, "Refcounts");
+                ui.selectable_value(
+                    &mut self.main_tab,
+                    MainTab::SurfaceCaptures,
+                    "Surface Captures",
+                );
+                ui.selectable_value(&mut self.main_tab, MainTab::Palettes, "Palettes");
+                ui.selectable_value(&mut self.main_tab, MainTab::LostSurfaces, "Lost Surfaces");
+                ui.selectable_value(&mut self.main_tab, MainTab::Timing, "Timing");
+                ui.selectable_value(&mut self.main_tab, MainTab::ConfigSources, "Config Sources");
+                ui.selectable_value(&mut self.main_tab, MainTab::Hooks, "Hooks");
             });
             ui.separator();
 
@@ -307,6 +480,67 @@ impl eframe::App for WinApiTraceApp {
                 MainTab::Dlls => {
                     widgets::dll_table::show(ui, &self.dlls, &mut self.dll_query);
                 }
+                MainTab::Windows => {
+                    widgets::window_table::show(ui, &self.windows, &mut self.window_query);
+                }
+                MainTab::DisplayModes => {
+                    widgets::display_modes::show(ui, &self.display_modes);
+                }
+                MainTab::DirectDrawObjects => {
+                    if let Some(pointer) = widgets::ddraw_graph::show(ui, &self.ddraw_objects) {
+                        self.filters.this_pointer = Some(pointer);
+                        self.main_tab = MainTab::Events;
+                    }
+                }
+                MainTab::Refcounts => {
+                    widgets::refcounts::show(ui, &self.refcounts, &self.ddraw_objects);
+                }
+                MainTab::SurfaceCaptures => {
+                    if let Some(request) = widgets::surface_captures::show(
+                        ui,
+                        &self.surface_captures,
+                        &mut self.capture_view,
+                        self.attached_pid.is_some(),
+                    ) {
+                        self.handle_capture_request(request);
+                    }
+                }
+                MainTab::Palettes => {
+                    widgets::palettes::show(ui, &self.palettes, &mut self.palette_view);
+                }
+                MainTab::LostSurfaces => {
+                    widgets::lost_surfaces::show(ui, &self.surface_loss);
+                }
+                MainTab::Timing => {
+                    if let Some(request) = widgets::timing::show(
+                        ui,
+                        &self.timing,
+                        &mut self.timing_view,
+                        self.attached_pid.is_some(),
+                    ) {
+                        self.handle_timing_request(request);
+                    }
+                }
+                MainTab::ConfigSources => {
+                    if let Some(request) = widgets::config_sources::show(
+                        ui,
+                        &self.config_sources,
+                        &mut self.config_view,
+                        self.attached_pid.is_some(),
+                    ) {
+                        self.handle_config_trace_request(request);
+                    }
+                }
+                MainTab::Hooks => {
+                    if let Some(request) = widgets::hooks::show(
+                        ui,
+                        &self.hook_inventory,
+                        &mut self.hooks_view,
+                        self.attached_pid.is_some(),
+                    ) {
+                        self.handle_hook_inventory_request(request);
+                    }
+                }
             }
         });
     }
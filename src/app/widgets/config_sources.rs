@@ -0,0 +1,111 @@
+use crate::model::config_sources::{ConfigSource, ConfigSourceKind, ConfigSources};
+use crate::util::ascii::contains_ignore_ascii_case;
+use crate::util::time_format::format_timestamp_ms;
+use eframe::egui;
+
+pub enum ConfigTraceRequest {
+    SetEnabled(bool),
+}
+
+#[derive(Default)]
+pub struct ConfigViewState {
+    pub enabled: bool,
+    pub query: String,
+    /// Hide plain files that are not INI files, which are mostly game data.
+    pub hide_files: bool,
+}
+
+pub fn show(
+    ui: &mut egui::Ui,
+    sources: &ConfigSources,
+    state: &mut ConfigViewState,
+    attached: bool,
+) -> Option<ConfigTraceRequest> {
+    ui.heading("Config Sources");
+
+    let mut request = None;
+    ui.add_enabled_ui(attached, |ui| {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut state.enabled, "Trace file and registry access");
+            if ui.button("Apply").clicked() {
+                request = Some(ConfigTraceRequest::SetEnabled(state.enabled));
+            }
+        });
+    });
+    if !attached {
+        ui.label("Attach to a process to trace the files and registry keys it reads.");
+    }
+    ui.horizontal(|ui| {
+        ui.label("Filter:");
+        ui.add(egui::TextEdit::singleline(&mut state.query).hint_text("Filter by path or entry"));
+        ui.checkbox(&mut state.hide_files, "Hide non-INI files");
+    });
+    ui.label(format!("Unique sources: {}", sources.len()));
+    ui.separator();
+
+    let q = state.query.trim();
+    let mut rows: Vec<&ConfigSource> = sources
+        .values()
+        .filter(|source| !(state.hide_files && source.kind == ConfigSourceKind::File))
+        .filter(|source| {
+            q.is_empty()
+                || contains_ignore_ascii_case(&source.path, q)
+                || source.entries.iter().any(|(name, value)| {
+                    contains_ignore_ascii_case(name, q) || contains_ignore_ascii_case(value, q)
+                })
+        })
+        .collect();
+    rows.sort_by(|a, b| a.first_seen_ms.cmp(&b.first_seen_ms));
+
+    if rows.is_empty() {
+        ui.label("No config sources match current filter.");
+        return request;
+    }
+
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("config_sources_grid")
+                .num_columns(6)
+                .striped(true)
+                .min_col_width(80.0)
+                .show(ui, |ui| {
+                    ui.strong("First Seen");
+                    ui.strong("Last Seen");
+                    ui.strong("Kind");
+                    ui.strong("Path / Entry");
+                    ui.strong("Reads");
+                    ui.strong("Failed");
+                    ui.end_row();
+
+                    for source in rows {
+                        ui.monospace(format_timestamp_ms(source.first_seen_ms));
+                        ui.monospace(format_timestamp_ms(source.last_seen_ms));
+                        ui.monospace(source.kind.label());
+                        ui.monospace(&source.path);
+                        ui.monospace(source.reads.to_string());
+                        if source.failures == 0 {
+                            ui.monospace("0");
+                        } else {
+                            ui.colored_label(egui::Color32::LIGHT_RED, source.failures.to_string());
+                        }
+                        ui.end_row();
+
+                        for (name, value) in &source.entries {
+                            ui.label("");
+                            ui.label("");
+                            ui.label("");
+                            if value.is_empty() {
+                                ui.monospace(format!("    {name}"));
+                            } else {
+                                ui.monospace(format!("    {name} = {value}"));
+                            }
+                            ui.label("");
+                            ui.label("");
+                            ui.end_row();
+                        }
+                    }
+                });
+        });
+    request
+}
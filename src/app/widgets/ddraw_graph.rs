@@ -0,0 +1,122 @@
+use crate::model::ddraw_objects::{DdObject, DdObjectGraph, DdObjectKind, SurfaceRole};
+use crate::util::time_format::format_timestamp_ms;
+use eframe::egui;
+
+/// Returns the object whose calls the user asked to see in the Events tab.
+pub fn show(ui: &mut egui::Ui, graph: &DdObjectGraph) -> Option<u64> {
+    ui.heading("DirectDraw Objects");
+    ui.label(format!("Objects seen: {}", graph.len()));
+    ui.separator();
+
+    if graph.len() == 0 {
+        ui.label("No DirectDraw objects seen.");
+        return None;
+    }
+
+    let mut selected = None;
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for root in graph.roots() {
+                show_object(ui, graph, root, &mut selected);
+            }
+        });
+    selected
+}
+
+fn show_object(
+    ui: &mut egui::Ui,
+    graph: &DdObjectGraph,
+    object: &DdObject,
+    selected: &mut Option<u64>,
+) {
+    let mut children = graph.children(object.pointer).peekable();
+    if children.peek().is_none() {
+        object_row(ui, object, selected);
+        return;
+    }
+
+    egui::CollapsingHeader::new(object_label(object))
+        .id_salt(("ddraw_object", object.pointer))
+        .default_open(true)
+        .show(ui, |ui| {
+            object_row(ui, object, selected);
+            for child in children {
+                show_object(ui, graph, child, selected);
+            }
+        });
+}
+
+fn object_row(ui: &mut egui::Ui, object: &DdObject, selected: &mut Option<u64>) {
+    ui.horizontal(|ui| {
+        ui.monospace(object_label(object));
+        if ui.small_button("Show calls").clicked() {
+            *selected = Some(object.pointer);
+        }
+    });
+
+    let mut details = Vec::new();
+    if let Some(created) = &object.created {
+        details.push(format!(
+            "created {} by {}",
+            format_timestamp_ms(created.timestamp_ms),
+            created.caller
+        ));
+    }
+    if let Some(released_ms) = object.released_ms {
+        details.push(format!("released {}", format_timestamp_ms(released_ms)));
+    }
+    if let Some(clipper) = object.clipper {
+        details.push(format!("clipper=0x{clipper:X}"));
+    }
+    if let Some(palette) = object.palette {
+        details.push(format!("palette=0x{palette:X}"));
+    }
+    if !object.attachments.is_empty() {
+        let attachments: Vec<String> = object
+            .attachments
+            .iter()
+            .map(|pointer| format!("0x{pointer:X}"))
+            .collect();
+        details.push(format!("attached=[{}]", attachments.join(", ")));
+    }
+    if !details.is_empty() {
+        ui.monospace(format!("    {}", details.join("  ")));
+    }
+}
+
+fn object_label(object: &DdObject) -> String {
+    let relation = if object.relation.is_empty() {
+        String::new()
+    } else {
+        format!(" via {}", object.relation)
+    };
+    let released = if object.released_ms.is_some() {
+        " [released]"
+    } else {
+        ""
+    };
+    format!(
+        "{} 0x{:X} ({}) calls={}{relation}{released}",
+        object.interface,
+        object.pointer,
+        kind_label(object.kind),
+        object.call_count
+    )
+}
+
+fn kind_label(kind: DdObjectKind) -> &'static str {
+    match kind {
+        DdObjectKind::DirectDraw => "directdraw",
+        DdObjectKind::Surface(SurfaceRole::Primary) => "primary surface",
+        DdObjectKind::Surface(SurfaceRole::BackBuffer) => "back buffer",
+        DdObjectKind::Surface(SurfaceRole::Offscreen) => "offscreen surface",
+        DdObjectKind::Surface(SurfaceRole::Texture) => "texture",
+        DdObjectKind::Surface(SurfaceRole::ZBuffer) => "z-buffer",
+        DdObjectKind::Surface(SurfaceRole::Overlay) => "overlay",
+        DdObjectKind::Surface(SurfaceRole::Other) => "surface",
+        DdObjectKind::Clipper => "clipper",
+        DdObjectKind::Palette => "palette",
+        DdObjectKind::Other => "interface",
+    }
+}
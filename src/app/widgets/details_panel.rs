@@ -1,3 +1,4 @@
+use crate::decode::annotate::annotate_fields;
 use crate::model::event::Event;
 use crate::util::time_format::format_timestamp_ms;
 use eframe::egui;
@@ -16,10 +17,16 @@ pub fn show(ctx: &egui::Context, selected_event: Option<&Event>) {
                         ui.monospace(format!("Time: {}", format_timestamp_ms(event.timestamp_ms)));
                         ui.monospace(format!("Timestamp (ms): {}", event.timestamp_ms));
                         ui.monospace(format!("API: {}", event.api));
-                        ui.monospace(format!("Summary: {}", event.summary));
+                        ui.monospace(format!(
+                            "Summary: {}",
+                            annotate_fields(&event.api, &event.summary)
+                        ));
                         ui.monospace(format!("Caller: {}", event.caller));
                         ui.monospace(format!("Thread ID: {}", event.thread_id));
-                        ui.monospace(format!("Result: {}", event.result));
+                        ui.monospace(format!(
+                            "Result: {}",
+                            annotate_fields(&event.api, &event.result)
+                        ));
                     });
                 }
                 None => {
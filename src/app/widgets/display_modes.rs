@@ -0,0 +1,93 @@
+use crate::model::display_modes::{DisplayModeHistory, ModeChangeKind};
+use crate::util::time_format::format_timestamp_ms;
+use eframe::egui;
+
+pub fn show(ui: &mut egui::Ui, history: &DisplayModeHistory) {
+    ui.heading("Display Modes");
+
+    let changes = history.changes();
+    let failures = changes.iter().filter(|change| !change.succeeded).count();
+    ui.label(format!(
+        "Mode requests: {}  Failed: {failures}",
+        changes.len()
+    ));
+
+    if let Some(change) = history.unrestored_change() {
+        let message = match history.exit() {
+            Some(exit) if exit.detached() => format!(
+                "Agent detached at {} with {} set by {} at {} still applied.",
+                format_timestamp_ms(exit.timestamp_ms),
+                change.mode,
+                change.api,
+                format_timestamp_ms(change.timestamp_ms)
+            ),
+            Some(exit) => format!(
+                "Process exited at {} without restoring {} set by {} at {}.",
+                format_timestamp_ms(exit.timestamp_ms),
+                change.mode,
+                change.api,
+                format_timestamp_ms(change.timestamp_ms)
+            ),
+            None => format!(
+                "{} set by {} at {} has not been restored yet.",
+                change.mode,
+                change.api,
+                format_timestamp_ms(change.timestamp_ms)
+            ),
+        };
+        let color = if history.exit().is_some_and(|exit| !exit.detached()) {
+            egui::Color32::LIGHT_RED
+        } else {
+            egui::Color32::YELLOW
+        };
+        ui.colored_label(color, message);
+    }
+    ui.separator();
+
+    if changes.is_empty() {
+        ui.label("No display mode changes seen.");
+        return;
+    }
+
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("display_modes_grid")
+                .num_columns(7)
+                .striped(true)
+                .min_col_width(90.0)
+                .show(ui, |ui| {
+                    ui.strong("Time");
+                    ui.strong("API");
+                    ui.strong("Kind");
+                    ui.strong("Mode");
+                    ui.strong("Result");
+                    ui.strong("Thread");
+                    ui.strong("Caller");
+                    ui.end_row();
+
+                    for change in changes {
+                        ui.monospace(format_timestamp_ms(change.timestamp_ms));
+                        ui.monospace(&change.api);
+                        ui.monospace(match change.kind {
+                            ModeChangeKind::Set => "set",
+                            ModeChangeKind::Test => "test",
+                            ModeChangeKind::Restore => "restore",
+                        });
+                        if change.kind == ModeChangeKind::Restore {
+                            ui.monospace("-");
+                        } else {
+                            ui.monospace(change.mode.to_string());
+                        }
+                        if change.succeeded {
+                            ui.monospace(&change.result);
+                        } else {
+                            ui.colored_label(egui::Color32::LIGHT_RED, &change.result);
+                        }
+                        ui.monospace(change.thread_id.to_string());
+                        ui.monospace(&change.caller);
+                        ui.end_row();
+                    }
+                });
+        });
+}
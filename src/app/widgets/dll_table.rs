@@ -8,7 +8,7 @@ pub fn show(ui: &mut egui::Ui, dlls: &LoadedDlls, query: &mut String) {
 
     ui.horizontal(|ui| {
         ui.label("Filter:");
-        ui.add(egui::TextEdit::singleline(query).hint_text("Filter by name/path"));
+        ui.add(egui::TextEdit::singleline(query).hint_text("Filter by name/path/hook group"));
     });
 
     ui.label(format!("Unique DLLs: {}", dlls.len()));
@@ -25,6 +25,10 @@ pub fn show(ui: &mut egui::Ui, dlls: &LoadedDlls, query: &mut String) {
             contains_ignore_ascii_case(&dll.name, q)
                 || contains_ignore_ascii_case(&dll.path, q)
                 || contains_ignore_ascii_case(&dll.last_summary, q)
+                || dll
+                    .hook_groups
+                    .iter()
+                    .any(|group| contains_ignore_ascii_case(group, q))
         })
         .collect();
 
@@ -39,7 +43,7 @@ pub fn show(ui: &mut egui::Ui, dlls: &LoadedDlls, query: &mut String) {
         .auto_shrink([false, false])
         .show(ui, |ui| {
             egui::Grid::new("dlls_grid")
-                .num_columns(5)
+                .num_columns(6)
                 .striped(true)
                 .min_col_width(130.0)
                 .show(ui, |ui| {
@@ -48,6 +52,7 @@ pub fn show(ui: &mut egui::Ui, dlls: &LoadedDlls, query: &mut String) {
                     ui.strong("Name");
                     ui.strong("Path");
                     ui.strong("Count");
+                    ui.strong("Hook Groups");
                     ui.end_row();
 
                     for dll in rows {
@@ -56,6 +61,7 @@ pub fn show(ui: &mut egui::Ui, dlls: &LoadedDlls, query: &mut String) {
                         ui.monospace(&dll.name);
                         ui.monospace(&dll.path);
                         ui.monospace(dll.count.to_string());
+                        ui.monospace(dll.hook_groups.join(", "));
                         ui.end_row();
 
                         // Optional extra context line (kept compact).
@@ -64,6 +70,7 @@ pub fn show(ui: &mut egui::Ui, dlls: &LoadedDlls, query: &mut String) {
                         ui.monospace("last:");
                         ui.monospace(&dll.last_summary);
                         ui.monospace("");
+                        ui.monospace("");
                         ui.end_row();
                     }
                 });
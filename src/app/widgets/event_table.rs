@@ -1,3 +1,5 @@
+use crate::decode::annotate::annotate_fields;
+use crate::decode::messages::MessageClass;
 use crate::model::event::Event;
 use crate::model::filters::{ApiScope, EventFilters, EventSortColumn};
 use crate::util::time_format::format_timestamp_ms;
@@ -38,7 +40,39 @@ pub fn show(
             ApiScope::DirectDrawCallsOnly,
             "DirectDraw calls only",
         );
+        ui.radio_value(
+            &mut filters.api_scope,
+            ApiScope::Direct3D9CallsOnly,
+            "Direct3D 9 calls only",
+        );
+        ui.radio_value(
+            &mut filters.api_scope,
+            ApiScope::DxgiD3D11CallsOnly,
+            "DXGI / D3D11 calls only",
+        );
+        ui.radio_value(
+            &mut filters.api_scope,
+            ApiScope::OpenGlCallsOnly,
+            "OpenGL calls only",
+        );
+        ui.radio_value(&mut filters.api_scope, ApiScope::InputOnly, "Input");
     });
+    ui.checkbox(&mut filters.failures_only, "Failures only (HRESULT)");
+    ui.horizontal(|ui| {
+        ui.label("Window messages:");
+        ui.radio_value(&mut filters.message_class, None, "All");
+        for class in MessageClass::ALL {
+            ui.radio_value(&mut filters.message_class, Some(class), class.label());
+        }
+    });
+    if let Some(pointer) = filters.this_pointer {
+        ui.horizontal(|ui| {
+            ui.label(format!("Object filter: this=0x{pointer:X}"));
+            if ui.button("Clear").clicked() {
+                filters.this_pointer = None;
+            }
+        });
+    }
 
     ui.separator();
 
@@ -51,7 +85,7 @@ pub fn show(
         .auto_shrink([false, false])
         .show(ui, |ui| {
             egui::Grid::new("events_grid")
-                .num_columns(4)
+                .num_columns(5)
                 .striped(true)
                 .min_col_width(130.0)
                 .show(ui, |ui| {
@@ -78,6 +112,7 @@ pub fn show(
                     }
 
                     ui.strong("Summary");
+                    ui.strong("Result");
                     if sort_header_button(
                         ui,
                         "Caller",
@@ -99,7 +134,18 @@ pub fn show(
                             .selectable_label(is_selected, format_timestamp_ms(event.timestamp_ms))
                             .clicked();
                         clicked |= ui.selectable_label(is_selected, &event.api).clicked();
-                        clicked |= ui.selectable_label(is_selected, &event.summary).clicked();
+                        clicked |= ui
+                            .selectable_label(
+                                is_selected,
+                                annotate_fields(&event.api, &event.summary),
+                            )
+                            .clicked();
+                        clicked |= ui
+                            .selectable_label(
+                                is_selected,
+                                annotate_fields(&event.api, &event.result),
+                            )
+                            .clicked();
                         clicked |= ui.selectable_label(is_selected, &event.caller).clicked();
                         ui.end_row();
 
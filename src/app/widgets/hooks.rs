@@ -0,0 +1,139 @@
+use crate::model::hook_inventory::{HookEntry, HookInventory, HookState};
+use crate::util::ascii::contains_ignore_ascii_case;
+use crate::util::time_format::format_timestamp_ms;
+use eframe::egui;
+
+pub enum HookInventoryRequest {
+    Refresh,
+}
+
+#[derive(Default)]
+pub struct HooksViewState {
+    pub query: String,
+    /// Only show hooks that are overwritten or whose target was modified.
+    pub problems_only: bool,
+}
+
+pub fn show(
+    ui: &mut egui::Ui,
+    inventory: &HookInventory,
+    state: &mut HooksViewState,
+    attached: bool,
+) -> Option<HookInventoryRequest> {
+    ui.heading("Hooks");
+
+    let mut request = None;
+    ui.add_enabled_ui(attached, |ui| {
+        if ui.button("Refresh inventory").clicked() {
+            request = Some(HookInventoryRequest::Refresh);
+        }
+    });
+    if !attached {
+        ui.label("Attach to a process to ask the agent which hooks it placed.");
+    }
+    let Some(reported_ms) = inventory.reported_ms() else {
+        ui.label("No hook inventory received yet.");
+        return request;
+    };
+
+    let hooks = inventory.hooks();
+    ui.label(format!(
+        "Reported at {}: {} hook(s), {} disabled",
+        format_timestamp_ms(reported_ms),
+        hooks.len(),
+        inventory.count(HookState::Disabled)
+    ));
+    if hooks.len() < inventory.expected() {
+        ui.colored_label(
+            egui::Color32::YELLOW,
+            format!(
+                "Only {} of {} entries arrived; refresh to try again.",
+                hooks.len(),
+                inventory.expected()
+            ),
+        );
+    }
+    let overwritten = inventory.count(HookState::Overwritten);
+    if overwritten != 0 {
+        ui.colored_label(
+            egui::Color32::LIGHT_RED,
+            format!("{overwritten} hook(s) were patched over by another tool after the agent placed them."),
+        );
+    }
+    ui.horizontal(|ui| {
+        ui.label("Filter:");
+        ui.add(egui::TextEdit::singleline(&mut state.query).hint_text("Filter by hook or module"));
+        ui.checkbox(&mut state.problems_only, "Only overwritten or modified");
+    });
+    ui.separator();
+
+    let q = state.query.trim();
+    let rows: Vec<&HookEntry> = hooks
+        .iter()
+        .filter(|hook| {
+            !state.problems_only
+                || matches!(hook.state, HookState::Overwritten | HookState::Modified)
+        })
+        .filter(|hook| {
+            q.is_empty()
+                || contains_ignore_ascii_case(&hook.name, q)
+                || contains_ignore_ascii_case(&hook.module, q)
+        })
+        .collect();
+
+    if rows.is_empty() {
+        ui.label("No hooks match current filter.");
+        return request;
+    }
+
+    egui::ScrollArea::both()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("hook_inventory_grid")
+                .num_columns(8)
+                .striped(true)
+                .min_col_width(70.0)
+                .show(ui, |ui| {
+                    ui.strong("Hook");
+                    ui.strong("Method");
+                    ui.strong("Target");
+                    ui.strong("Module");
+                    ui.strong("State");
+                    ui.strong("Calls");
+                    ui.strong("Original Bytes");
+                    ui.strong("Current Bytes");
+                    ui.end_row();
+
+                    for hook in rows {
+                        ui.monospace(&hook.name);
+                        ui.monospace(&hook.method);
+                        ui.monospace(format!("0x{:X}", hook.target));
+                        ui.monospace(hook.module_name()).on_hover_text(&hook.module);
+                        match hook.state {
+                            HookState::Active => ui.monospace(hook.state.label()),
+                            HookState::Overwritten => {
+                                ui.colored_label(egui::Color32::LIGHT_RED, hook.state.label())
+                            }
+                            HookState::Disabled => {
+                                ui.colored_label(egui::Color32::GRAY, hook.state.label())
+                            }
+                            HookState::Modified => {
+                                ui.colored_label(egui::Color32::YELLOW, hook.state.label())
+                            }
+                        };
+                        ui.monospace(hook.calls.to_string());
+                        ui.monospace(&hook.original);
+                        if matches!(hook.state, HookState::Overwritten | HookState::Modified) {
+                            ui.colored_label(
+                                egui::Color32::LIGHT_RED,
+                                egui::RichText::new(&hook.current).monospace(),
+                            );
+                        } else {
+                            ui.monospace(&hook.current);
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+    request
+}
@@ -6,6 +6,7 @@ pub enum LeftPanelAction {
     Attach(u32),
     RefreshProcesses,
     LaunchAndAttach,
+    Detach,
 }
 
 pub fn show(
@@ -13,6 +14,7 @@ pub fn show(
     pid_input: &mut String,
     launch_exe_path: &mut String,
     attach_status: &str,
+    attached: bool,
     processes: &[ProcessEntry],
     selected_process: &mut Option<usize>,
 ) -> Option<LeftPanelAction> {
@@ -34,6 +36,13 @@ pub fn show(
             if attach_clicked {
                 action = pid_validation.ok().map(LeftPanelAction::Attach);
             }
+            if ui
+                .add_enabled(attached, egui::Button::new("Detach"))
+                .on_hover_text("Remove every hook and unload the agent from the target.")
+                .clicked()
+            {
+                action = Some(LeftPanelAction::Detach);
+            }
 
             ui.separator();
             ui.heading("Launch Target");
@@ -0,0 +1,171 @@
+use crate::decode::annotate::annotate_fields;
+use crate::model::surface_loss::{
+    ContextEvent, ContextKind, LossEpisode, SurfaceLossAnalysis, describe_context,
+};
+use crate::util::time_format::format_timestamp_ms;
+use eframe::egui;
+
+enum TimelineRow<'a> {
+    Context(&'a ContextEvent),
+    Loss(&'a LossEpisode),
+}
+
+impl TimelineRow<'_> {
+    fn timestamp_ms(&self) -> u64 {
+        match self {
+            Self::Context(context) => context.timestamp_ms,
+            Self::Loss(episode) => episode.lost_ms,
+        }
+    }
+}
+
+pub fn show(ui: &mut egui::Ui, analysis: &SurfaceLossAnalysis) {
+    ui.heading("Lost Surfaces");
+
+    let episodes = analysis.episodes();
+    let restored_delays: Vec<u64> = episodes
+        .iter()
+        .filter_map(LossEpisode::restore_delay_ms)
+        .collect();
+    let unrestored: Vec<&LossEpisode> = analysis.unrestored().collect();
+    let released = episodes
+        .iter()
+        .filter(|episode| episode.released_ms.is_some())
+        .count();
+    ui.label(format!(
+        "Surface losses: {}  restored: {}  released while lost: {}",
+        episodes.len(),
+        restored_delays.len(),
+        released
+    ));
+    if let Some(max) = restored_delays.iter().max() {
+        let average = restored_delays.iter().sum::<u64>() / restored_delays.len() as u64;
+        ui.label(format!(
+            "Time to restore: average {average} ms, worst {max} ms"
+        ));
+    }
+    if !unrestored.is_empty() {
+        let (when, color) = match analysis.exit() {
+            Some(exit) if exit.detached() => (
+                format!(
+                    "when the agent detached ({})",
+                    format_timestamp_ms(exit.timestamp_ms)
+                ),
+                egui::Color32::YELLOW,
+            ),
+            Some(exit) => (
+                format!("at exit ({})", format_timestamp_ms(exit.timestamp_ms)),
+                egui::Color32::LIGHT_RED,
+            ),
+            None => ("so far".to_owned(), egui::Color32::LIGHT_RED),
+        };
+        ui.colored_label(
+            color,
+            format!("{} surface(s) not restored {when}", unrestored.len()),
+        );
+    }
+    ui.separator();
+
+    if episodes.is_empty() && analysis.context().is_empty() {
+        ui.label("No lost surfaces or cooperative-level/activation calls seen.");
+        return;
+    }
+
+    let mut rows: Vec<TimelineRow> = analysis
+        .context()
+        .iter()
+        .map(TimelineRow::Context)
+        .chain(episodes.iter().map(TimelineRow::Loss))
+        .collect();
+    // Stable, so a loss stays after the context event with the same timestamp.
+    rows.sort_by_key(TimelineRow::timestamp_ms);
+
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("lost_surfaces_grid")
+                .num_columns(3)
+                .striped(true)
+                .min_col_width(90.0)
+                .show(ui, |ui| {
+                    ui.strong("Time");
+                    ui.strong("Event");
+                    ui.strong("Details");
+                    ui.end_row();
+
+                    for row in &rows {
+                        ui.monospace(format_timestamp_ms(row.timestamp_ms()));
+                        match row {
+                            TimelineRow::Context(context) => {
+                                ui.monospace(match context.kind {
+                                    ContextKind::CooperativeLevel => "cooperative level",
+                                    ContextKind::Activation => "activation",
+                                    ContextKind::DisplayMode => "display mode",
+                                });
+                                ui.monospace(format!(
+                                    "{} -> {}",
+                                    annotate_fields(&context.api, &describe_context(context)),
+                                    annotate_fields(&context.api, &context.result)
+                                ));
+                            }
+                            TimelineRow::Loss(episode) => {
+                                let color = if episode.is_open() {
+                                    egui::Color32::LIGHT_RED
+                                } else {
+                                    egui::Color32::LIGHT_YELLOW
+                                };
+                                ui.colored_label(color, format!("lost 0x{:X}", episode.surface));
+                                ui.vertical(|ui| {
+                                    show_episode_details(ui, analysis, episode);
+                                });
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+fn show_episode_details(ui: &mut egui::Ui, analysis: &SurfaceLossAnalysis, episode: &LossEpisode) {
+    let outcome = match (episode.restored_ms, episode.released_ms) {
+        (Some(restored), _) => format!(
+            "restored at {} (+{} ms) by {}",
+            format_timestamp_ms(restored),
+            restored.saturating_sub(episode.lost_ms),
+            episode.restored_by.as_deref().unwrap_or("?")
+        ),
+        (None, Some(released)) => {
+            format!("released while lost at {}", format_timestamp_ms(released))
+        }
+        (None, None) => "never restored".to_owned(),
+    };
+    ui.monospace(format!(
+        "detected by {}; {outcome}; {} restore attempt(s)",
+        episode.detected_by, episode.restore_attempts
+    ));
+    if let Some(error) = &episode.last_restore_error {
+        ui.monospace(format!("last failed Restore: {error}"));
+    }
+    if let Some(context) = episode
+        .preceding_context
+        .and_then(|index| analysis.context().get(index))
+    {
+        ui.monospace(format!(
+            "after {} (+{} ms)",
+            annotate_fields(&context.api, &describe_context(context)),
+            episode.lost_ms.saturating_sub(context.timestamp_ms)
+        ));
+    }
+    if !episode.calls_while_lost.is_empty() {
+        let calls = episode
+            .calls_while_lost
+            .iter()
+            .map(|(api, count)| format!("{api} x{count}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        ui.monospace(format!(
+            "{} call(s) while lost: {calls}",
+            episode.total_calls_while_lost()
+        ));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::model::palettes::{PaletteTracker, Rgb, TrackedPalette};
+use crate::util::time_format::format_timestamp_ms;
+use eframe::egui;
+
+const SWATCH_SIZE: f32 = 18.0;
+const SWATCHES_PER_ROW: usize = 16;
+
+#[derive(Default)]
+pub struct PaletteViewState {
+    selected: Option<u64>,
+    /// Index into the selected palette's changes; `None` follows the latest one.
+    change: Option<usize>,
+}
+
+pub fn show(ui: &mut egui::Ui, palettes: &PaletteTracker, state: &mut PaletteViewState) {
+    ui.heading("Palettes");
+    ui.label(format!("Palettes tracked: {}", palettes.len()));
+    ui.separator();
+
+    if palettes.len() == 0 {
+        ui.label("No DirectDraw palettes seen.");
+        return;
+    }
+
+    ui.horizontal_wrapped(|ui| {
+        for palette in palettes.values() {
+            let mut label = format!(
+                "0x{:X} ({} changes)",
+                palette.pointer,
+                palette.changes.len()
+            );
+            if palette.released_ms.is_some() {
+                label.push_str(" released");
+            }
+            if ui
+                .selectable_label(state.selected == Some(palette.pointer), label)
+                .clicked()
+            {
+                state.selected = Some(palette.pointer);
+                state.change = None;
+            }
+        }
+    });
+
+    let Some(palette) = state
+        .selected
+        .and_then(|pointer| palettes.get(pointer))
+        .or_else(|| palettes.values().last())
+    else {
+        return;
+    };
+    ui.separator();
+    show_palette_header(ui, palette);
+
+    let latest = palette.changes.len().checked_sub(1);
+    let change_index = state.change.filter(|index| *index < palette.changes.len());
+    let colors: &[Rgb] = match change_index.or(latest) {
+        Some(index) => {
+            let change = &palette.changes[index];
+            ui.horizontal(|ui| {
+                let mut shown = index;
+                let slider = egui::Slider::new(&mut shown, 0..=latest.unwrap_or(0)).text("change");
+                if ui.add(slider).changed() {
+                    state.change = Some(shown);
+                }
+                if ui
+                    .add_enabled(state.change.is_some(), egui::Button::new("Latest"))
+                    .clicked()
+                {
+                    state.change = None;
+                }
+            });
+            ui.monospace(format!(
+                "{} at {}: entries {}..{}",
+                change.api,
+                format_timestamp_ms(change.timestamp_ms),
+                change.start,
+                change.start + change.count
+            ));
+            &change.colors
+        }
+        None => &palette.colors,
+    };
+    let highlighted = change_index
+        .or(latest)
+        .map(|index| &palette.changes[index])
+        .map_or(0..0, |change| change.start..change.start + change.count);
+    show_swatches(ui, colors, highlighted);
+
+    ui.separator();
+    ui.strong("Changes");
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("palette_changes_grid")
+                .num_columns(6)
+                .striped(true)
+                .min_col_width(70.0)
+                .show(ui, |ui| {
+                    ui.strong("#");
+                    ui.strong("Time");
+                    ui.strong("API");
+                    ui.strong("Entries");
+                    ui.strong("Thread");
+                    ui.strong("Caller");
+                    ui.end_row();
+
+                    for (index, change) in palette.changes.iter().enumerate() {
+                        let selected = change_index.or(latest) == Some(index);
+                        if ui.selectable_label(selected, index.to_string()).clicked() {
+                            state.change = Some(index);
+                        }
+                        ui.monospace(format_timestamp_ms(change.timestamp_ms));
+                        ui.monospace(&change.api);
+                        ui.monospace(format!("{}..{}", change.start, change.start + change.count));
+                        ui.monospace(change.thread_id.to_string());
+                        ui.monospace(&change.caller);
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+fn show_palette_header(ui: &mut egui::Ui, palette: &TrackedPalette) {
+    let created = palette
+        .created_ms
+        .map_or_else(|| "before attach".to_owned(), format_timestamp_ms);
+    let flags = palette
+        .flags
+        .map_or_else(|| "?".to_owned(), |flags| format!("0x{flags:08X}"));
+    ui.monospace(format!(
+        "palette 0x{:X}  created {created}  flags={flags}  entries={}",
+        palette.pointer,
+        palette.colors.len()
+    ));
+    let surfaces = palette
+        .surfaces
+        .iter()
+        .map(|surface| format!("0x{surface:X}"))
+        .collect::<Vec<_>>();
+    ui.monospace(if surfaces.is_empty() {
+        "attached to: none".to_owned()
+    } else {
+        format!("attached to: {}", surfaces.join(", "))
+    });
+    if let Some(released_ms) = palette.released_ms {
+        ui.colored_label(
+            egui::Color32::GRAY,
+            format!("released at {}", format_timestamp_ms(released_ms)),
+        );
+    }
+}
+
+/// Draws the palette as a 16-wide grid; entries touched by the shown change are outlined.
+fn show_swatches(ui: &mut egui::Ui, colors: &[Rgb], highlighted: std::ops::Range<usize>) {
+    let rows = colors.len().div_ceil(SWATCHES_PER_ROW);
+    let size = egui::vec2(
+        SWATCHES_PER_ROW as f32 * SWATCH_SIZE,
+        rows as f32 * SWATCH_SIZE,
+    );
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    for (index, [r, g, b]) in colors.iter().copied().enumerate() {
+        let cell = egui::Rect::from_min_size(
+            rect.min
+                + egui::vec2(
+                    (index % SWATCHES_PER_ROW) as f32 * SWATCH_SIZE,
+                    (index / SWATCHES_PER_ROW) as f32 * SWATCH_SIZE,
+                ),
+            egui::vec2(SWATCH_SIZE, SWATCH_SIZE),
+        );
+        painter.rect_filled(cell.shrink(1.0), 0.0, egui::Color32::from_rgb(r, g, b));
+        if highlighted.contains(&index) {
+            painter.rect_stroke(
+                cell.shrink(0.5),
+                0.0,
+                egui::Stroke::new(1.0, egui::Color32::YELLOW),
+            );
+        }
+    }
+
+    if let Some(pointer) = response.hover_pos() {
+        let offset = pointer - rect.min;
+        let index = (offset.y / SWATCH_SIZE) as usize * SWATCHES_PER_ROW
+            + (offset.x / SWATCH_SIZE) as usize;
+        if let Some([r, g, b]) = colors.get(index) {
+            response.on_hover_text(format!(
+                "entry {index}: #{r:02X}{g:02X}{b:02X} ({r}, {g}, {b})"
+            ));
+        }
+    }
+}
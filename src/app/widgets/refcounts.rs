@@ -0,0 +1,124 @@
+use crate::model::ddraw_objects::DdObjectGraph;
+use crate::model::refcounts::RefcountLedger;
+use crate::util::time_format::format_timestamp_ms;
+use eframe::egui;
+
+pub fn show(ui: &mut egui::Ui, ledger: &RefcountLedger, graph: &DdObjectGraph) {
+    ui.heading("Refcounts");
+    ui.label(format!("Interface pointers tracked: {}", ledger.len()));
+    ui.separator();
+
+    let leaks = ledger.leak_report(graph);
+    match ledger.exit() {
+        Some(exit) if exit.detached() => {
+            let color = if leaks.is_empty() {
+                egui::Color32::LIGHT_GREEN
+            } else {
+                egui::Color32::YELLOW
+            };
+            ui.colored_label(
+                color,
+                format!(
+                    "{} object(s) still alive when the agent detached ({})",
+                    leaks.len(),
+                    format_timestamp_ms(exit.timestamp_ms)
+                ),
+            );
+        }
+        Some(exit) => {
+            let color = if leaks.is_empty() {
+                egui::Color32::LIGHT_GREEN
+            } else {
+                egui::Color32::LIGHT_RED
+            };
+            ui.colored_label(
+                color,
+                format!(
+                    "Leak report at {} ({}): {} object(s) never released",
+                    exit.api,
+                    format_timestamp_ms(exit.timestamp_ms),
+                    leaks.len()
+                ),
+            );
+        }
+        None => {
+            ui.label(format!("Outstanding objects: {}", leaks.len()));
+        }
+    }
+
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            if !leaks.is_empty() {
+                egui::Grid::new("leak_report_grid")
+                    .num_columns(6)
+                    .striped(true)
+                    .min_col_width(90.0)
+                    .show(ui, |ui| {
+                        ui.strong("Object");
+                        ui.strong("Interface");
+                        ui.strong("Refcount");
+                        ui.strong("Created");
+                        ui.strong("By");
+                        ui.strong("Caller");
+                        ui.end_row();
+
+                        for leak in &leaks {
+                            let Some(created) = &leak.object.created else {
+                                continue;
+                            };
+                            ui.monospace(format!("0x{:X}", leak.object.pointer));
+                            ui.monospace(&leak.object.interface);
+                            ui.monospace(
+                                leak.refcount
+                                    .map_or_else(|| "?".to_owned(), |count| count.to_string()),
+                            );
+                            ui.monospace(format_timestamp_ms(created.timestamp_ms));
+                            ui.monospace(format!("{} (tid {})", created.api, created.thread_id));
+                            ui.monospace(&created.caller);
+                            ui.end_row();
+                        }
+                    });
+            }
+
+            ui.separator();
+            ui.strong("Ledger");
+            if ledger.len() == 0 {
+                ui.label("No AddRef/Release calls seen.");
+                return;
+            }
+            egui::Grid::new("refcount_ledger_grid")
+                .num_columns(6)
+                .striped(true)
+                .min_col_width(90.0)
+                .show(ui, |ui| {
+                    ui.strong("Pointer");
+                    ui.strong("Interface");
+                    ui.strong("AddRef");
+                    ui.strong("Release");
+                    ui.strong("Refcount");
+                    ui.strong("Last change");
+                    ui.end_row();
+
+                    for entry in ledger.values() {
+                        ui.monospace(format!("0x{:X}", entry.pointer));
+                        ui.monospace(&entry.interface);
+                        ui.monospace(entry.add_refs.to_string());
+                        ui.monospace(entry.releases.to_string());
+                        match entry.refcount {
+                            Some(0) => {
+                                ui.colored_label(egui::Color32::GRAY, "0");
+                            }
+                            Some(count) => {
+                                ui.monospace(count.to_string());
+                            }
+                            None => {
+                                ui.monospace("?");
+                            }
+                        }
+                        ui.monospace(format_timestamp_ms(entry.last_change_ms));
+                        ui.end_row();
+                    }
+                });
+        });
+}
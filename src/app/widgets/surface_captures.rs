@@ -0,0 +1,146 @@
+use crate::model::surface_captures::{SurfaceCapture, SurfaceCaptures};
+use crate::util::time_format::format_timestamp_ms;
+use eframe::egui;
+use std::collections::HashMap;
+
+const STRIP_SCALE: f32 = 2.0;
+const PREVIEW_SCALE: f32 = 6.0;
+
+pub enum CaptureRequest {
+    /// Capture every N presented frames; zero turns periodic capture off.
+    SetInterval(u32),
+    CaptureNextFrame,
+}
+
+pub struct CaptureViewState {
+    pub periodic: bool,
+    pub interval: u32,
+    selected: Option<u64>,
+    textures: HashMap<u64, egui::TextureHandle>,
+}
+
+impl Default for CaptureViewState {
+    fn default() -> Self {
+        Self {
+            periodic: false,
+            interval: 60,
+            selected: None,
+            textures: HashMap::new(),
+        }
+    }
+}
+
+pub fn show(
+    ui: &mut egui::Ui,
+    captures: &SurfaceCaptures,
+    state: &mut CaptureViewState,
+    attached: bool,
+) -> Option<CaptureRequest> {
+    ui.heading("Surface Captures");
+
+    let mut request = None;
+    ui.add_enabled_ui(attached, |ui| {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut state.periodic, "Capture every");
+            ui.add(egui::DragValue::new(&mut state.interval).range(1..=10_000));
+            ui.label("frames");
+            if ui.button("Apply").clicked() {
+                let interval = if state.periodic { state.interval } else { 0 };
+                request = Some(CaptureRequest::SetInterval(interval));
+            }
+            if ui.button("Capture next frame").clicked() {
+                request = Some(CaptureRequest::CaptureNextFrame);
+            }
+        });
+    });
+    if !attached {
+        ui.label("Attach to a process to capture its primary surface.");
+    }
+    ui.label(format!("Captures: {}", captures.captures().len()));
+    if let Some(failure) = captures.last_failure() {
+        ui.colored_label(
+            egui::Color32::LIGHT_RED,
+            format!("Last failed capture: {failure}"),
+        );
+    }
+    ui.separator();
+
+    // Drop textures of captures that have been evicted.
+    let oldest = captures.captures().front().map_or(u64::MAX, |c| c.id);
+    state.textures.retain(|id, _| *id >= oldest);
+
+    if captures.captures().is_empty() {
+        ui.label("No surface captures yet.");
+        return request;
+    }
+
+    egui::ScrollArea::horizontal()
+        .id_salt("surface_capture_strip")
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                for capture in captures.captures() {
+                    let texture = texture_for(ui.ctx(), &mut state.textures, capture);
+                    ui.vertical(|ui| {
+                        let size = egui::vec2(capture.width as f32, capture.height as f32);
+                        let image = egui::Image::new((texture.id(), size * STRIP_SCALE))
+                            .sense(egui::Sense::click());
+                        if ui.add(image).clicked() {
+                            state.selected = Some(capture.id);
+                        }
+                        let label = format!("frame {}", capture.frame);
+                        if state.selected == Some(capture.id) {
+                            ui.strong(label);
+                        } else {
+                            ui.monospace(label);
+                        }
+                    });
+                }
+            });
+        });
+
+    let selected = state
+        .selected
+        .and_then(|id| captures.captures().iter().find(|capture| capture.id == id))
+        .or_else(|| captures.captures().back());
+    if let Some(capture) = selected {
+        ui.separator();
+        ui.monospace(format!(
+            "frame {} at {}  {} of surface 0x{:X}  {}x{}x{} shown at {}x{}",
+            capture.frame,
+            format_timestamp_ms(capture.timestamp_ms),
+            capture.trigger,
+            capture.surface,
+            capture.source_width,
+            capture.source_height,
+            capture.bpp,
+            capture.width,
+            capture.height
+        ));
+        let texture = texture_for(ui.ctx(), &mut state.textures, capture);
+        let size = egui::vec2(capture.width as f32, capture.height as f32);
+        ui.image((texture.id(), size * PREVIEW_SCALE));
+    }
+
+    request
+}
+
+fn texture_for(
+    ctx: &egui::Context,
+    textures: &mut HashMap<u64, egui::TextureHandle>,
+    capture: &SurfaceCapture,
+) -> egui::TextureHandle {
+    textures
+        .entry(capture.id)
+        .or_insert_with(|| {
+            let image = egui::ColorImage::from_rgba_unmultiplied(
+                [capture.width, capture.height],
+                &capture.rgba,
+            );
+            ctx.load_texture(
+                format!("surface_capture_{}", capture.id),
+                image,
+                egui::TextureOptions::NEAREST,
+            )
+        })
+        .clone()
+}
@@ -0,0 +1,133 @@
+use crate::model::timing::{TimingHistory, TimingReport};
+use crate::util::time_format::format_timestamp_ms;
+use eframe::egui;
+
+pub enum TimingRequest {
+    /// Report every N milliseconds; zero turns the timing group off.
+    SetInterval(u32),
+}
+
+pub struct TimingViewState {
+    pub enabled: bool,
+    pub interval_ms: u32,
+}
+
+impl Default for TimingViewState {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_ms: 1_000,
+        }
+    }
+}
+
+pub fn show(
+    ui: &mut egui::Ui,
+    history: &TimingHistory,
+    state: &mut TimingViewState,
+    attached: bool,
+) -> Option<TimingRequest> {
+    ui.heading("Timing");
+
+    let mut request = None;
+    ui.add_enabled_ui(attached, |ui| {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut state.enabled, "Aggregate timer calls every");
+            ui.add(egui::DragValue::new(&mut state.interval_ms).range(100..=60_000));
+            ui.label("ms");
+            if ui.button("Apply").clicked() {
+                let interval = if state.enabled { state.interval_ms } else { 0 };
+                request = Some(TimingRequest::SetInterval(interval));
+            }
+        });
+    });
+    if !attached {
+        ui.label("Attach to a process to aggregate its timer and sleep calls.");
+    }
+    match history.interval_ms() {
+        Some(0) => ui.label("Timing group is off."),
+        Some(interval) => ui.label(format!("Agent reports every {interval} ms.")),
+        None => ui.label("Timing group has not been turned on."),
+    };
+    ui.separator();
+
+    let reports = history.reports();
+    if reports.is_empty() {
+        ui.label("No timing reports yet.");
+        return request;
+    }
+
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .stick_to_bottom(true)
+        .show(ui, |ui| {
+            egui::Grid::new("timing_grid")
+                .num_columns(9)
+                .striped(true)
+                .min_col_width(70.0)
+                .show(ui, |ui| {
+                    ui.strong("Time");
+                    ui.strong("Frames");
+                    ui.strong("FPS");
+                    ui.strong("timeGetTime/frame");
+                    ui.strong("QPC/frame");
+                    ui.strong("GetTickCount/frame");
+                    ui.strong("Sleep/frame");
+                    ui.strong("Sleep ms (arg:calls)");
+                    ui.strong("timeBeginPeriod");
+                    ui.end_row();
+
+                    for report in reports {
+                        show_report_row(ui, report);
+                        ui.end_row();
+                    }
+                });
+        });
+    request
+}
+
+fn show_report_row(ui: &mut egui::Ui, report: &TimingReport) {
+    ui.monospace(format_timestamp_ms(report.timestamp_ms));
+    let frames = ui.monospace(report.frames.to_string());
+    if let Some(api) = report.frame_api {
+        frames.on_hover_text(format!("Counted from {api}"));
+    }
+    ui.monospace(
+        report
+            .frames_per_second()
+            .map_or_else(|| "-".to_owned(), |fps| format!("{fps:.1}")),
+    );
+    for calls in [
+        report.time_get_time,
+        report.query_performance_counter,
+        report.get_tick_count,
+        report.sleep_calls,
+    ] {
+        // Without presented frames the raw count is the only thing to show.
+        ui.monospace(
+            report
+                .per_frame(calls)
+                .map_or_else(|| calls.to_string(), |per_frame| format!("{per_frame:.1}")),
+        );
+    }
+    let sleeps: Vec<String> = report
+        .sleep_buckets
+        .iter()
+        .map(|(bucket, count)| format!("{bucket}:{count}"))
+        .collect();
+    ui.monospace(if sleeps.is_empty() {
+        "-".to_owned()
+    } else {
+        format!("{} (total {} ms)", sleeps.join(" "), report.sleep_total_ms)
+    });
+    let periods: Vec<String> = report
+        .timer_periods
+        .iter()
+        .map(|(period, count)| format!("{period} ms x{count}"))
+        .collect();
+    ui.monospace(if periods.is_empty() {
+        "-".to_owned()
+    } else {
+        periods.join(", ")
+    });
+}
@@ -0,0 +1,145 @@
+use crate::decode::flags::{WS_EX_FLAGS, WS_FLAGS, format_flags};
+use crate::model::windows::{TrackedWindow, TrackedWindows, WindowRect};
+use crate::util::ascii::contains_ignore_ascii_case;
+use crate::util::time_format::format_timestamp_ms;
+use eframe::egui;
+
+pub fn show(ui: &mut egui::Ui, windows: &TrackedWindows, query: &mut String) {
+    ui.heading("Windows");
+
+    ui.horizontal(|ui| {
+        ui.label("Filter:");
+        ui.add(egui::TextEdit::singleline(query).hint_text("Filter by HWND/class/title"));
+    });
+
+    ui.label(format!("Windows seen: {}", windows.len()));
+    ui.separator();
+
+    let q = query.trim();
+
+    let mut rows: Vec<&TrackedWindow> = windows
+        .values()
+        .filter(|window| {
+            if q.is_empty() {
+                return true;
+            }
+            contains_ignore_ascii_case(&format!("0x{:016X}", window.hwnd), q)
+                || contains_ignore_ascii_case(&window.class_name, q)
+                || contains_ignore_ascii_case(&window.title, q)
+        })
+        .collect();
+
+    // Live windows first, then most recently created.
+    rows.sort_by(|a, b| {
+        b.is_alive()
+            .cmp(&a.is_alive())
+            .then_with(|| b.created_ms.cmp(&a.created_ms))
+    });
+
+    if rows.is_empty() {
+        ui.label("No windows match current filter.");
+        return;
+    }
+
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for window in rows {
+                show_window(ui, window);
+            }
+        });
+}
+
+fn show_window(ui: &mut egui::Ui, window: &TrackedWindow) {
+    let state = if window.is_alive() {
+        ""
+    } else {
+        " [destroyed]"
+    };
+    let header = format!(
+        "0x{:016X}  class={}  title=\"{}\"  {}{state}",
+        window.hwnd,
+        window.class_name,
+        window.title,
+        window.rect.map_or_else(|| "-".to_owned(), format_rect)
+    );
+
+    egui::CollapsingHeader::new(header)
+        .id_salt(window.hwnd)
+        .show(ui, |ui| {
+            ui.monospace(format!(
+                "Created: {}",
+                window
+                    .created_ms
+                    .map_or_else(|| "before attach".to_owned(), format_timestamp_ms)
+            ));
+            if !window.creation_summary.is_empty() {
+                ui.monospace(format!("Create call: {}", window.creation_summary));
+            }
+            if let Some(destroyed_ms) = window.destroyed_ms {
+                ui.monospace(format!("Destroyed: {}", format_timestamp_ms(destroyed_ms)));
+            }
+            ui.monospace(format!("Parent: 0x{:016X}", window.parent));
+            ui.monospace(format!("Thread ID: {}", window.thread_id));
+            ui.monospace(format!(
+                "Style: 0x{:08X} ({})",
+                window.style,
+                format_flags(window.style, WS_FLAGS)
+            ));
+            ui.monospace(format!(
+                "Ex style: 0x{:08X} ({})",
+                window.ex_style,
+                format_flags(window.ex_style, WS_EX_FLAGS)
+            ));
+
+            ui.separator();
+            ui.strong("Geometry");
+            if window.geometry.is_empty() {
+                ui.label("No geometry changes recorded.");
+            } else {
+                egui::Grid::new(("window_geometry_grid", window.hwnd))
+                    .num_columns(3)
+                    .striped(true)
+                    .min_col_width(130.0)
+                    .show(ui, |ui| {
+                        ui.strong("Time");
+                        ui.strong("API");
+                        ui.strong("Rect");
+                        ui.end_row();
+
+                        for change in &window.geometry {
+                            ui.monospace(format_timestamp_ms(change.timestamp_ms));
+                            ui.monospace(&change.api);
+                            ui.monospace(format_rect(change.rect));
+                            ui.end_row();
+                        }
+                    });
+            }
+
+            ui.separator();
+            ui.strong("Style history");
+            egui::Grid::new(("window_style_grid", window.hwnd))
+                .num_columns(4)
+                .striped(true)
+                .min_col_width(130.0)
+                .show(ui, |ui| {
+                    ui.strong("Time");
+                    ui.strong("API");
+                    ui.strong("Style");
+                    ui.strong("Ex style");
+                    ui.end_row();
+
+                    for change in &window.styles {
+                        ui.monospace(format_timestamp_ms(change.timestamp_ms));
+                        ui.monospace(&change.api);
+                        ui.monospace(format_flags(change.style, WS_FLAGS));
+                        ui.monospace(format_flags(change.ex_style, WS_EX_FLAGS));
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+fn format_rect(rect: WindowRect) -> String {
+    format!("{},{} {}x{}", rect.x, rect.y, rect.width, rect.height)
+}
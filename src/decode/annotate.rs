@@ -0,0 +1,128 @@
+//! Symbolic annotations for the `key=value` fields of event summaries and results.
+
+use super::flags::{
+    CDS_FLAGS, CREATION_DISPOSITION_VALUES, D3D_DRIVER_TYPE_VALUES, D3D11_CREATE_DEVICE_FLAGS,
+    D3DCREATE_FLAGS, D3DDEVTYPE_VALUES, D3DFMT_VALUES, D3DPOOL_VALUES, D3DRS_VALUES,
+    D3DUSAGE_FLAGS, DDBLT_FLAGS, DDBLTFAST_FLAGS, DDFLIP_FLAGS, DDLOCK_FLAGS, DDPCAPS_FLAGS,
+    DDSCL_FLAGS, DEVICE_CAPS_VALUES, DIB_USAGE_VALUES, DISCL_FLAGS, DISP_CHANGE_VALUES,
+    DXGI_FORMAT_VALUES, DXGI_PRESENT_FLAGS, DXGI_SWAP_CHAIN_FLAGS, EnumTable, FILE_ACCESS_FLAGS,
+    FILE_FLAGS_AND_ATTRIBUTES, FILE_SHARE_FLAGS, FlagTable, REG_SAM_FLAGS, REG_TYPE_VALUES,
+    SW_VALUES, SWP_FLAGS, WIN32_ERROR_VALUES, WS_EX_FLAGS, WS_FLAGS, format_enum, format_flags,
+    format_rop,
+};
+use super::hresult::{describe_hresult, parse_hresult};
+use super::messages::message_name;
+
+enum FieldDecoder {
+    Flags(FlagTable),
+    Enum(EnumTable),
+    Rop,
+    WindowMessage,
+    HResult,
+}
+
+/// Which `key=value` fields of an event carry a known flag word, per API.
+fn field_decoder(api: &str, key: &str) -> Option<FieldDecoder> {
+    let decoder = match (api, key) {
+        (_, "HRESULT") => FieldDecoder::HResult,
+        ("SetWindowPos", "flags") => FieldDecoder::Flags(SWP_FLAGS),
+        ("CreateWindowExW" | "AdjustWindowRectEx", "style") => FieldDecoder::Flags(WS_FLAGS),
+        ("CreateWindowExW" | "AdjustWindowRectEx", "ex") => FieldDecoder::Flags(WS_EX_FLAGS),
+        ("ChangeDisplaySettingsExW", "flags") => FieldDecoder::Flags(CDS_FLAGS),
+        ("ChangeDisplaySettingsExW", "DISP_CHANGE") => FieldDecoder::Enum(DISP_CHANGE_VALUES),
+        ("ShowWindow", "cmd") => FieldDecoder::Enum(SW_VALUES),
+        ("IDirectDraw::SetCooperativeLevel", "flags") => FieldDecoder::Flags(DDSCL_FLAGS),
+        ("IDirectDraw::CreatePalette", "flags") => FieldDecoder::Flags(DDPCAPS_FLAGS),
+        ("IDirectDrawSurface::Blt", "flags") => FieldDecoder::Flags(DDBLT_FLAGS),
+        ("IDirectDrawSurface::BltFast", "trans") => FieldDecoder::Flags(DDBLTFAST_FLAGS),
+        ("IDirectDrawSurface::Flip", "flags") => FieldDecoder::Flags(DDFLIP_FLAGS),
+        ("IDirectDrawSurface::Lock", "flags") => FieldDecoder::Flags(DDLOCK_FLAGS),
+        ("IDirect3D9::CreateDevice" | "IDirect3D9Ex::CreateDeviceEx", "type") => {
+            FieldDecoder::Enum(D3DDEVTYPE_VALUES)
+        }
+        ("IDirect3D9::CreateDevice" | "IDirect3D9Ex::CreateDeviceEx", "behavior") => {
+            FieldDecoder::Flags(D3DCREATE_FLAGS)
+        }
+        ("IDirect3DDevice9::CreateTexture", "usage") => FieldDecoder::Flags(D3DUSAGE_FLAGS),
+        ("IDirect3DDevice9::CreateTexture", "pool") => FieldDecoder::Enum(D3DPOOL_VALUES),
+        ("IDirect3DDevice9::CreateTexture" | "IDirect3DDevice9::CreateRenderTarget", "format") => {
+            FieldDecoder::Enum(D3DFMT_VALUES)
+        }
+        ("IDirect3DDevice9::SetRenderState", "state") => FieldDecoder::Enum(D3DRS_VALUES),
+        ("D3D11CreateDevice" | "D3D11CreateDeviceAndSwapChain", "driver_type") => {
+            FieldDecoder::Enum(D3D_DRIVER_TYPE_VALUES)
+        }
+        ("D3D11CreateDevice" | "D3D11CreateDeviceAndSwapChain", "flags") => {
+            FieldDecoder::Flags(D3D11_CREATE_DEVICE_FLAGS)
+        }
+        ("IDXGISwapChain::Present", "flags") => FieldDecoder::Flags(DXGI_PRESENT_FLAGS),
+        ("IDXGISwapChain::ResizeBuffers", "format") => FieldDecoder::Enum(DXGI_FORMAT_VALUES),
+        ("IDXGISwapChain::ResizeBuffers", "flags") => FieldDecoder::Flags(DXGI_SWAP_CHAIN_FLAGS),
+        ("BitBlt" | "StretchBlt" | "StretchDIBits", "rop") => FieldDecoder::Rop,
+        ("StretchDIBits" | "SetDIBitsToDevice" | "CreateDIBSection", "usage") => {
+            FieldDecoder::Enum(DIB_USAGE_VALUES)
+        }
+        ("GetDeviceCaps", "index") => FieldDecoder::Enum(DEVICE_CAPS_VALUES),
+        ("WindowMessage", "msg") => FieldDecoder::WindowMessage,
+        ("IDirectInputDevice::SetCooperativeLevel", "flags") => FieldDecoder::Flags(DISCL_FLAGS),
+        ("CreateFileW" | "CreateFileA", "access") => FieldDecoder::Flags(FILE_ACCESS_FLAGS),
+        ("CreateFileW" | "CreateFileA", "share") => FieldDecoder::Flags(FILE_SHARE_FLAGS),
+        ("CreateFileW" | "CreateFileA", "disposition") => {
+            FieldDecoder::Enum(CREATION_DISPOSITION_VALUES)
+        }
+        ("CreateFileW" | "CreateFileA", "flags") => FieldDecoder::Flags(FILE_FLAGS_AND_ATTRIBUTES),
+        ("CreateFileW" | "CreateFileA" | "GetProcAddress", "error") => {
+            FieldDecoder::Enum(WIN32_ERROR_VALUES)
+        }
+        ("RegOpenKeyExW" | "RegOpenKeyExA", "sam") => FieldDecoder::Flags(REG_SAM_FLAGS),
+        ("RegOpenKeyExW" | "RegOpenKeyExA" | "RegQueryValueExW" | "RegQueryValueExA", "status") => {
+            FieldDecoder::Enum(WIN32_ERROR_VALUES)
+        }
+        ("RegQueryValueExW" | "RegQueryValueExA", "type") => FieldDecoder::Enum(REG_TYPE_VALUES),
+        _ => return None,
+    };
+    Some(decoder)
+}
+
+fn decode_field(api: &str, key: &str, value: &str) -> Option<String> {
+    match field_decoder(api, key)? {
+        FieldDecoder::Flags(table) => {
+            let value = u32::from_str_radix(value.strip_prefix("0x")?, 16).ok()?;
+            Some(format_flags(value, table))
+        }
+        FieldDecoder::Enum(table) => {
+            let value = value.parse::<i32>().ok()?;
+            format_enum(value, table).map(str::to_owned)
+        }
+        FieldDecoder::Rop => {
+            let value = u32::from_str_radix(value.strip_prefix("0x")?, 16).ok()?;
+            // Unnamed codes without modifier bits would only repeat the value.
+            Some(format_rop(value)).filter(|text| !text.starts_with("0x") || text.contains('|'))
+        }
+        FieldDecoder::WindowMessage => {
+            message_name(u32::from_str_radix(value.strip_prefix("0x")?, 16).ok()?)
+        }
+        FieldDecoder::HResult => parse_hresult(value).map(describe_hresult),
+    }
+}
+
+/// Appends the symbolic form after every recognised `key=value` field of an event's summary or
+/// result, e.g. `flags=0x00000041` becomes `flags=0x00000041 (SWP_NOSIZE|SWP_SHOWWINDOW)` and
+/// `HRESULT=0x887601C2` becomes `HRESULT=0x887601C2 (DDERR_SURFACELOST)`.
+pub fn annotate_fields(api: &str, text: &str) -> String {
+    let mut annotated = String::with_capacity(text.len());
+    for (i, token) in text.split(' ').enumerate() {
+        if i > 0 {
+            annotated.push(' ');
+        }
+        annotated.push_str(token);
+        if let Some(decoded) = token
+            .split_once('=')
+            .and_then(|(key, value)| decode_field(api, key, value))
+        {
+            annotated.push_str(&format!(" ({decoded})"));
+        }
+    }
+
+    annotated
+}
@@ -0,0 +1,883 @@
+//! Symbolic names for the Win32 and DirectDraw flag words the agent reports as hex.
+//!
+//! Tables list composite values (such as `WS_OVERLAPPEDWINDOW`) before the bits they are made of,
+//! so a value is named by the widest match first.
+
+pub type FlagTable = &'static [(u32, &'static str)];
+pub type EnumTable = &'static [(i32, &'static str)];
+
+pub const SWP_FLAGS: FlagTable = &[
+    (0x0001, "SWP_NOSIZE"),
+    (0x0002, "SWP_NOMOVE"),
+    (0x0004, "SWP_NOZORDER"),
+    (0x0008, "SWP_NOREDRAW"),
+    (0x0010, "SWP_NOACTIVATE"),
+    (0x0020, "SWP_FRAMECHANGED"),
+    (0x0040, "SWP_SHOWWINDOW"),
+    (0x0080, "SWP_HIDEWINDOW"),
+    (0x0100, "SWP_NOCOPYBITS"),
+    (0x0200, "SWP_NOOWNERZORDER"),
+    (0x0400, "SWP_NOSENDCHANGING"),
+    (0x2000, "SWP_DEFERERASE"),
+    (0x4000, "SWP_ASYNCWINDOWPOS"),
+];
+
+pub const WS_FLAGS: FlagTable = &[
+    (0x00CF_0000, "WS_OVERLAPPEDWINDOW"),
+    (0x8088_0000, "WS_POPUPWINDOW"),
+    (0x00C0_0000, "WS_CAPTION"),
+    (0x8000_0000, "WS_POPUP"),
+    (0x4000_0000, "WS_CHILD"),
+    (0x2000_0000, "WS_MINIMIZE"),
+    (0x1000_0000, "WS_VISIBLE"),
+    (0x0800_0000, "WS_DISABLED"),
+    (0x0400_0000, "WS_CLIPSIBLINGS"),
+    (0x0200_0000, "WS_CLIPCHILDREN"),
+    (0x0100_0000, "WS_MAXIMIZE"),
+    (0x0080_0000, "WS_BORDER"),
+    (0x0040_0000, "WS_DLGFRAME"),
+    (0x0020_0000, "WS_VSCROLL"),
+    (0x0010_0000, "WS_HSCROLL"),
+    (0x0008_0000, "WS_SYSMENU"),
+    (0x0004_0000, "WS_THICKFRAME"),
+    (0x0002_0000, "WS_MINIMIZEBOX"),
+    (0x0001_0000, "WS_MAXIMIZEBOX"),
+];
+
+pub const WS_EX_FLAGS: FlagTable = &[
+    (0x0000_0300, "WS_EX_OVERLAPPEDWINDOW"),
+    (0x0000_0001, "WS_EX_DLGMODALFRAME"),
+    (0x0000_0004, "WS_EX_NOPARENTNOTIFY"),
+    (0x0000_0008, "WS_EX_TOPMOST"),
+    (0x0000_0010, "WS_EX_ACCEPTFILES"),
+    (0x0000_0020, "WS_EX_TRANSPARENT"),
+    (0x0000_0040, "WS_EX_MDICHILD"),
+    (0x0000_0080, "WS_EX_TOOLWINDOW"),
+    (0x0000_0100, "WS_EX_WINDOWEDGE"),
+    (0x0000_0200, "WS_EX_CLIENTEDGE"),
+    (0x0000_0400, "WS_EX_CONTEXTHELP"),
+    (0x0000_1000, "WS_EX_RIGHT"),
+    (0x0000_2000, "WS_EX_RTLREADING"),
+    (0x0000_4000, "WS_EX_LEFTSCROLLBAR"),
+    (0x0001_0000, "WS_EX_CONTROLPARENT"),
+    (0x0002_0000, "WS_EX_STATICEDGE"),
+    (0x0004_0000, "WS_EX_APPWINDOW"),
+    (0x0008_0000, "WS_EX_LAYERED"),
+    (0x0010_0000, "WS_EX_NOINHERITLAYOUT"),
+    (0x0020_0000, "WS_EX_NOREDIRECTIONBITMAP"),
+    (0x0040_0000, "WS_EX_LAYOUTRTL"),
+    (0x0200_0000, "WS_EX_COMPOSITED"),
+    (0x0800_0000, "WS_EX_NOACTIVATE"),
+];
+
+pub const CDS_FLAGS: FlagTable = &[
+    (0x0000_0001, "CDS_UPDATEREGISTRY"),
+    (0x0000_0002, "CDS_TEST"),
+    (0x0000_0004, "CDS_FULLSCREEN"),
+    (0x0000_0008, "CDS_GLOBAL"),
+    (0x0000_0010, "CDS_SET_PRIMARY"),
+    (0x0000_0020, "CDS_VIDEOPARAMETERS"),
+    (0x0000_0100, "CDS_ENABLE_UNSAFE_MODES"),
+    (0x0000_0200, "CDS_DISABLE_UNSAFE_MODES"),
+    (0x1000_0000, "CDS_NORESET"),
+    (0x2000_0000, "CDS_RESET_EX"),
+    (0x4000_0000, "CDS_RESET"),
+];
+
+pub const DISP_CHANGE_VALUES: EnumTable = &[
+    (0, "DISP_CHANGE_SUCCESSFUL"),
+    (1, "DISP_CHANGE_RESTART"),
+    (-1, "DISP_CHANGE_FAILED"),
+    (-2, "DISP_CHANGE_BADMODE"),
+    (-3, "DISP_CHANGE_NOTUPDATED"),
+    (-4, "DISP_CHANGE_BADFLAGS"),
+    (-5, "DISP_CHANGE_BADPARAM"),
+    (-6, "DISP_CHANGE_BADDUALVIEW"),
+];
+
+pub const SW_VALUES: EnumTable = &[
+    (0, "SW_HIDE"),
+    (1, "SW_SHOWNORMAL"),
+    (2, "SW_SHOWMINIMIZED"),
+    (3, "SW_SHOWMAXIMIZED"),
+    (4, "SW_SHOWNOACTIVATE"),
+    (5, "SW_SHOW"),
+    (6, "SW_MINIMIZE"),
+    (7, "SW_SHOWMINNOACTIVE"),
+    (8, "SW_SHOWNA"),
+    (9, "SW_RESTORE"),
+    (10, "SW_SHOWDEFAULT"),
+    (11, "SW_FORCEMINIMIZE"),
+];
+
+pub const DDSCL_FLAGS: FlagTable = &[
+    (0x0000_0001, "DDSCL_FULLSCREEN"),
+    (0x0000_0002, "DDSCL_ALLOWREBOOT"),
+    (0x0000_0004, "DDSCL_NOWINDOWCHANGES"),
+    (0x0000_0008, "DDSCL_NORMAL"),
+    (0x0000_0010, "DDSCL_EXCLUSIVE"),
+    (0x0000_0040, "DDSCL_ALLOWMODEX"),
+    (0x0000_0080, "DDSCL_SETFOCUSWINDOW"),
+    (0x0000_0100, "DDSCL_SETDEVICEWINDOW"),
+    (0x0000_0200, "DDSCL_CREATEDEVICEWINDOW"),
+    (0x0000_0400, "DDSCL_MULTITHREADED"),
+    (0x0000_0800, "DDSCL_FPUSETUP"),
+    (0x0000_1000, "DDSCL_FPUPRESERVE"),
+];
+
+pub const DDBLT_FLAGS: FlagTable = &[
+    (0x0000_0001, "DDBLT_ALPHADEST"),
+    (0x0000_0002, "DDBLT_ALPHADESTCONSTOVERRIDE"),
+    (0x0000_0004, "DDBLT_ALPHADESTNEG"),
+    (0x0000_0008, "DDBLT_ALPHADESTSURFACEOVERRIDE"),
+    (0x0000_0010, "DDBLT_ALPHAEDGEBLEND"),
+    (0x0000_0020, "DDBLT_ALPHASRC"),
+    (0x0000_0040, "DDBLT_ALPHASRCCONSTOVERRIDE"),
+    (0x0000_0080, "DDBLT_ALPHASRCNEG"),
+    (0x0000_0100, "DDBLT_ALPHASRCSURFACEOVERRIDE"),
+    (0x0000_0200, "DDBLT_ASYNC"),
+    (0x0000_0400, "DDBLT_COLORFILL"),
+    (0x0000_0800, "DDBLT_DDFX"),
+    (0x0000_1000, "DDBLT_DDROPS"),
+    (0x0000_2000, "DDBLT_KEYDEST"),
+    (0x0000_4000, "DDBLT_KEYDESTOVERRIDE"),
+    (0x0000_8000, "DDBLT_KEYSRC"),
+    (0x0001_0000, "DDBLT_KEYSRCOVERRIDE"),
+    (0x0002_0000, "DDBLT_ROP"),
+    (0x0004_0000, "DDBLT_ROTATIONANGLE"),
+    (0x0008_0000, "DDBLT_ZBUFFER"),
+    (0x0010_0000, "DDBLT_ZBUFFERDESTCONSTOVERRIDE"),
+    (0x0020_0000, "DDBLT_ZBUFFERDESTOVERRIDE"),
+    (0x0040_0000, "DDBLT_ZBUFFERSRCCONSTOVERRIDE"),
+    (0x0080_0000, "DDBLT_ZBUFFERSRCOVERRIDE"),
+    (0x0100_0000, "DDBLT_WAIT"),
+    (0x0200_0000, "DDBLT_DEPTHFILL"),
+    (0x0800_0000, "DDBLT_DONOTWAIT"),
+    (0x1000_0000, "DDBLT_PRESENTATION"),
+    (0x2000_0000, "DDBLT_LAST_PRESENTATION"),
+];
+
+pub const DDBLTFAST_FLAGS: FlagTable = &[
+    (0x0000_0001, "DDBLTFAST_SRCCOLORKEY"),
+    (0x0000_0002, "DDBLTFAST_DESTCOLORKEY"),
+    (0x0000_0010, "DDBLTFAST_WAIT"),
+    (0x0000_0020, "DDBLTFAST_DONOTWAIT"),
+];
+
+pub const DDFLIP_FLAGS: FlagTable = &[
+    (0x0000_0001, "DDFLIP_WAIT"),
+    (0x0000_0002, "DDFLIP_EVEN"),
+    (0x0000_0004, "DDFLIP_ODD"),
+    (0x0000_0008, "DDFLIP_NOVSYNC"),
+    (0x0000_0010, "DDFLIP_STEREO"),
+    (0x0000_0020, "DDFLIP_DONOTWAIT"),
+    (0x0300_0000, "DDFLIP_INTERVAL3"),
+    (0x0200_0000, "DDFLIP_INTERVAL2"),
+    (0x0400_0000, "DDFLIP_INTERVAL4"),
+];
+
+pub const DDLOCK_FLAGS: FlagTable = &[
+    (0x0000_0001, "DDLOCK_WAIT"),
+    (0x0000_0002, "DDLOCK_EVENT"),
+    (0x0000_0010, "DDLOCK_READONLY"),
+    (0x0000_0020, "DDLOCK_WRITEONLY"),
+    (0x0000_0800, "DDLOCK_NOSYSLOCK"),
+    (0x0000_1000, "DDLOCK_NOOVERWRITE"),
+    (0x0000_2000, "DDLOCK_DISCARDCONTENTS"),
+    (0x0000_4000, "DDLOCK_DONOTWAIT"),
+    (0x0000_8000, "DDLOCK_HASVOLUMETEXTUREBOXRECT"),
+    (0x0001_0000, "DDLOCK_NODIRTYUPDATE"),
+];
+
+pub const DDPCAPS_FLAGS: FlagTable = &[
+    (0x0000_0001, "DDPCAPS_4BIT"),
+    (0x0000_0002, "DDPCAPS_8BITENTRIES"),
+    (0x0000_0004, "DDPCAPS_8BIT"),
+    (0x0000_0008, "DDPCAPS_INITIALIZE"),
+    (0x0000_0010, "DDPCAPS_PRIMARYSURFACE"),
+    (0x0000_0020, "DDPCAPS_PRIMARYSURFACELEFT"),
+    (0x0000_0040, "DDPCAPS_ALLOW256"),
+    (0x0000_0080, "DDPCAPS_VSYNC"),
+    (0x0000_0100, "DDPCAPS_1BIT"),
+    (0x0000_0200, "DDPCAPS_2BIT"),
+    (0x0000_0400, "DDPCAPS_ALPHA"),
+];
+
+pub const DDSCAPS_FLAGS: FlagTable = &[
+    (0x0000_0002, "DDSCAPS_ALPHA"),
+    (0x0000_0004, "DDSCAPS_BACKBUFFER"),
+    (0x0000_0008, "DDSCAPS_COMPLEX"),
+    (0x0000_0010, "DDSCAPS_FLIP"),
+    (0x0000_0020, "DDSCAPS_FRONTBUFFER"),
+    (0x0000_0040, "DDSCAPS_OFFSCREENPLAIN"),
+    (0x0000_0080, "DDSCAPS_OVERLAY"),
+    (0x0000_0100, "DDSCAPS_PALETTE"),
+    (0x0000_0200, "DDSCAPS_PRIMARYSURFACE"),
+    (0x0000_0400, "DDSCAPS_PRIMARYSURFACELEFT"),
+    (0x0000_0800, "DDSCAPS_SYSTEMMEMORY"),
+    (0x0000_1000, "DDSCAPS_TEXTURE"),
+    (0x0000_2000, "DDSCAPS_3DDEVICE"),
+    (0x0000_4000, "DDSCAPS_VIDEOMEMORY"),
+    (0x0000_8000, "DDSCAPS_VISIBLE"),
+    (0x0001_0000, "DDSCAPS_WRITEONLY"),
+    (0x0002_0000, "DDSCAPS_ZBUFFER"),
+    (0x0004_0000, "DDSCAPS_OWNDC"),
+    (0x0008_0000, "DDSCAPS_LIVEVIDEO"),
+    (0x0010_0000, "DDSCAPS_HWCODEC"),
+    (0x0020_0000, "DDSCAPS_MODEX"),
+    (0x0040_0000, "DDSCAPS_MIPMAP"),
+    (0x0400_0000, "DDSCAPS_ALLOCONLOAD"),
+    (0x0800_0000, "DDSCAPS_VIDEOPORT"),
+    (0x1000_0000, "DDSCAPS_LOCALVIDMEM"),
+    (0x2000_0000, "DDSCAPS_NONLOCALVIDMEM"),
+    (0x4000_0000, "DDSCAPS_STANDARDVGAMODE"),
+    (0x8000_0000, "DDSCAPS_OPTIMIZED"),
+];
+
+pub const D3DDEVTYPE_VALUES: EnumTable = &[
+    (1, "D3DDEVTYPE_HAL"),
+    (2, "D3DDEVTYPE_REF"),
+    (3, "D3DDEVTYPE_SW"),
+    (4, "D3DDEVTYPE_NULLREF"),
+];
+
+pub const D3DCREATE_FLAGS: FlagTable = &[
+    (0x0000_0002, "D3DCREATE_FPU_PRESERVE"),
+    (0x0000_0004, "D3DCREATE_MULTITHREADED"),
+    (0x0000_0010, "D3DCREATE_PUREDEVICE"),
+    (0x0000_0020, "D3DCREATE_SOFTWARE_VERTEXPROCESSING"),
+    (0x0000_0040, "D3DCREATE_HARDWARE_VERTEXPROCESSING"),
+    (0x0000_0080, "D3DCREATE_MIXED_VERTEXPROCESSING"),
+    (0x0000_0100, "D3DCREATE_DISABLE_DRIVER_MANAGEMENT"),
+    (0x0000_0200, "D3DCREATE_ADAPTERGROUP_DEVICE"),
+    (0x0000_0400, "D3DCREATE_DISABLE_DRIVER_MANAGEMENT_EX"),
+    (0x0000_0800, "D3DCREATE_NOWINDOWCHANGES"),
+    (0x0000_2000, "D3DCREATE_DISABLE_PSGP_THREADING"),
+    (0x0000_4000, "D3DCREATE_ENABLE_PRESENTSTATS"),
+    (0x0000_8000, "D3DCREATE_DISABLE_PRINTSCREEN"),
+    (0x1000_0000, "D3DCREATE_SCREENSAVER"),
+];
+
+pub const D3DFMT_VALUES: EnumTable = &[
+    (0, "D3DFMT_UNKNOWN"),
+    (20, "D3DFMT_R8G8B8"),
+    (21, "D3DFMT_A8R8G8B8"),
+    (22, "D3DFMT_X8R8G8B8"),
+    (23, "D3DFMT_R5G6B5"),
+    (24, "D3DFMT_X1R5G5B5"),
+    (25, "D3DFMT_A1R5G5B5"),
+    (26, "D3DFMT_A4R4G4B4"),
+    (27, "D3DFMT_R3G3B2"),
+    (28, "D3DFMT_A8"),
+    (29, "D3DFMT_A8R3G3B2"),
+    (30, "D3DFMT_X4R4G4B4"),
+    (31, "D3DFMT_A2B10G10R10"),
+    (32, "D3DFMT_A8B8G8R8"),
+    (33, "D3DFMT_X8B8G8R8"),
+    (34, "D3DFMT_G16R16"),
+    (35, "D3DFMT_A2R10G10B10"),
+    (36, "D3DFMT_A16B16G16R16"),
+    (40, "D3DFMT_A8P8"),
+    (41, "D3DFMT_P8"),
+    (50, "D3DFMT_L8"),
+    (51, "D3DFMT_A8L8"),
+    (52, "D3DFMT_A4L4"),
+    (60, "D3DFMT_V8U8"),
+    (70, "D3DFMT_D16_LOCKABLE"),
+    (71, "D3DFMT_D32"),
+    (73, "D3DFMT_D15S1"),
+    (75, "D3DFMT_D24S8"),
+    (77, "D3DFMT_D24X8"),
+    (79, "D3DFMT_D24X4S4"),
+    (80, "D3DFMT_D16"),
+    (82, "D3DFMT_D32F_LOCKABLE"),
+    (83, "D3DFMT_D24FS8"),
+    (101, "D3DFMT_INDEX16"),
+    (102, "D3DFMT_INDEX32"),
+    (111, "D3DFMT_R16F"),
+    (112, "D3DFMT_G16R16F"),
+    (113, "D3DFMT_A16B16G16R16F"),
+    (114, "D3DFMT_R32F"),
+    (115, "D3DFMT_G32R32F"),
+    (116, "D3DFMT_A32B32G32R32F"),
+    // FourCC formats.
+    (0x3154_5844, "D3DFMT_DXT1"),
+    (0x3254_5844, "D3DFMT_DXT2"),
+    (0x3354_5844, "D3DFMT_DXT3"),
+    (0x3454_5844, "D3DFMT_DXT4"),
+    (0x3554_5844, "D3DFMT_DXT5"),
+];
+
+pub const D3DSWAPEFFECT_VALUES: EnumTable = &[
+    (1, "D3DSWAPEFFECT_DISCARD"),
+    (2, "D3DSWAPEFFECT_FLIP"),
+    (3, "D3DSWAPEFFECT_COPY"),
+    (4, "D3DSWAPEFFECT_OVERLAY"),
+    (5, "D3DSWAPEFFECT_FLIPEX"),
+];
+
+pub const D3DPRESENT_INTERVAL_VALUES: EnumTable = &[
+    (0, "D3DPRESENT_INTERVAL_DEFAULT"),
+    (1, "D3DPRESENT_INTERVAL_ONE"),
+    (2, "D3DPRESENT_INTERVAL_TWO"),
+    (4, "D3DPRESENT_INTERVAL_THREE"),
+    (8, "D3DPRESENT_INTERVAL_FOUR"),
+    (0x8000_0000_u32 as i32, "D3DPRESENT_INTERVAL_IMMEDIATE"),
+];
+
+pub const D3DPRESENTFLAG_FLAGS: FlagTable = &[
+    (0x0000_0001, "D3DPRESENTFLAG_LOCKABLE_BACKBUFFER"),
+    (0x0000_0002, "D3DPRESENTFLAG_DISCARD_DEPTHSTENCIL"),
+    (0x0000_0004, "D3DPRESENTFLAG_DEVICECLIP"),
+    (0x0000_0010, "D3DPRESENTFLAG_VIDEO"),
+    (0x0000_0020, "D3DPRESENTFLAG_NOAUTOROTATE"),
+    (0x0000_0040, "D3DPRESENTFLAG_UNPRUNEDMODE"),
+    (0x0000_0080, "D3DPRESENTFLAG_OVERLAY_LIMITEDRGB"),
+    (0x0000_0100, "D3DPRESENTFLAG_OVERLAY_YCbCr_BT709"),
+    (0x0000_0200, "D3DPRESENTFLAG_OVERLAY_YCbCr_xvYCC"),
+    (0x0000_0400, "D3DPRESENTFLAG_RESTRICTED_CONTENT"),
+    (
+        0x0000_0800,
+        "D3DPRESENTFLAG_RESTRICT_SHARED_RESOURCE_DRIVER",
+    ),
+];
+
+pub const D3DUSAGE_FLAGS: FlagTable = &[
+    (0x0000_0001, "D3DUSAGE_RENDERTARGET"),
+    (0x0000_0002, "D3DUSAGE_DEPTHSTENCIL"),
+    (0x0000_0008, "D3DUSAGE_WRITEONLY"),
+    (0x0000_0010, "D3DUSAGE_SOFTWAREPROCESSING"),
+    (0x0000_0020, "D3DUSAGE_DONOTCLIP"),
+    (0x0000_0040, "D3DUSAGE_POINTS"),
+    (0x0000_0080, "D3DUSAGE_RTPATCHES"),
+    (0x0000_0100, "D3DUSAGE_NPATCHES"),
+    (0x0000_0200, "D3DUSAGE_DYNAMIC"),
+    (0x0000_0400, "D3DUSAGE_AUTOGENMIPMAP"),
+    (0x0000_0800, "D3DUSAGE_RESTRICTED_CONTENT"),
+    (0x0000_1000, "D3DUSAGE_RESTRICT_SHARED_RESOURCE_DRIVER"),
+    (0x0000_2000, "D3DUSAGE_RESTRICT_SHARED_RESOURCE"),
+    (0x0000_4000, "D3DUSAGE_DMAP"),
+    (0x0080_0000, "D3DUSAGE_NONSECURE"),
+    (0x1000_0000, "D3DUSAGE_TEXTAPI"),
+];
+
+pub const D3DPOOL_VALUES: EnumTable = &[
+    (0, "D3DPOOL_DEFAULT"),
+    (1, "D3DPOOL_MANAGED"),
+    (2, "D3DPOOL_SYSTEMMEM"),
+    (3, "D3DPOOL_SCRATCH"),
+];
+
+pub const D3DRS_VALUES: EnumTable = &[
+    (7, "D3DRS_ZENABLE"),
+    (8, "D3DRS_FILLMODE"),
+    (9, "D3DRS_SHADEMODE"),
+    (14, "D3DRS_ZWRITEENABLE"),
+    (15, "D3DRS_ALPHATESTENABLE"),
+    (16, "D3DRS_LASTPIXEL"),
+    (19, "D3DRS_SRCBLEND"),
+    (20, "D3DRS_DESTBLEND"),
+    (22, "D3DRS_CULLMODE"),
+    (23, "D3DRS_ZFUNC"),
+    (24, "D3DRS_ALPHAREF"),
+    (25, "D3DRS_ALPHAFUNC"),
+    (26, "D3DRS_DITHERENABLE"),
+    (27, "D3DRS_ALPHABLENDENABLE"),
+    (28, "D3DRS_FOGENABLE"),
+    (29, "D3DRS_SPECULARENABLE"),
+    (34, "D3DRS_FOGCOLOR"),
+    (35, "D3DRS_FOGTABLEMODE"),
+    (36, "D3DRS_FOGSTART"),
+    (37, "D3DRS_FOGEND"),
+    (38, "D3DRS_FOGDENSITY"),
+    (48, "D3DRS_RANGEFOGENABLE"),
+    (52, "D3DRS_STENCILENABLE"),
+    (53, "D3DRS_STENCILFAIL"),
+    (54, "D3DRS_STENCILZFAIL"),
+    (55, "D3DRS_STENCILPASS"),
+    (56, "D3DRS_STENCILFUNC"),
+    (57, "D3DRS_STENCILREF"),
+    (58, "D3DRS_STENCILMASK"),
+    (59, "D3DRS_STENCILWRITEMASK"),
+    (60, "D3DRS_TEXTUREFACTOR"),
+    (128, "D3DRS_WRAP0"),
+    (129, "D3DRS_WRAP1"),
+    (130, "D3DRS_WRAP2"),
+    (131, "D3DRS_WRAP3"),
+    (132, "D3DRS_WRAP4"),
+    (133, "D3DRS_WRAP5"),
+    (134, "D3DRS_WRAP6"),
+    (135, "D3DRS_WRAP7"),
+    (136, "D3DRS_CLIPPING"),
+    (137, "D3DRS_LIGHTING"),
+    (139, "D3DRS_AMBIENT"),
+    (140, "D3DRS_FOGVERTEXMODE"),
+    (141, "D3DRS_COLORVERTEX"),
+    (142, "D3DRS_LOCALVIEWER"),
+    (143, "D3DRS_NORMALIZENORMALS"),
+    (145, "D3DRS_DIFFUSEMATERIALSOURCE"),
+    (146, "D3DRS_SPECULARMATERIALSOURCE"),
+    (147, "D3DRS_AMBIENTMATERIALSOURCE"),
+    (148, "D3DRS_EMISSIVEMATERIALSOURCE"),
+    (151, "D3DRS_VERTEXBLEND"),
+    (152, "D3DRS_CLIPPLANEENABLE"),
+    (154, "D3DRS_POINTSIZE"),
+    (155, "D3DRS_POINTSIZE_MIN"),
+    (156, "D3DRS_POINTSPRITEENABLE"),
+    (157, "D3DRS_POINTSCALEENABLE"),
+    (158, "D3DRS_POINTSCALE_A"),
+    (159, "D3DRS_POINTSCALE_B"),
+    (160, "D3DRS_POINTSCALE_C"),
+    (161, "D3DRS_MULTISAMPLEANTIALIAS"),
+    (162, "D3DRS_MULTISAMPLEMASK"),
+    (163, "D3DRS_PATCHEDGESTYLE"),
+    (165, "D3DRS_DEBUGMONITORTOKEN"),
+    (166, "D3DRS_POINTSIZE_MAX"),
+    (167, "D3DRS_INDEXEDVERTEXBLENDENABLE"),
+    (168, "D3DRS_COLORWRITEENABLE"),
+    (170, "D3DRS_TWEENFACTOR"),
+    (171, "D3DRS_BLENDOP"),
+    (172, "D3DRS_POSITIONDEGREE"),
+    (173, "D3DRS_NORMALDEGREE"),
+    (174, "D3DRS_SCISSORTESTENABLE"),
+    (175, "D3DRS_SLOPESCALEDEPTHBIAS"),
+    (176, "D3DRS_ANTIALIASEDLINEENABLE"),
+    (178, "D3DRS_MINTESSELLATIONLEVEL"),
+    (179, "D3DRS_MAXTESSELLATIONLEVEL"),
+    (180, "D3DRS_ADAPTIVETESS_X"),
+    (181, "D3DRS_ADAPTIVETESS_Y"),
+    (182, "D3DRS_ADAPTIVETESS_Z"),
+    (183, "D3DRS_ADAPTIVETESS_W"),
+    (184, "D3DRS_ENABLEADAPTIVETESSELLATION"),
+    (185, "D3DRS_TWOSIDEDSTENCILMODE"),
+    (186, "D3DRS_CCW_STENCILFAIL"),
+    (187, "D3DRS_CCW_STENCILZFAIL"),
+    (188, "D3DRS_CCW_STENCILPASS"),
+    (189, "D3DRS_CCW_STENCILFUNC"),
+    (190, "D3DRS_COLORWRITEENABLE1"),
+    (191, "D3DRS_COLORWRITEENABLE2"),
+    (192, "D3DRS_COLORWRITEENABLE3"),
+    (193, "D3DRS_BLENDFACTOR"),
+    (194, "D3DRS_SRGBWRITEENABLE"),
+    (195, "D3DRS_DEPTHBIAS"),
+    (198, "D3DRS_WRAP8"),
+    (199, "D3DRS_WRAP9"),
+    (200, "D3DRS_WRAP10"),
+    (201, "D3DRS_WRAP11"),
+    (202, "D3DRS_WRAP12"),
+    (203, "D3DRS_WRAP13"),
+    (204, "D3DRS_WRAP14"),
+    (205, "D3DRS_WRAP15"),
+    (206, "D3DRS_SEPARATEALPHABLENDENABLE"),
+    (207, "D3DRS_SRCBLENDALPHA"),
+    (208, "D3DRS_DESTBLENDALPHA"),
+    (209, "D3DRS_BLENDOPALPHA"),
+];
+
+pub const D3D_DRIVER_TYPE_VALUES: EnumTable = &[
+    (0, "D3D_DRIVER_TYPE_UNKNOWN"),
+    (1, "D3D_DRIVER_TYPE_HARDWARE"),
+    (2, "D3D_DRIVER_TYPE_REFERENCE"),
+    (3, "D3D_DRIVER_TYPE_NULL"),
+    (4, "D3D_DRIVER_TYPE_SOFTWARE"),
+    (5, "D3D_DRIVER_TYPE_WARP"),
+];
+
+pub const D3D11_CREATE_DEVICE_FLAGS: FlagTable = &[
+    (0x0000_0001, "D3D11_CREATE_DEVICE_SINGLETHREADED"),
+    (0x0000_0002, "D3D11_CREATE_DEVICE_DEBUG"),
+    (0x0000_0004, "D3D11_CREATE_DEVICE_SWITCH_TO_REF"),
+    (
+        0x0000_0008,
+        "D3D11_CREATE_DEVICE_PREVENT_INTERNAL_THREADING_OPTIMIZATIONS",
+    ),
+    (0x0000_0020, "D3D11_CREATE_DEVICE_BGRA_SUPPORT"),
+    (0x0000_0040, "D3D11_CREATE_DEVICE_DEBUGGABLE"),
+    (
+        0x0000_0080,
+        "D3D11_CREATE_DEVICE_PREVENT_ALTERING_LAYER_SETTINGS_FROM_REGISTRY",
+    ),
+    (0x0000_0100, "D3D11_CREATE_DEVICE_DISABLE_GPU_TIMEOUT"),
+    (0x0000_0800, "D3D11_CREATE_DEVICE_VIDEO_SUPPORT"),
+];
+
+pub const D3D_FEATURE_LEVEL_VALUES: EnumTable = &[
+    (0x9100, "9_1"),
+    (0x9200, "9_2"),
+    (0x9300, "9_3"),
+    (0xA000, "10_0"),
+    (0xA100, "10_1"),
+    (0xB000, "11_0"),
+    (0xB100, "11_1"),
+    (0xC000, "12_0"),
+    (0xC100, "12_1"),
+    (0xC200, "12_2"),
+];
+
+pub const DXGI_FORMAT_VALUES: EnumTable = &[
+    (0, "DXGI_FORMAT_UNKNOWN"),
+    (2, "DXGI_FORMAT_R32G32B32A32_FLOAT"),
+    (10, "DXGI_FORMAT_R16G16B16A16_FLOAT"),
+    (24, "DXGI_FORMAT_R10G10B10A2_UNORM"),
+    (28, "DXGI_FORMAT_R8G8B8A8_UNORM"),
+    (29, "DXGI_FORMAT_R8G8B8A8_UNORM_SRGB"),
+    (40, "DXGI_FORMAT_D32_FLOAT"),
+    (45, "DXGI_FORMAT_D24_UNORM_S8_UINT"),
+    (55, "DXGI_FORMAT_D16_UNORM"),
+    (85, "DXGI_FORMAT_B5G6R5_UNORM"),
+    (86, "DXGI_FORMAT_B5G5R5A1_UNORM"),
+    (87, "DXGI_FORMAT_B8G8R8A8_UNORM"),
+    (88, "DXGI_FORMAT_B8G8R8X8_UNORM"),
+    (91, "DXGI_FORMAT_B8G8R8A8_UNORM_SRGB"),
+    (93, "DXGI_FORMAT_B8G8R8X8_UNORM_SRGB"),
+];
+
+pub const DXGI_MODE_SCALING_VALUES: EnumTable = &[
+    (0, "DXGI_MODE_SCALING_UNSPECIFIED"),
+    (1, "DXGI_MODE_SCALING_CENTERED"),
+    (2, "DXGI_MODE_SCALING_STRETCHED"),
+];
+
+pub const DXGI_SWAP_EFFECT_VALUES: EnumTable = &[
+    (0, "DXGI_SWAP_EFFECT_DISCARD"),
+    (1, "DXGI_SWAP_EFFECT_SEQUENTIAL"),
+    (3, "DXGI_SWAP_EFFECT_FLIP_SEQUENTIAL"),
+    (4, "DXGI_SWAP_EFFECT_FLIP_DISCARD"),
+];
+
+pub const DXGI_USAGE_FLAGS: FlagTable = &[
+    (0x0000_0010, "DXGI_USAGE_SHADER_INPUT"),
+    (0x0000_0020, "DXGI_USAGE_RENDER_TARGET_OUTPUT"),
+    (0x0000_0040, "DXGI_USAGE_BACK_BUFFER"),
+    (0x0000_0080, "DXGI_USAGE_SHARED"),
+    (0x0000_0100, "DXGI_USAGE_READ_ONLY"),
+    (0x0000_0200, "DXGI_USAGE_DISCARD_ON_PRESENT"),
+    (0x0000_0400, "DXGI_USAGE_UNORDERED_ACCESS"),
+];
+
+pub const DXGI_SWAP_CHAIN_FLAGS: FlagTable = &[
+    (0x0000_0001, "DXGI_SWAP_CHAIN_FLAG_NONPREROTATED"),
+    (0x0000_0002, "DXGI_SWAP_CHAIN_FLAG_ALLOW_MODE_SWITCH"),
+    (0x0000_0004, "DXGI_SWAP_CHAIN_FLAG_GDI_COMPATIBLE"),
+    (0x0000_0008, "DXGI_SWAP_CHAIN_FLAG_RESTRICTED_CONTENT"),
+    (
+        0x0000_0010,
+        "DXGI_SWAP_CHAIN_FLAG_RESTRICT_SHARED_RESOURCE_DRIVER",
+    ),
+    (0x0000_0020, "DXGI_SWAP_CHAIN_FLAG_DISPLAY_ONLY"),
+    (
+        0x0000_0040,
+        "DXGI_SWAP_CHAIN_FLAG_FRAME_LATENCY_WAITABLE_OBJECT",
+    ),
+    (0x0000_0080, "DXGI_SWAP_CHAIN_FLAG_FOREGROUND_LAYER"),
+    (0x0000_0100, "DXGI_SWAP_CHAIN_FLAG_FULLSCREEN_VIDEO"),
+    (0x0000_0200, "DXGI_SWAP_CHAIN_FLAG_YUV_VIDEO"),
+    (0x0000_0400, "DXGI_SWAP_CHAIN_FLAG_HW_PROTECTED"),
+    (0x0000_0800, "DXGI_SWAP_CHAIN_FLAG_ALLOW_TEARING"),
+];
+
+pub const DXGI_PRESENT_FLAGS: FlagTable = &[
+    (0x0000_0001, "DXGI_PRESENT_TEST"),
+    (0x0000_0002, "DXGI_PRESENT_DO_NOT_SEQUENCE"),
+    (0x0000_0004, "DXGI_PRESENT_RESTART"),
+    (0x0000_0008, "DXGI_PRESENT_DO_NOT_WAIT"),
+    (0x0000_0010, "DXGI_PRESENT_STEREO_PREFER_RIGHT"),
+    (0x0000_0020, "DXGI_PRESENT_STEREO_TEMPORARY_MONO"),
+    (0x0000_0040, "DXGI_PRESENT_RESTRICT_TO_OUTPUT"),
+    (0x0000_0100, "DXGI_PRESENT_USE_DURATION"),
+    (0x0000_0200, "DXGI_PRESENT_ALLOW_TEARING"),
+];
+
+pub const PFD_FLAGS: FlagTable = &[
+    (0x0000_0001, "PFD_DOUBLEBUFFER"),
+    (0x0000_0002, "PFD_STEREO"),
+    (0x0000_0004, "PFD_DRAW_TO_WINDOW"),
+    (0x0000_0008, "PFD_DRAW_TO_BITMAP"),
+    (0x0000_0010, "PFD_SUPPORT_GDI"),
+    (0x0000_0020, "PFD_SUPPORT_OPENGL"),
+    (0x0000_0040, "PFD_GENERIC_FORMAT"),
+    (0x0000_0080, "PFD_NEED_PALETTE"),
+    (0x0000_0100, "PFD_NEED_SYSTEM_PALETTE"),
+    (0x0000_0200, "PFD_SWAP_EXCHANGE"),
+    (0x0000_0400, "PFD_SWAP_COPY"),
+    (0x0000_0800, "PFD_SWAP_LAYER_BUFFERS"),
+    (0x0000_1000, "PFD_GENERIC_ACCELERATED"),
+    (0x0000_2000, "PFD_SUPPORT_DIRECTDRAW"),
+    (0x0000_4000, "PFD_DIRECT3D_ACCELERATED"),
+    (0x0000_8000, "PFD_SUPPORT_COMPOSITION"),
+    (0x2000_0000, "PFD_DEPTH_DONTCARE"),
+    (0x4000_0000, "PFD_DOUBLEBUFFER_DONTCARE"),
+    (0x8000_0000, "PFD_STEREO_DONTCARE"),
+];
+
+pub const PFD_PIXEL_TYPE_VALUES: EnumTable = &[(0, "PFD_TYPE_RGBA"), (1, "PFD_TYPE_COLORINDEX")];
+
+/// Ternary raster operations with a name; any other code is printed as hex.
+pub const ROP_VALUES: EnumTable = &[
+    (0x00CC_0020, "SRCCOPY"),
+    (0x00EE_0086, "SRCPAINT"),
+    (0x0088_00C6, "SRCAND"),
+    (0x0066_0046, "SRCINVERT"),
+    (0x0044_0328, "SRCERASE"),
+    (0x0033_0008, "NOTSRCCOPY"),
+    (0x0011_00A6, "NOTSRCERASE"),
+    (0x00C0_00CA, "MERGECOPY"),
+    (0x00BB_0226, "MERGEPAINT"),
+    (0x00F0_0021, "PATCOPY"),
+    (0x00FB_0A09, "PATPAINT"),
+    (0x005A_0049, "PATINVERT"),
+    (0x0055_0009, "DSTINVERT"),
+    (0x0000_0042, "BLACKNESS"),
+    (0x00FF_0062, "WHITENESS"),
+];
+
+/// Modifier bits BitBlt and StretchBlt accept on top of the raster operation.
+pub const ROP_MODIFIER_FLAGS: FlagTable =
+    &[(0x4000_0000, "CAPTUREBLT"), (0x8000_0000, "NOMIRRORBITMAP")];
+
+pub const DIB_USAGE_VALUES: EnumTable = &[(0, "DIB_RGB_COLORS"), (1, "DIB_PAL_COLORS")];
+
+pub const BI_COMPRESSION_VALUES: EnumTable = &[
+    (0, "BI_RGB"),
+    (1, "BI_RLE8"),
+    (2, "BI_RLE4"),
+    (3, "BI_BITFIELDS"),
+    (4, "BI_JPEG"),
+    (5, "BI_PNG"),
+];
+
+pub const DEVICE_CAPS_VALUES: EnumTable = &[
+    (0, "DRIVERVERSION"),
+    (2, "TECHNOLOGY"),
+    (4, "HORZSIZE"),
+    (6, "VERTSIZE"),
+    (8, "HORZRES"),
+    (10, "VERTRES"),
+    (12, "BITSPIXEL"),
+    (14, "PLANES"),
+    (24, "NUMCOLORS"),
+    (38, "RASTERCAPS"),
+    (40, "ASPECTX"),
+    (42, "ASPECTY"),
+    (88, "LOGPIXELSX"),
+    (90, "LOGPIXELSY"),
+    (104, "SIZEPALETTE"),
+    (106, "NUMRESERVED"),
+    (108, "COLORRES"),
+    (110, "PHYSICALWIDTH"),
+    (111, "PHYSICALHEIGHT"),
+    (116, "VREFRESH"),
+    (117, "DESKTOPVERTRES"),
+    (118, "DESKTOPHORZRES"),
+    (119, "BLTALIGNMENT"),
+    (120, "SHADEBLENDCAPS"),
+];
+
+/// `RIDEV_NOLEGACY` is `RIDEV_EXCLUDE|RIDEV_PAGEONLY`, so it has to come first.
+pub const RIDEV_FLAGS: FlagTable = &[
+    (0x0000_0001, "RIDEV_REMOVE"),
+    (0x0000_0030, "RIDEV_NOLEGACY"),
+    (0x0000_0010, "RIDEV_EXCLUDE"),
+    (0x0000_0020, "RIDEV_PAGEONLY"),
+    (0x0000_0100, "RIDEV_INPUTSINK"),
+    // RIDEV_NOHOTKEYS for keyboards.
+    (0x0000_0200, "RIDEV_CAPTUREMOUSE"),
+    (0x0000_0400, "RIDEV_APPKEYS"),
+    (0x0000_1000, "RIDEV_EXINPUTSINK"),
+    (0x0000_2000, "RIDEV_DEVNOTIFY"),
+];
+
+pub const DISCL_FLAGS: FlagTable = &[
+    (0x0000_0001, "DISCL_EXCLUSIVE"),
+    (0x0000_0002, "DISCL_NONEXCLUSIVE"),
+    (0x0000_0004, "DISCL_FOREGROUND"),
+    (0x0000_0008, "DISCL_BACKGROUND"),
+    (0x0000_0010, "DISCL_NOWINKEY"),
+];
+
+/// `CreateFile` desired access; the generic rights come first since games rarely ask for more.
+pub const FILE_ACCESS_FLAGS: FlagTable = &[
+    (0x8000_0000, "GENERIC_READ"),
+    (0x4000_0000, "GENERIC_WRITE"),
+    (0x2000_0000, "GENERIC_EXECUTE"),
+    (0x1000_0000, "GENERIC_ALL"),
+    (0x0000_0001, "FILE_READ_DATA"),
+    (0x0000_0002, "FILE_WRITE_DATA"),
+    (0x0000_0004, "FILE_APPEND_DATA"),
+    (0x0000_0008, "FILE_READ_EA"),
+    (0x0000_0010, "FILE_WRITE_EA"),
+    (0x0000_0020, "FILE_EXECUTE"),
+    (0x0000_0080, "FILE_READ_ATTRIBUTES"),
+    (0x0000_0100, "FILE_WRITE_ATTRIBUTES"),
+    (0x0001_0000, "DELETE"),
+    (0x0002_0000, "READ_CONTROL"),
+    (0x0010_0000, "SYNCHRONIZE"),
+];
+
+pub const FILE_SHARE_FLAGS: FlagTable = &[
+    (0x0000_0001, "FILE_SHARE_READ"),
+    (0x0000_0002, "FILE_SHARE_WRITE"),
+    (0x0000_0004, "FILE_SHARE_DELETE"),
+];
+
+pub const CREATION_DISPOSITION_VALUES: EnumTable = &[
+    (1, "CREATE_NEW"),
+    (2, "CREATE_ALWAYS"),
+    (3, "OPEN_EXISTING"),
+    (4, "OPEN_ALWAYS"),
+    (5, "TRUNCATE_EXISTING"),
+];
+
+/// `CreateFile` flags and attributes.
+pub const FILE_FLAGS_AND_ATTRIBUTES: FlagTable = &[
+    (0x0000_0001, "FILE_ATTRIBUTE_READONLY"),
+    (0x0000_0002, "FILE_ATTRIBUTE_HIDDEN"),
+    (0x0000_0004, "FILE_ATTRIBUTE_SYSTEM"),
+    (0x0000_0020, "FILE_ATTRIBUTE_ARCHIVE"),
+    (0x0000_0080, "FILE_ATTRIBUTE_NORMAL"),
+    (0x0000_0100, "FILE_ATTRIBUTE_TEMPORARY"),
+    (0x8000_0000, "FILE_FLAG_WRITE_THROUGH"),
+    (0x4000_0000, "FILE_FLAG_OVERLAPPED"),
+    (0x2000_0000, "FILE_FLAG_NO_BUFFERING"),
+    (0x1000_0000, "FILE_FLAG_RANDOM_ACCESS"),
+    (0x0800_0000, "FILE_FLAG_SEQUENTIAL_SCAN"),
+    (0x0400_0000, "FILE_FLAG_DELETE_ON_CLOSE"),
+    (0x0200_0000, "FILE_FLAG_BACKUP_SEMANTICS"),
+];
+
+/// Registry access masks; the composite rights come first.
+pub const REG_SAM_FLAGS: FlagTable = &[
+    (0x000F_003F, "KEY_ALL_ACCESS"),
+    (0x0002_0019, "KEY_READ"),
+    (0x0002_0006, "KEY_WRITE"),
+    (0x0000_0001, "KEY_QUERY_VALUE"),
+    (0x0000_0002, "KEY_SET_VALUE"),
+    (0x0000_0004, "KEY_CREATE_SUB_KEY"),
+    (0x0000_0008, "KEY_ENUMERATE_SUB_KEYS"),
+    (0x0000_0010, "KEY_NOTIFY"),
+    (0x0000_0020, "KEY_CREATE_LINK"),
+    (0x0000_0100, "KEY_WOW64_64KEY"),
+    (0x0000_0200, "KEY_WOW64_32KEY"),
+    (0x0001_0000, "DELETE"),
+    (0x0002_0000, "READ_CONTROL"),
+    (0x0004_0000, "WRITE_DAC"),
+    (0x0008_0000, "WRITE_OWNER"),
+    (0x0200_0000, "MAXIMUM_ALLOWED"),
+];
+
+pub const REG_TYPE_VALUES: EnumTable = &[
+    (0, "REG_NONE"),
+    (1, "REG_SZ"),
+    (2, "REG_EXPAND_SZ"),
+    (3, "REG_BINARY"),
+    (4, "REG_DWORD"),
+    (5, "REG_DWORD_BIG_ENDIAN"),
+    (6, "REG_LINK"),
+    (7, "REG_MULTI_SZ"),
+    (11, "REG_QWORD"),
+];
+
+/// The Win32 error codes file, registry and export lookups commonly fail with.
+pub const WIN32_ERROR_VALUES: EnumTable = &[
+    (0, "ERROR_SUCCESS"),
+    (2, "ERROR_FILE_NOT_FOUND"),
+    (3, "ERROR_PATH_NOT_FOUND"),
+    (5, "ERROR_ACCESS_DENIED"),
+    (6, "ERROR_INVALID_HANDLE"),
+    (32, "ERROR_SHARING_VIOLATION"),
+    (80, "ERROR_FILE_EXISTS"),
+    (87, "ERROR_INVALID_PARAMETER"),
+    (123, "ERROR_INVALID_NAME"),
+    (126, "ERROR_MOD_NOT_FOUND"),
+    (127, "ERROR_PROC_NOT_FOUND"),
+    (183, "ERROR_ALREADY_EXISTS"),
+    (234, "ERROR_MORE_DATA"),
+    (259, "ERROR_NO_MORE_ITEMS"),
+    (1009, "ERROR_BADDB"),
+    (1010, "ERROR_BADKEY"),
+];
+
+/// Renders `value` as `NAME|NAME|0x...`, with any bits missing from `table` left in hex.
+pub fn format_flags(value: u32, table: FlagTable) -> String {
+    if value == 0 {
+        return "0".to_owned();
+    }
+
+    let mut names = Vec::new();
+    let mut remaining = value;
+    for &(bit, name) in table {
+        if bit != 0 && value & bit == bit && remaining & bit != 0 {
+            names.push(name.to_owned());
+            remaining &= !bit;
+        }
+    }
+    if remaining != 0 {
+        names.push(format!("0x{remaining:X}"));
+    }
+
+    names.join("|")
+}
+
+pub fn format_enum(value: i32, table: EnumTable) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|(candidate, _)| *candidate == value)
+        .map(|(_, name)| *name)
+}
+
+/// Names a raster operation code, e.g. `SRCCOPY|CAPTUREBLT`.
+pub fn format_rop(value: u32) -> String {
+    let modifiers = value & 0xC000_0000;
+    let rop = value & !0xC000_0000;
+    let mut text =
+        format_enum(rop as i32, ROP_VALUES).map_or_else(|| format!("0x{rop:08X}"), str::to_owned);
+    if modifiers != 0 {
+        text.push('|');
+        text.push_str(&format_flags(modifiers, ROP_MODIFIER_FLAGS));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn d3dcreate_flags_match_d3d9_headers() {
+        assert_eq!(
+            format_flags(0x0000_0044, D3DCREATE_FLAGS),
+            "D3DCREATE_MULTITHREADED|D3DCREATE_HARDWARE_VERTEXPROCESSING"
+        );
+        assert_eq!(
+            format_flags(0x1000_E000, D3DCREATE_FLAGS),
+            "D3DCREATE_DISABLE_PSGP_THREADING|D3DCREATE_ENABLE_PRESENTSTATS|\
+             D3DCREATE_DISABLE_PRINTSCREEN|D3DCREATE_SCREENSAVER"
+        );
+        assert_eq!(format_flags(0x0000_1000, D3DCREATE_FLAGS), "0x1000");
+    }
+
+    #[test]
+    fn d3dusage_flags_match_d3d9_headers() {
+        assert_eq!(
+            format_flags(0x0000_0201, D3DUSAGE_FLAGS),
+            "D3DUSAGE_RENDERTARGET|D3DUSAGE_DYNAMIC"
+        );
+        assert_eq!(
+            format_flags(0x1080_0000, D3DUSAGE_FLAGS),
+            "D3DUSAGE_NONSECURE|D3DUSAGE_TEXTAPI"
+        );
+        assert_eq!(format_flags(0x0003_0000, D3DUSAGE_FLAGS), "0x30000");
+    }
+
+    #[test]
+    fn ddlock_flags_match_ddraw_headers() {
+        assert_eq!(
+            format_flags(0x0000_0011, DDLOCK_FLAGS),
+            "DDLOCK_WAIT|DDLOCK_READONLY"
+        );
+        assert_eq!(
+            format_flags(0x0001_8000, DDLOCK_FLAGS),
+            "DDLOCK_HASVOLUMETEXTUREBOXRECT|DDLOCK_NODIRTYUPDATE"
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! Names for the HRESULTs the agent reports as `HRESULT=0x...`.
+
+const FACILITY_WIN32_PREFIX: u32 = 0x8007_0000;
+
+/// Exact codes: COM/Win32 generics first, then DirectDraw/Direct3D (facility 0x876, which both
+/// share) and DXGI (facility 0x87A).
+const HRESULT_NAMES: &[(u32, &str)] = &[
+    (0x0000_0000, "S_OK"),
+    (0x0000_0001, "S_FALSE"),
+    (0x8000_FFFF, "E_UNEXPECTED"),
+    (0x8000_000A, "E_PENDING"),
+    (0x8000_4001, "E_NOTIMPL/DDERR_UNSUPPORTED"),
+    (0x8000_4002, "E_NOINTERFACE/DDERR_NOINTERFACE"),
+    (0x8000_4003, "E_POINTER"),
+    (0x8000_4004, "E_ABORT"),
+    (0x8000_4005, "E_FAIL/DDERR_GENERIC"),
+    (0x8004_0110, "CLASS_E_NOAGGREGATION"),
+    (0x8004_0154, "REGDB_E_CLASSNOTREG"),
+    (0x8004_01F0, "CO_E_NOTINITIALIZED/DDERR_NOTINITIALIZED"),
+    (0x8007_0005, "E_ACCESSDENIED"),
+    (0x8007_0006, "E_HANDLE"),
+    (0x8007_000E, "E_OUTOFMEMORY/DDERR_OUTOFMEMORY"),
+    (0x8007_0057, "E_INVALIDARG/DDERR_INVALIDPARAMS"),
+    (0x8876_0005, "DDERR_ALREADYINITIALIZED"),
+    (0x8876_000A, "DDERR_CANNOTATTACHSURFACE"),
+    (0x8876_0014, "DDERR_CANNOTDETACHSURFACE"),
+    (0x8876_0028, "DDERR_CURRENTLYNOTAVAIL"),
+    (0x8876_0037, "DDERR_EXCEPTION"),
+    (0x8876_005A, "DDERR_HEIGHTALIGN"),
+    (0x8876_005F, "DDERR_INCOMPATIBLEPRIMARY"),
+    (0x8876_0064, "DDERR_INVALIDCAPS"),
+    (0x8876_006E, "DDERR_INVALIDCLIPLIST"),
+    (0x8876_0078, "DDERR_INVALIDMODE"),
+    (0x8876_0082, "DDERR_INVALIDOBJECT"),
+    (0x8876_0091, "DDERR_INVALIDPIXELFORMAT"),
+    (0x8876_0096, "DDERR_INVALIDRECT"),
+    (0x8876_00A0, "DDERR_LOCKEDSURFACES"),
+    (0x8876_00AA, "DDERR_NO3D"),
+    (0x8876_00B4, "DDERR_NOALPHAHW"),
+    (0x8876_00B5, "DDERR_NOSTEREOHARDWARE"),
+    (0x8876_00B6, "DDERR_NOSURFACELEFT"),
+    (0x8876_00CD, "DDERR_NOCLIPLIST"),
+    (0x8876_00D2, "DDERR_NOCOLORCONVHW"),
+    (0x8876_00D4, "DDERR_NOCOOPERATIVELEVELSET"),
+    (0x8876_00D7, "DDERR_NOCOLORKEY"),
+    (0x8876_00DC, "DDERR_NOCOLORKEYHW"),
+    (0x8876_00DE, "DDERR_NODIRECTDRAWSUPPORT"),
+    (0x8876_00E1, "DDERR_NOEXCLUSIVEMODE"),
+    (0x8876_00E6, "DDERR_NOFLIPHW"),
+    (0x8876_00F0, "DDERR_NOGDI"),
+    (0x8876_00FA, "DDERR_NOMIRRORHW"),
+    (0x8876_00FF, "DDERR_NOTFOUND"),
+    (0x8876_0104, "DDERR_NOOVERLAYHW"),
+    (0x8876_010E, "DDERR_OVERLAPPINGRECTS"),
+    (0x8876_0118, "DDERR_NORASTEROPHW"),
+    (0x8876_0122, "DDERR_NOROTATIONHW"),
+    (0x8876_0136, "DDERR_NOSTRETCHHW"),
+    (0x8876_013C, "DDERR_NOT4BITCOLOR"),
+    (0x8876_013D, "DDERR_NOT4BITCOLORINDEX"),
+    (0x8876_0140, "DDERR_NOT8BITCOLOR"),
+    (0x8876_014A, "DDERR_NOTEXTUREHW"),
+    (0x8876_014F, "DDERR_NOVSYNCHW"),
+    (0x8876_0154, "DDERR_NOZBUFFERHW"),
+    (0x8876_015E, "DDERR_NOZOVERLAYHW"),
+    (0x8876_0168, "DDERR_OUTOFCAPS"),
+    (
+        0x8876_017C,
+        "DDERR_OUTOFVIDEOMEMORY/D3DERR_OUTOFVIDEOMEMORY",
+    ),
+    (0x8876_017E, "DDERR_OVERLAYCANTCLIP"),
+    (0x8876_0180, "DDERR_OVERLAYCOLORKEYONLYONEACTIVE"),
+    (0x8876_0183, "DDERR_PALETTEBUSY"),
+    (0x8876_0190, "DDERR_COLORKEYNOTSET"),
+    (0x8876_019A, "DDERR_SURFACEALREADYATTACHED"),
+    (0x8876_01A4, "DDERR_SURFACEALREADYDEPENDENT"),
+    (0x8876_01AE, "DDERR_SURFACEBUSY"),
+    (0x8876_01B3, "DDERR_CANTLOCKSURFACE"),
+    (0x8876_01B8, "DDERR_SURFACEISOBSCURED"),
+    (0x8876_01C2, "DDERR_SURFACELOST"),
+    (0x8876_01CC, "DDERR_SURFACENOTATTACHED"),
+    (0x8876_01D6, "DDERR_TOOBIGHEIGHT"),
+    (0x8876_01E0, "DDERR_TOOBIGSIZE"),
+    (0x8876_01EA, "DDERR_TOOBIGWIDTH"),
+    (0x8876_01FE, "DDERR_UNSUPPORTEDFORMAT"),
+    (0x8876_0208, "DDERR_UNSUPPORTEDMASK"),
+    (0x8876_0209, "DDERR_INVALIDSTREAM"),
+    (0x8876_0219, "DDERR_VERTICALBLANKINPROGRESS"),
+    (0x8876_021C, "DDERR_WASSTILLDRAWING/D3DERR_WASSTILLDRAWING"),
+    (0x8876_021E, "DDERR_DDSCAPSCOMPLEXREQUIRED"),
+    (0x8876_0230, "DDERR_XALIGN"),
+    (0x8876_0231, "DDERR_INVALIDDIRECTDRAWGUID"),
+    (0x8876_0232, "DDERR_DIRECTDRAWALREADYCREATED"),
+    (0x8876_0233, "DDERR_NODIRECTDRAWHW"),
+    (0x8876_0234, "DDERR_PRIMARYSURFACEALREADYEXISTS"),
+    (0x8876_0235, "DDERR_NOEMULATION"),
+    (0x8876_0236, "DDERR_REGIONTOOSMALL"),
+    (0x8876_0237, "DDERR_CLIPPERISUSINGHWND"),
+    (0x8876_0238, "DDERR_NOCLIPPERATTACHED"),
+    (0x8876_0239, "DDERR_NOHWND"),
+    (0x8876_023A, "DDERR_HWNDSUBCLASSED"),
+    (0x8876_023B, "DDERR_HWNDALREADYSET"),
+    (0x8876_023C, "DDERR_NOPALETTEATTACHED"),
+    (0x8876_023D, "DDERR_NOPALETTEHW"),
+    (0x8876_023E, "DDERR_BLTFASTCANTCLIP"),
+    (0x8876_023F, "DDERR_NOBLTHW"),
+    (0x8876_0240, "DDERR_NODDROPSHW"),
+    (0x8876_0241, "DDERR_OVERLAYNOTVISIBLE"),
+    (0x8876_0242, "DDERR_NOOVERLAYDEST"),
+    (0x8876_0243, "DDERR_INVALIDPOSITION"),
+    (0x8876_0244, "DDERR_NOTAOVERLAYSURFACE"),
+    (0x8876_0245, "DDERR_EXCLUSIVEMODEALREADYSET"),
+    (0x8876_0246, "DDERR_NOTFLIPPABLE"),
+    (0x8876_0247, "DDERR_CANTDUPLICATE"),
+    (0x8876_0248, "DDERR_NOTLOCKED"),
+    (0x8876_0249, "DDERR_CANTCREATEDC"),
+    (0x8876_024A, "DDERR_NODC"),
+    (0x8876_024B, "DDERR_WRONGMODE"),
+    (0x8876_024C, "DDERR_IMPLICITLYCREATED"),
+    (0x8876_024D, "DDERR_NOTPALETTIZED"),
+    (0x8876_024E, "DDERR_UNSUPPORTEDMODE"),
+    (0x8876_024F, "DDERR_NOMIPMAPHW"),
+    (0x8876_0250, "DDERR_INVALIDSURFACETYPE"),
+    (0x8876_0258, "DDERR_NOOPTIMIZEHW"),
+    (0x8876_0259, "DDERR_NOTLOADED"),
+    (0x8876_025A, "DDERR_NOFOCUSWINDOW"),
+    (0x8876_025B, "DDERR_NOTONMIPMAPSUBLEVEL"),
+    (0x8876_026C, "DDERR_DCALREADYCREATED"),
+    (0x8876_0276, "DDERR_NONONLOCALVIDMEM"),
+    (0x8876_0280, "DDERR_CANTPAGELOCK"),
+    (0x8876_0294, "DDERR_CANTPAGEUNLOCK"),
+    (0x8876_02A8, "DDERR_NOTPAGELOCKED"),
+    (0x8876_02B2, "DDERR_MOREDATA"),
+    (0x8876_02B3, "DDERR_EXPIRED"),
+    (0x8876_02B4, "DDERR_TESTFINISHED"),
+    (0x8876_02B5, "DDERR_NEWMODE"),
+    (0x8876_02B6, "DDERR_D3DNOTINITIALIZED"),
+    (0x8876_02B7, "DDERR_VIDEONOTACTIVE"),
+    (0x8876_02B8, "DDERR_NOMONITORINFORMATION"),
+    (0x8876_02B9, "DDERR_NODRIVERSUPPORT"),
+    (0x8876_02BB, "DDERR_DEVICEDOESNTOWNSURFACE"),
+    (0x8876_0818, "D3DERR_WRONGTEXTUREFORMAT"),
+    (0x8876_0819, "D3DERR_UNSUPPORTEDCOLOROPERATION"),
+    (0x8876_081A, "D3DERR_UNSUPPORTEDCOLORARG"),
+    (0x8876_081B, "D3DERR_UNSUPPORTEDALPHAOPERATION"),
+    (0x8876_081C, "D3DERR_UNSUPPORTEDALPHAARG"),
+    (0x8876_081D, "D3DERR_TOOMANYOPERATIONS"),
+    (0x8876_081E, "D3DERR_CONFLICTINGTEXTUREFILTER"),
+    (0x8876_081F, "D3DERR_UNSUPPORTEDFACTORVALUE"),
+    (0x8876_0821, "D3DERR_CONFLICTINGRENDERSTATE"),
+    (0x8876_0822, "D3DERR_UNSUPPORTEDTEXTUREFILTER"),
+    (0x8876_0826, "D3DERR_CONFLICTINGTEXTUREPALETTE"),
+    (0x8876_0827, "D3DERR_DRIVERINTERNALERROR"),
+    (0x8876_0866, "D3DERR_NOTFOUND"),
+    (0x8876_0867, "D3DERR_MOREDATA"),
+    (0x8876_0868, "D3DERR_DEVICELOST"),
+    (0x8876_0869, "D3DERR_DEVICENOTRESET"),
+    (0x8876_086A, "D3DERR_NOTAVAILABLE"),
+    (0x8876_086B, "D3DERR_INVALIDDEVICE"),
+    (0x8876_086C, "D3DERR_INVALIDCALL"),
+    (0x8876_086D, "D3DERR_DRIVERINVALIDCALL"),
+    (0x8876_0870, "D3DERR_DEVICEREMOVED"),
+    (0x8876_0874, "D3DERR_DEVICEHUNG"),
+    (0x8876_087B, "D3DERR_UNSUPPORTEDOVERLAY"),
+    (0x8876_087C, "D3DERR_UNSUPPORTEDOVERLAYFORMAT"),
+    (0x8876_087D, "D3DERR_CANNOTPROTECTCONTENT"),
+    (0x8876_087E, "D3DERR_UNSUPPORTEDCRYPTO"),
+    (0x8876_0884, "D3DERR_PRESENT_STATISTICS_DISJOINT"),
+    (0x0876_086F, "D3DOK_NOAUTOGEN"),
+    (0x0876_0877, "S_PRESENT_MODE_CHANGED"),
+    (0x0876_0878, "S_PRESENT_OCCLUDED"),
+    (0x887A_0001, "DXGI_ERROR_INVALID_CALL"),
+    (0x887A_0002, "DXGI_ERROR_NOT_FOUND"),
+    (0x887A_0003, "DXGI_ERROR_MORE_DATA"),
+    (0x887A_0004, "DXGI_ERROR_UNSUPPORTED"),
+    (0x887A_0005, "DXGI_ERROR_DEVICE_REMOVED"),
+    (0x887A_0006, "DXGI_ERROR_DEVICE_HUNG"),
+    (0x887A_0007, "DXGI_ERROR_DEVICE_RESET"),
+    (0x887A_000A, "DXGI_ERROR_WAS_STILL_DRAWING"),
+    (0x887A_000B, "DXGI_ERROR_FRAME_STATISTICS_DISJOINT"),
+    (0x887A_000C, "DXGI_ERROR_GRAPHICS_VIDPN_SOURCE_IN_USE"),
+    (0x887A_0020, "DXGI_ERROR_DRIVER_INTERNAL_ERROR"),
+    (0x887A_0021, "DXGI_ERROR_NONEXCLUSIVE"),
+    (0x887A_0022, "DXGI_ERROR_NOT_CURRENTLY_AVAILABLE"),
+    (0x887A_0023, "DXGI_ERROR_REMOTE_CLIENT_DISCONNECTED"),
+    (0x887A_0024, "DXGI_ERROR_REMOTE_OUTOFMEMORY"),
+    (0x887A_0025, "DXGI_ERROR_MODE_CHANGE_IN_PROGRESS"),
+    (0x887A_0026, "DXGI_ERROR_ACCESS_LOST"),
+    (0x887A_0027, "DXGI_ERROR_WAIT_TIMEOUT"),
+    (0x887A_0028, "DXGI_ERROR_SESSION_DISCONNECTED"),
+    (0x887A_0029, "DXGI_ERROR_RESTRICT_TO_OUTPUT_STALE"),
+    (0x887A_002A, "DXGI_ERROR_CANNOT_PROTECT_CONTENT"),
+    (0x887A_002B, "DXGI_ERROR_ACCESS_DENIED"),
+    (0x887A_002C, "DXGI_ERROR_NAME_ALREADY_EXISTS"),
+    (0x887A_002D, "DXGI_ERROR_SDK_COMPONENT_MISSING"),
+    (0x887A_002E, "DXGI_ERROR_NOT_CURRENT"),
+    (0x887A_0030, "DXGI_ERROR_HW_PROTECTION_OUTOFMEMORY"),
+    (0x887A_0031, "DXGI_ERROR_DYNAMIC_CODE_POLICY_VIOLATION"),
+    (0x887A_0032, "DXGI_ERROR_NON_COMPOSITED_UI"),
+    (0x887A_0033, "DXGI_ERROR_CACHE_CORRUPT"),
+    (0x887A_0034, "DXGI_ERROR_CACHE_FULL"),
+    (0x887A_0035, "DXGI_ERROR_CACHE_HASH_COLLISION"),
+    (0x887A_0036, "DXGI_ERROR_ALREADY_EXISTS"),
+    (0x087A_0001, "DXGI_STATUS_OCCLUDED"),
+];
+
+/// Win32 error codes most often wrapped by `HRESULT_FROM_WIN32` in graphics and loader paths.
+const WIN32_ERROR_NAMES: &[(u32, &str)] = &[
+    (2, "ERROR_FILE_NOT_FOUND"),
+    (3, "ERROR_PATH_NOT_FOUND"),
+    (5, "ERROR_ACCESS_DENIED"),
+    (6, "ERROR_INVALID_HANDLE"),
+    (8, "ERROR_NOT_ENOUGH_MEMORY"),
+    (14, "ERROR_OUTOFMEMORY"),
+    (50, "ERROR_NOT_SUPPORTED"),
+    (87, "ERROR_INVALID_PARAMETER"),
+    (120, "ERROR_CALL_NOT_IMPLEMENTED"),
+    (122, "ERROR_INSUFFICIENT_BUFFER"),
+    (126, "ERROR_MOD_NOT_FOUND"),
+    (127, "ERROR_PROC_NOT_FOUND"),
+    (170, "ERROR_BUSY"),
+    (183, "ERROR_ALREADY_EXISTS"),
+    (998, "ERROR_NOACCESS"),
+    (1168, "ERROR_NOT_FOUND"),
+    (1400, "ERROR_INVALID_WINDOW_HANDLE"),
+    (1460, "ERROR_TIMEOUT"),
+];
+
+/// Same test as the agent's `hresult_succeeded`: the severity bit is clear.
+pub fn hresult_succeeded(value: i32) -> bool {
+    value >= 0
+}
+
+/// Parses the `0x...` value of an `HRESULT=` field.
+pub fn parse_hresult(value: &str) -> Option<i32> {
+    let hex = value.strip_prefix("0x")?;
+    u32::from_str_radix(hex, 16).ok().map(|value| value as i32)
+}
+
+/// Finds the `HRESULT=0x...` field in an event result.
+pub fn find_hresult(text: &str) -> Option<i32> {
+    text.split(' ')
+        .find_map(|token| token.strip_prefix("HRESULT="))
+        .and_then(parse_hresult)
+}
+
+/// Returns the symbolic name of `value`, falling back to its facility and code.
+pub fn describe_hresult(value: i32) -> String {
+    let value = value as u32;
+    if let Some((_, name)) = HRESULT_NAMES.iter().find(|(code, _)| *code == value) {
+        return (*name).to_owned();
+    }
+
+    if value & 0xFFFF_0000 == FACILITY_WIN32_PREFIX {
+        let code = value & 0xFFFF;
+        return match WIN32_ERROR_NAMES.iter().find(|(error, _)| *error == code) {
+            Some((_, name)) => format!("HRESULT_FROM_WIN32({name})"),
+            None => format!("HRESULT_FROM_WIN32({code})"),
+        };
+    }
+
+    let severity = if hresult_succeeded(value as i32) {
+        "SUCCESS"
+    } else {
+        "FAILURE"
+    };
+    format!(
+        "{severity} facility=0x{:X} code=0x{:X}",
+        (value >> 16) & 0x1FFF,
+        value & 0xFFFF
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_ddraw_and_d3d_errors() {
+        assert_eq!(
+            describe_hresult(0x8876_01C2_u32 as i32),
+            "DDERR_SURFACELOST"
+        );
+        assert_eq!(
+            describe_hresult(0x8876_0868_u32 as i32),
+            "D3DERR_DEVICELOST"
+        );
+        assert_eq!(
+            describe_hresult(0x8000_4005_u32 as i32),
+            "E_FAIL/DDERR_GENERIC"
+        );
+    }
+
+    #[test]
+    fn names_success_codes() {
+        assert_eq!(describe_hresult(0), "S_OK");
+        assert_eq!(describe_hresult(1), "S_FALSE");
+        assert_eq!(describe_hresult(0x087A_0001), "DXGI_STATUS_OCCLUDED");
+    }
+
+    #[test]
+    fn falls_back_to_win32_error_and_facility() {
+        assert_eq!(
+            describe_hresult(0x8007_007E_u32 as i32),
+            "HRESULT_FROM_WIN32(ERROR_MOD_NOT_FOUND)"
+        );
+        assert_eq!(
+            describe_hresult(0x8007_04C7_u32 as i32),
+            "HRESULT_FROM_WIN32(1223)"
+        );
+        assert_eq!(
+            describe_hresult(0x8876_7777_u32 as i32),
+            "FAILURE facility=0x876 code=0x7777"
+        );
+    }
+
+    #[test]
+    fn succeeded_tests_the_severity_bit() {
+        assert!(hresult_succeeded(0));
+        assert!(hresult_succeeded(1));
+        assert!(hresult_succeeded(0x087A_0001));
+        assert!(!hresult_succeeded(0x8876_01C2_u32 as i32));
+        assert!(!hresult_succeeded(0x8000_4005_u32 as i32));
+    }
+
+    #[test]
+    fn finds_hresult_field_in_results() {
+        assert_eq!(
+            find_hresult("HRESULT=0x887601C2"),
+            Some(0x8876_01C2_u32 as i32)
+        );
+        assert_eq!(find_hresult("count=3 HRESULT=0x00000001"), Some(1));
+        assert_eq!(find_hresult("TRUE"), None);
+        assert_eq!(parse_hresult("887601C2"), None);
+    }
+}
@@ -0,0 +1,310 @@
+//! Window message names, classes and parameter decoding.
+//!
+//! The agent formats the decoded parameters into `WindowMessage` events; the UI names the message
+//! and filters by class.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageClass {
+    Activation,
+    Sizing,
+    Input,
+    Display,
+    Other,
+}
+
+impl MessageClass {
+    pub const ALL: [Self; 5] = [
+        Self::Activation,
+        Self::Sizing,
+        Self::Input,
+        Self::Display,
+        Self::Other,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Activation => "Activation",
+            Self::Sizing => "Sizing",
+            Self::Input => "Input",
+            Self::Display => "Display",
+            Self::Other => "Other",
+        }
+    }
+}
+
+pub const WM_ACTIVATE: u32 = 0x0006;
+pub const WM_ACTIVATEAPP: u32 = 0x001C;
+pub const WM_DISPLAYCHANGE: u32 = 0x007E;
+pub const WM_NCDESTROY: u32 = 0x0082;
+const WM_MOVE: u32 = 0x0003;
+const WM_SIZE: u32 = 0x0005;
+const WM_SHOWWINDOW: u32 = 0x0018;
+const WM_KEYDOWN: u32 = 0x0100;
+const WM_SYSKEYUP: u32 = 0x0105;
+const WM_MOUSEFIRST: u32 = 0x0200;
+const WM_MOUSELAST: u32 = 0x020E;
+const WM_SYSCOMMAND: u32 = 0x0112;
+const WM_USER: u32 = 0x0400;
+const WM_APP: u32 = 0x8000;
+/// `RegisterWindowMessage` hands out 0xC000..=0xFFFF.
+const REGISTERED_MESSAGE_FIRST: u32 = 0xC000;
+
+pub const WINDOW_MESSAGES: &[(u32, &str, MessageClass)] = &[
+    (0x0000, "WM_NULL", MessageClass::Other),
+    (0x0001, "WM_CREATE", MessageClass::Other),
+    (0x0002, "WM_DESTROY", MessageClass::Other),
+    (WM_MOVE, "WM_MOVE", MessageClass::Sizing),
+    (WM_SIZE, "WM_SIZE", MessageClass::Sizing),
+    (WM_ACTIVATE, "WM_ACTIVATE", MessageClass::Activation),
+    (0x0007, "WM_SETFOCUS", MessageClass::Activation),
+    (0x0008, "WM_KILLFOCUS", MessageClass::Activation),
+    (0x000A, "WM_ENABLE", MessageClass::Activation),
+    (0x000C, "WM_SETTEXT", MessageClass::Other),
+    (0x000D, "WM_GETTEXT", MessageClass::Other),
+    (0x000F, "WM_PAINT", MessageClass::Display),
+    (0x0010, "WM_CLOSE", MessageClass::Other),
+    (0x0012, "WM_QUIT", MessageClass::Other),
+    (0x0014, "WM_ERASEBKGND", MessageClass::Display),
+    (WM_SHOWWINDOW, "WM_SHOWWINDOW", MessageClass::Activation),
+    (0x001A, "WM_SETTINGCHANGE", MessageClass::Display),
+    (WM_ACTIVATEAPP, "WM_ACTIVATEAPP", MessageClass::Activation),
+    (0x0020, "WM_SETCURSOR", MessageClass::Input),
+    (0x0021, "WM_MOUSEACTIVATE", MessageClass::Activation),
+    (0x0024, "WM_GETMINMAXINFO", MessageClass::Sizing),
+    (0x003D, "WM_GETOBJECT", MessageClass::Other),
+    (0x0046, "WM_WINDOWPOSCHANGING", MessageClass::Sizing),
+    (0x0047, "WM_WINDOWPOSCHANGED", MessageClass::Sizing),
+    (0x007C, "WM_STYLECHANGING", MessageClass::Sizing),
+    (0x007D, "WM_STYLECHANGED", MessageClass::Sizing),
+    (WM_DISPLAYCHANGE, "WM_DISPLAYCHANGE", MessageClass::Display),
+    (0x007F, "WM_GETICON", MessageClass::Other),
+    (0x0080, "WM_SETICON", MessageClass::Other),
+    (0x0081, "WM_NCCREATE", MessageClass::Other),
+    (WM_NCDESTROY, "WM_NCDESTROY", MessageClass::Other),
+    (0x0083, "WM_NCCALCSIZE", MessageClass::Sizing),
+    (0x0084, "WM_NCHITTEST", MessageClass::Input),
+    (0x0085, "WM_NCPAINT", MessageClass::Display),
+    (0x0086, "WM_NCACTIVATE", MessageClass::Activation),
+    (0x0088, "WM_SYNCPAINT", MessageClass::Display),
+    (0x00A0, "WM_NCMOUSEMOVE", MessageClass::Input),
+    (0x00A1, "WM_NCLBUTTONDOWN", MessageClass::Input),
+    (0x00FE, "WM_INPUT_DEVICE_CHANGE", MessageClass::Input),
+    (0x00FF, "WM_INPUT", MessageClass::Input),
+    (WM_KEYDOWN, "WM_KEYDOWN", MessageClass::Input),
+    (0x0101, "WM_KEYUP", MessageClass::Input),
+    (0x0102, "WM_CHAR", MessageClass::Input),
+    (0x0103, "WM_DEADCHAR", MessageClass::Input),
+    (0x0104, "WM_SYSKEYDOWN", MessageClass::Input),
+    (WM_SYSKEYUP, "WM_SYSKEYUP", MessageClass::Input),
+    (0x0106, "WM_SYSCHAR", MessageClass::Input),
+    (0x0111, "WM_COMMAND", MessageClass::Other),
+    (WM_SYSCOMMAND, "WM_SYSCOMMAND", MessageClass::Activation),
+    (0x0113, "WM_TIMER", MessageClass::Other),
+    (0x0121, "WM_ENTERIDLE", MessageClass::Other),
+    (WM_MOUSEFIRST, "WM_MOUSEMOVE", MessageClass::Input),
+    (0x0201, "WM_LBUTTONDOWN", MessageClass::Input),
+    (0x0202, "WM_LBUTTONUP", MessageClass::Input),
+    (0x0203, "WM_LBUTTONDBLCLK", MessageClass::Input),
+    (0x0204, "WM_RBUTTONDOWN", MessageClass::Input),
+    (0x0205, "WM_RBUTTONUP", MessageClass::Input),
+    (0x0206, "WM_RBUTTONDBLCLK", MessageClass::Input),
+    (0x0207, "WM_MBUTTONDOWN", MessageClass::Input),
+    (0x0208, "WM_MBUTTONUP", MessageClass::Input),
+    (0x0209, "WM_MBUTTONDBLCLK", MessageClass::Input),
+    (0x020A, "WM_MOUSEWHEEL", MessageClass::Input),
+    (0x020B, "WM_XBUTTONDOWN", MessageClass::Input),
+    (0x020C, "WM_XBUTTONUP", MessageClass::Input),
+    (WM_MOUSELAST, "WM_MOUSEHWHEEL", MessageClass::Input),
+    (0x0211, "WM_ENTERMENULOOP", MessageClass::Other),
+    (0x0212, "WM_EXITMENULOOP", MessageClass::Other),
+    (0x0214, "WM_SIZING", MessageClass::Sizing),
+    (0x0215, "WM_CAPTURECHANGED", MessageClass::Input),
+    (0x0216, "WM_MOVING", MessageClass::Sizing),
+    (0x0231, "WM_ENTERSIZEMOVE", MessageClass::Sizing),
+    (0x0232, "WM_EXITSIZEMOVE", MessageClass::Sizing),
+    (0x0281, "WM_IME_SETCONTEXT", MessageClass::Input),
+    (0x0282, "WM_IME_NOTIFY", MessageClass::Input),
+    (0x02A3, "WM_MOUSELEAVE", MessageClass::Input),
+    (0x02E0, "WM_DPICHANGED", MessageClass::Sizing),
+    (0x030F, "WM_QUERYNEWPALETTE", MessageClass::Display),
+    (0x0310, "WM_PALETTEISCHANGING", MessageClass::Display),
+    (0x0311, "WM_PALETTECHANGED", MessageClass::Display),
+    (0x031A, "WM_THEMECHANGED", MessageClass::Display),
+    (0x031E, "WM_DWMCOMPOSITIONCHANGED", MessageClass::Display),
+];
+
+const WA_VALUES: &[(usize, &str)] = &[(0, "WA_INACTIVE"), (1, "WA_ACTIVE"), (2, "WA_CLICKACTIVE")];
+
+const SIZE_VALUES: &[(usize, &str)] = &[
+    (0, "SIZE_RESTORED"),
+    (1, "SIZE_MINIMIZED"),
+    (2, "SIZE_MAXIMIZED"),
+    (3, "SIZE_MAXSHOW"),
+    (4, "SIZE_MAXHIDE"),
+];
+
+const SC_VALUES: &[(usize, &str)] = &[
+    (0xF000, "SC_SIZE"),
+    (0xF010, "SC_MOVE"),
+    (0xF020, "SC_MINIMIZE"),
+    (0xF030, "SC_MAXIMIZE"),
+    (0xF060, "SC_CLOSE"),
+    (0xF100, "SC_KEYMENU"),
+    (0xF120, "SC_RESTORE"),
+    (0xF140, "SC_SCREENSAVE"),
+    (0xF170, "SC_MONITORPOWER"),
+];
+
+fn lookup(msg: u32) -> Option<&'static (u32, &'static str, MessageClass)> {
+    WINDOW_MESSAGES.iter().find(|(value, _, _)| *value == msg)
+}
+
+/// `WM_SIZE`, `WM_USER+5`, `WM_APP+1` or `registered 0xC123`; `None` for unnamed system messages.
+pub fn message_name(msg: u32) -> Option<String> {
+    if let Some((_, name, _)) = lookup(msg) {
+        return Some((*name).to_owned());
+    }
+    match msg {
+        WM_USER..WM_APP => Some(format!("WM_USER+{}", msg - WM_USER)),
+        WM_APP..REGISTERED_MESSAGE_FIRST => Some(format!("WM_APP+{}", msg - WM_APP)),
+        REGISTERED_MESSAGE_FIRST..=0xFFFF => Some(format!("registered 0x{msg:04X}")),
+        _ => None,
+    }
+}
+
+pub fn message_class(msg: u32) -> MessageClass {
+    lookup(msg).map_or(MessageClass::Other, |(_, _, class)| *class)
+}
+
+fn name_in(value: usize, table: &[(usize, &'static str)]) -> String {
+    table
+        .iter()
+        .find(|(candidate, _)| *candidate == value)
+        .map_or_else(|| value.to_string(), |(_, name)| (*name).to_owned())
+}
+
+fn low_word(value: isize) -> i16 {
+    value as u16 as i16
+}
+
+fn high_word(value: isize) -> i16 {
+    (value >> 16) as u16 as i16
+}
+
+/// Decoded `wParam`/`lParam` fields for the messages that matter when tracing window and display
+/// changes, e.g. `type=SIZE_MAXIMIZED size=1024x768` for `WM_SIZE`.
+pub fn describe_message_params(msg: u32, wparam: usize, lparam: isize) -> Option<String> {
+    let bool_text = |value: usize| if value != 0 { "TRUE" } else { "FALSE" };
+    let size = || format!("{}x{}", low_word(lparam) as u16, high_word(lparam) as u16);
+    let position = || format!("{},{}", low_word(lparam), high_word(lparam));
+    let text = match msg {
+        WM_ACTIVATEAPP => format!("active={} thread={lparam}", bool_text(wparam)),
+        WM_ACTIVATE => format!(
+            "state={} minimized={}",
+            name_in(wparam & 0xFFFF, WA_VALUES),
+            bool_text(wparam >> 16)
+        ),
+        WM_SHOWWINDOW => format!("show={}", bool_text(wparam)),
+        WM_SIZE => format!("type={} size={}", name_in(wparam, SIZE_VALUES), size()),
+        WM_MOVE => format!("pos={}", position()),
+        WM_DISPLAYCHANGE => format!("bpp={wparam} size={}", size()),
+        WM_SYSCOMMAND => format!("command={}", name_in(wparam & 0xFFF0, SC_VALUES)),
+        WM_KEYDOWN..=WM_SYSKEYUP => format!("vk=0x{:02X}", wparam & 0xFFFF),
+        WM_MOUSEFIRST..=WM_MOUSELAST => format!("pos={}", position()),
+        _ => return None,
+    };
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn table_values_and_names_are_unique() {
+        let mut values = HashSet::new();
+        let mut names = HashSet::new();
+        for (value, name, _) in WINDOW_MESSAGES {
+            assert!(values.insert(*value), "duplicate value 0x{value:04X}");
+            assert!(names.insert(*name), "duplicate name {name}");
+            assert!(name.starts_with("WM_"), "{name}");
+        }
+    }
+
+    #[test]
+    fn table_is_sorted_by_value() {
+        assert!(WINDOW_MESSAGES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn names_well_known_messages() {
+        assert_eq!(message_name(0x0005).as_deref(), Some("WM_SIZE"));
+        assert_eq!(message_name(0x001C).as_deref(), Some("WM_ACTIVATEAPP"));
+        assert_eq!(message_name(0x007E).as_deref(), Some("WM_DISPLAYCHANGE"));
+        assert_eq!(message_name(0x0200).as_deref(), Some("WM_MOUSEMOVE"));
+    }
+
+    #[test]
+    fn names_user_app_and_registered_ranges() {
+        assert_eq!(message_name(0x0400).as_deref(), Some("WM_USER+0"));
+        assert_eq!(message_name(0x0405).as_deref(), Some("WM_USER+5"));
+        assert_eq!(message_name(0x8001).as_deref(), Some("WM_APP+1"));
+        assert_eq!(message_name(0xC0DE).as_deref(), Some("registered 0xC0DE"));
+        assert_eq!(message_name(0x0399), None);
+        assert_eq!(message_name(0x1_0000), None);
+    }
+
+    #[test]
+    fn classifies_messages() {
+        assert_eq!(message_class(WM_ACTIVATEAPP), MessageClass::Activation);
+        assert_eq!(message_class(WM_SIZE), MessageClass::Sizing);
+        assert_eq!(message_class(0x0201), MessageClass::Input);
+        assert_eq!(message_class(WM_DISPLAYCHANGE), MessageClass::Display);
+        assert_eq!(message_class(0x0113), MessageClass::Other);
+        assert_eq!(message_class(0x0405), MessageClass::Other);
+    }
+
+    #[test]
+    fn describes_size_and_display_change() {
+        let lparam = (768 << 16) | 1024;
+        assert_eq!(
+            describe_message_params(WM_SIZE, 2, lparam).as_deref(),
+            Some("type=SIZE_MAXIMIZED size=1024x768")
+        );
+        assert_eq!(
+            describe_message_params(WM_DISPLAYCHANGE, 16, (480 << 16) | 640).as_deref(),
+            Some("bpp=16 size=640x480")
+        );
+    }
+
+    #[test]
+    fn describes_activation() {
+        assert_eq!(
+            describe_message_params(WM_ACTIVATEAPP, 0, 1234).as_deref(),
+            Some("active=FALSE thread=1234")
+        );
+        assert_eq!(
+            describe_message_params(WM_ACTIVATE, 0x0001_0002, 0).as_deref(),
+            Some("state=WA_CLICKACTIVE minimized=TRUE")
+        );
+        assert_eq!(
+            describe_message_params(WM_SYSCOMMAND, 0xF022, 0).as_deref(),
+            Some("command=SC_MINIMIZE")
+        );
+    }
+
+    #[test]
+    fn mouse_positions_are_signed() {
+        let lparam = ((-5i16 as u16 as isize) << 16) | (-10i16 as u16 as isize);
+        assert_eq!(
+            describe_message_params(WM_MOUSEFIRST, 0, lparam).as_deref(),
+            Some("pos=-10,-5")
+        );
+    }
+
+    #[test]
+    fn leaves_other_messages_undecoded() {
+        assert_eq!(describe_message_params(0x0113, 1, 0), None);
+    }
+}
@@ -0,0 +1,252 @@
+//! Conversion of raw DirectDraw surface pixels to 8-bit RGBA.
+
+/// Layout of the pixels the agent reads back from a locked surface.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// One byte per pixel indexing a 256-entry `PALETTEENTRY` table.
+    Palettized8,
+    /// Packed little-endian pixels of 2, 3 or 4 bytes described by their channel masks.
+    Rgb {
+        bytes_per_pixel: usize,
+        red_mask: u32,
+        green_mask: u32,
+        blue_mask: u32,
+        alpha_mask: u32,
+    },
+}
+
+impl PixelFormat {
+    pub const RGB565: Self = Self::rgb(2, 0xF800, 0x07E0, 0x001F);
+    pub const RGB555: Self = Self::rgb(2, 0x7C00, 0x03E0, 0x001F);
+    pub const RGB888: Self = Self::rgb(3, 0x00FF_0000, 0x0000_FF00, 0x0000_00FF);
+    pub const XRGB8888: Self = Self::rgb(4, 0x00FF_0000, 0x0000_FF00, 0x0000_00FF);
+
+    const fn rgb(bytes_per_pixel: usize, red_mask: u32, green_mask: u32, blue_mask: u32) -> Self {
+        Self::Rgb {
+            bytes_per_pixel,
+            red_mask,
+            green_mask,
+            blue_mask,
+            alpha_mask: 0,
+        }
+    }
+
+    /// Builds an RGB format from a `DDPIXELFORMAT` bit count and masks. Drivers that leave the
+    /// masks zeroed get the usual 565 or X8R8G8B8 layout for their depth.
+    pub fn from_masks(bit_count: u32, red: u32, green: u32, blue: u32, alpha: u32) -> Option<Self> {
+        let bytes_per_pixel = match bit_count {
+            16 => 2,
+            24 => 3,
+            32 => 4,
+            _ => return None,
+        };
+        if red == 0 && green == 0 && blue == 0 {
+            return Some(match bytes_per_pixel {
+                2 => Self::RGB565,
+                3 => Self::RGB888,
+                _ => Self::XRGB8888,
+            });
+        }
+        Some(Self::Rgb {
+            bytes_per_pixel,
+            red_mask: red,
+            green_mask: green,
+            blue_mask: blue,
+            alpha_mask: alpha,
+        })
+    }
+
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            Self::Palettized8 => 1,
+            Self::Rgb {
+                bytes_per_pixel, ..
+            } => *bytes_per_pixel,
+        }
+    }
+}
+
+/// Converts `height` rows of `width` pixels, each row starting `pitch` bytes after the previous
+/// one, to tightly packed RGBA. `palette` holds `PALETTEENTRY`s (red, green, blue, flags);
+/// indices it does not cover are shown as grey levels. Returns `None` if `data` is too short.
+pub fn to_rgba(
+    format: PixelFormat,
+    width: usize,
+    height: usize,
+    pitch: usize,
+    data: &[u8],
+    palette: &[u8],
+) -> Option<Vec<u8>> {
+    let bytes_per_pixel = format.bytes_per_pixel();
+    let row_bytes = width.checked_mul(bytes_per_pixel)?;
+    if pitch < row_bytes {
+        return None;
+    }
+    if height > 0 && data.len() < (height - 1) * pitch + row_bytes {
+        return None;
+    }
+
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for row in 0..height {
+        let row_data = &data[row * pitch..row * pitch + row_bytes];
+        for pixel in row_data.chunks_exact(bytes_per_pixel) {
+            rgba.extend_from_slice(&convert_pixel(format, pixel, palette));
+        }
+    }
+    Some(rgba)
+}
+
+fn convert_pixel(format: PixelFormat, pixel: &[u8], palette: &[u8]) -> [u8; 4] {
+    match format {
+        PixelFormat::Palettized8 => {
+            let index = pixel[0] as usize;
+            match palette.get(index * 4..index * 4 + 3) {
+                Some(entry) => [entry[0], entry[1], entry[2], 0xFF],
+                None => [pixel[0], pixel[0], pixel[0], 0xFF],
+            }
+        }
+        PixelFormat::Rgb {
+            red_mask,
+            green_mask,
+            blue_mask,
+            alpha_mask,
+            ..
+        } => {
+            let mut raw = [0u8; 4];
+            raw[..pixel.len()].copy_from_slice(pixel);
+            let value = u32::from_le_bytes(raw);
+            let alpha = if alpha_mask == 0 {
+                0xFF
+            } else {
+                channel(value, alpha_mask)
+            };
+            [
+                channel(value, red_mask),
+                channel(value, green_mask),
+                channel(value, blue_mask),
+                alpha,
+            ]
+        }
+    }
+}
+
+/// Scales the bits under `mask` to 0..=255, so a 5-bit 31 becomes 255.
+fn channel(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let shift = mask.trailing_zeros();
+    let max = u64::from(mask >> shift);
+    let bits = u64::from((value & mask) >> shift);
+    ((bits * 255 + max / 2) / max) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn convert_one(format: PixelFormat, pixel: &[u8]) -> [u8; 4] {
+        let rgba = to_rgba(format, 1, 1, pixel.len(), pixel, &[]).unwrap();
+        rgba.try_into().unwrap()
+    }
+
+    #[test]
+    fn rgb565_primaries() {
+        let f = PixelFormat::RGB565;
+        assert_eq!(convert_one(f, &0xF800u16.to_le_bytes()), [255, 0, 0, 255]);
+        assert_eq!(convert_one(f, &0x07E0u16.to_le_bytes()), [0, 255, 0, 255]);
+        assert_eq!(convert_one(f, &0x001Fu16.to_le_bytes()), [0, 0, 255, 255]);
+        assert_eq!(
+            convert_one(f, &0xFFFFu16.to_le_bytes()),
+            [255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn rgb565_scales_mid_values() {
+        // Red 16 of 31, green 32 of 63, blue 8 of 31.
+        let pixel = (16u16 << 11) | (32 << 5) | 8;
+        assert_eq!(
+            convert_one(PixelFormat::RGB565, &pixel.to_le_bytes()),
+            [132, 130, 66, 255]
+        );
+    }
+
+    #[test]
+    fn rgb555_ignores_top_bit() {
+        let f = PixelFormat::RGB555;
+        assert_eq!(convert_one(f, &0x7C00u16.to_le_bytes()), [255, 0, 0, 255]);
+        assert_eq!(convert_one(f, &0x83E0u16.to_le_bytes()), [0, 255, 0, 255]);
+    }
+
+    #[test]
+    fn rgb888_is_stored_blue_first() {
+        assert_eq!(
+            convert_one(PixelFormat::RGB888, &[0x30, 0x20, 0x10]),
+            [0x10, 0x20, 0x30, 255]
+        );
+    }
+
+    #[test]
+    fn xrgb8888_ignores_unused_byte() {
+        assert_eq!(
+            convert_one(PixelFormat::XRGB8888, &[0x30, 0x20, 0x10, 0x7F]),
+            [0x10, 0x20, 0x30, 255]
+        );
+    }
+
+    #[test]
+    fn argb8888_keeps_alpha() {
+        let f = PixelFormat::from_masks(32, 0x00FF_0000, 0x0000_FF00, 0x0000_00FF, 0xFF00_0000)
+            .unwrap();
+        assert_eq!(
+            convert_one(f, &[0x30, 0x20, 0x10, 0x7F]),
+            [0x10, 0x20, 0x30, 0x7F]
+        );
+    }
+
+    #[test]
+    fn zero_masks_fall_back_to_common_layouts() {
+        assert_eq!(
+            PixelFormat::from_masks(16, 0, 0, 0, 0),
+            Some(PixelFormat::RGB565)
+        );
+        assert_eq!(
+            PixelFormat::from_masks(24, 0, 0, 0, 0),
+            Some(PixelFormat::RGB888)
+        );
+        assert_eq!(
+            PixelFormat::from_masks(32, 0, 0, 0, 0),
+            Some(PixelFormat::XRGB8888)
+        );
+        assert_eq!(PixelFormat::from_masks(8, 0, 0, 0, 0), None);
+    }
+
+    #[test]
+    fn palettized_looks_up_entries() {
+        let mut palette = vec![0u8; 256 * 4];
+        palette[4 * 7..4 * 7 + 4].copy_from_slice(&[10, 20, 30, 0]);
+        let rgba = to_rgba(PixelFormat::Palettized8, 2, 1, 2, &[7, 0], &palette).unwrap();
+        assert_eq!(rgba, [10, 20, 30, 255, 0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn palettized_without_palette_is_greyscale() {
+        let rgba = to_rgba(PixelFormat::Palettized8, 1, 1, 1, &[0x40], &[]).unwrap();
+        assert_eq!(rgba, [0x40, 0x40, 0x40, 255]);
+    }
+
+    #[test]
+    fn pitch_padding_is_skipped() {
+        // Two 1-pixel rows, each padded to 4 bytes.
+        let data = [0x1F, 0x00, 0xAA, 0xAA, 0x00, 0xF8];
+        let rgba = to_rgba(PixelFormat::RGB565, 1, 2, 4, &data, &[]).unwrap();
+        assert_eq!(rgba, [0, 0, 255, 255, 255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn short_data_is_rejected() {
+        assert_eq!(to_rgba(PixelFormat::RGB565, 2, 2, 4, &[0; 7], &[]), None);
+        assert_eq!(to_rgba(PixelFormat::RGB565, 2, 1, 3, &[0; 4], &[]), None);
+    }
+}
@@ -0,0 +1,991 @@
+//! Decoders for the small Win32/DirectX structures detours receive by pointer.
+//!
+//! Everything here works on byte buffers copied out of the traced process, so the agent only has
+//! to guard the read itself.
+
+use super::flags::{
+    BI_COMPRESSION_VALUES, D3D_FEATURE_LEVEL_VALUES, D3DFMT_VALUES, D3DPRESENT_INTERVAL_VALUES,
+    D3DPRESENTFLAG_FLAGS, D3DSWAPEFFECT_VALUES, DXGI_FORMAT_VALUES, DXGI_MODE_SCALING_VALUES,
+    DXGI_SWAP_CHAIN_FLAGS, DXGI_SWAP_EFFECT_VALUES, DXGI_USAGE_FLAGS, EnumTable, FlagTable,
+    PFD_FLAGS, PFD_PIXEL_TYPE_VALUES, RIDEV_FLAGS, format_enum, format_flags,
+};
+use super::surface_desc::{ColorKey, align_up, read_u32};
+use std::fmt;
+
+pub const RECT_SIZE: usize = 16;
+pub const GUID_SIZE: usize = 16;
+/// `DXGI_MODE_DESC`: six DWORDs with the refresh rate as a numerator/denominator pair.
+pub const DXGI_MODE_DESC_SIZE: usize = 28;
+pub const PIXELFORMATDESCRIPTOR_SIZE: usize = 40;
+pub const BITMAPINFOHEADER_SIZE: usize = 40;
+/// `DEVMODEW` up to and including `dmDisplayFrequency`; drivers may append private data.
+pub const DEVMODEW_DISPLAY_SIZE: usize = 188;
+
+const DEVMODEW_DEVICE_NAME_BYTES: usize = 64;
+const DEVMODEW_FIELDS_OFFSET: usize = 72;
+const DEVMODEW_POSITION_OFFSET: usize = 76;
+const DEVMODEW_BITS_PER_PEL_OFFSET: usize = 168;
+const DEVMODEW_PELS_WIDTH_OFFSET: usize = 172;
+const DEVMODEW_PELS_HEIGHT_OFFSET: usize = 176;
+const DEVMODEW_DISPLAY_FLAGS_OFFSET: usize = 180;
+const DEVMODEW_DISPLAY_FREQUENCY_OFFSET: usize = 184;
+
+pub const DM_POSITION: u32 = 0x0000_0020;
+pub const DM_DISPLAYORIENTATION: u32 = 0x0000_0080;
+pub const DM_BITSPERPEL: u32 = 0x0004_0000;
+pub const DM_PELSWIDTH: u32 = 0x0008_0000;
+pub const DM_PELSHEIGHT: u32 = 0x0010_0000;
+pub const DM_DISPLAYFLAGS: u32 = 0x0020_0000;
+pub const DM_DISPLAYFREQUENCY: u32 = 0x0040_0000;
+pub const DM_DISPLAYFIXEDOUTPUT: u32 = 0x2000_0000;
+
+pub const DM_FIELDS: FlagTable = &[
+    (DM_POSITION, "DM_POSITION"),
+    (DM_DISPLAYORIENTATION, "DM_DISPLAYORIENTATION"),
+    (DM_BITSPERPEL, "DM_BITSPERPEL"),
+    (DM_PELSWIDTH, "DM_PELSWIDTH"),
+    (DM_PELSHEIGHT, "DM_PELSHEIGHT"),
+    (DM_DISPLAYFLAGS, "DM_DISPLAYFLAGS"),
+    (DM_DISPLAYFREQUENCY, "DM_DISPLAYFREQUENCY"),
+    (DM_DISPLAYFIXEDOUTPUT, "DM_DISPLAYFIXEDOUTPUT"),
+];
+
+/// Interfaces and classes worth naming in a trace, in canonical registry form.
+pub const KNOWN_GUIDS: &[(&str, &str)] = &[
+    ("{00000000-0000-0000-C000-000000000046}", "IID_IUnknown"),
+    ("{D7B70EE0-4340-11CF-B063-0020AFC2CD35}", "CLSID_DirectDraw"),
+    (
+        "{3C305196-50DB-11D3-9CFE-00C04FD930C5}",
+        "CLSID_DirectDraw7",
+    ),
+    ("{6C14DB80-A733-11CE-A521-0020AF0BE560}", "IID_IDirectDraw"),
+    ("{B3A6F3E0-2B43-11CF-A2DE-00AA00B93356}", "IID_IDirectDraw2"),
+    ("{9C59509A-39BD-11D1-8C4A-00C04FD930C5}", "IID_IDirectDraw4"),
+    ("{15E65EC0-3B9C-11D2-B92F-00609797EA5B}", "IID_IDirectDraw7"),
+    (
+        "{6C14DB81-A733-11CE-A521-0020AF0BE560}",
+        "IID_IDirectDrawSurface",
+    ),
+    (
+        "{57805885-6EEC-11CF-9441-A82303C10E27}",
+        "IID_IDirectDrawSurface2",
+    ),
+    (
+        "{DA044E00-69B2-11D0-A1D5-00AA00B8DFBB}",
+        "IID_IDirectDrawSurface3",
+    ),
+    (
+        "{0B2B8630-AD35-11D0-8EA6-00609797EA5B}",
+        "IID_IDirectDrawSurface4",
+    ),
+    (
+        "{06675A80-3B9B-11D2-B92F-00609797EA5B}",
+        "IID_IDirectDrawSurface7",
+    ),
+    (
+        "{6C14DB84-A733-11CE-A521-0020AF0BE560}",
+        "IID_IDirectDrawPalette",
+    ),
+    (
+        "{6C14DB85-A733-11CE-A521-0020AF0BE560}",
+        "IID_IDirectDrawClipper",
+    ),
+    ("{3BBA0080-2421-11CF-A31A-00AA00B93356}", "IID_IDirect3D"),
+    ("{F5049E77-4861-11D2-A407-00A0C90629A8}", "IID_IDirect3D7"),
+    ("{81BDCBCA-64D4-426D-AE8D-AD0147F4275C}", "IID_IDirect3D9"),
+    ("{02177241-69FC-400C-8FF1-93A44DF6861D}", "IID_IDirect3D9Ex"),
+    (
+        "{D0223B96-BF7A-43FD-92BD-A43B0D82B9EB}",
+        "IID_IDirect3DDevice9",
+    ),
+    ("{7B7166EC-21C7-44AE-B21A-C9AE321AE369}", "IID_IDXGIFactory"),
+    (
+        "{770AAE78-F26F-4DBA-A829-253C83D1B387}",
+        "IID_IDXGIFactory1",
+    ),
+    (
+        "{50C83A1C-E072-4C48-87B0-3630FA36A6D0}",
+        "IID_IDXGIFactory2",
+    ),
+    (
+        "{310D36A0-D2E7-4C0A-AA04-6A9D23B8886A}",
+        "IID_IDXGISwapChain",
+    ),
+    ("{DB6F6DDB-AC77-4E88-8253-819DF9BBF140}", "IID_ID3D11Device"),
+    (
+        "{89521360-AA8A-11CF-BFC7-444553540000}",
+        "IID_IDirectInputA",
+    ),
+    (
+        "{89521361-AA8A-11CF-BFC7-444553540000}",
+        "IID_IDirectInputW",
+    ),
+    (
+        "{5944E662-AA8A-11CF-BFC7-444553540000}",
+        "IID_IDirectInput2A",
+    ),
+    (
+        "{5944E663-AA8A-11CF-BFC7-444553540000}",
+        "IID_IDirectInput2W",
+    ),
+    (
+        "{9A4CB684-236D-11D3-8E9D-00C04F6844AE}",
+        "IID_IDirectInput7A",
+    ),
+    (
+        "{9A4CB685-236D-11D3-8E9D-00C04F6844AE}",
+        "IID_IDirectInput7W",
+    ),
+    (
+        "{BF798030-483A-4DA2-AA99-5D64ED369700}",
+        "IID_IDirectInput8A",
+    ),
+    (
+        "{BF798031-483A-4DA2-AA99-5D64ED369700}",
+        "IID_IDirectInput8W",
+    ),
+    ("{6F1D2B60-D5A0-11CF-BFC7-444553540000}", "GUID_SysMouse"),
+    ("{6F1D2B61-D5A0-11CF-BFC7-444553540000}", "GUID_SysKeyboard"),
+    ("{6F1D2B70-D5A0-11CF-BFC7-444553540000}", "GUID_Joystick"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DevMode {
+    pub device_name: String,
+    pub fields: u32,
+    pub position: (i32, i32),
+    pub bits_per_pel: u32,
+    pub width: u32,
+    pub height: u32,
+    pub display_flags: u32,
+    pub frequency: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BltFx {
+    pub size: u32,
+    pub ddfx: u32,
+    pub rop: u32,
+    pub fill_color: u32,
+    pub dest_color_key: ColorKey,
+    pub src_color_key: ColorKey,
+}
+
+/// Offsets of the DDBLTFX fields we report; several unions in front of them hold pointers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BltFxLayout {
+    pub fill_color: usize,
+    pub dest_color_key: usize,
+    pub src_color_key: usize,
+    pub size: usize,
+}
+
+impl BltFxLayout {
+    pub const fn for_pointer_size(pointer_size: usize) -> Self {
+        // dwSize .. dwZDestConstBitDepth, then alternating depth/union pairs.
+        let z_dest_const = align_up(40, pointer_size);
+        let z_src_const = align_up(z_dest_const + pointer_size + 4, pointer_size);
+        // dwAlphaEdgeBlendBitDepth, dwAlphaEdgeBlend, dwReserved, dwAlphaDestConstBitDepth
+        let alpha_dest_const = align_up(z_src_const + pointer_size + 16, pointer_size);
+        let alpha_src_const = align_up(alpha_dest_const + pointer_size + 4, pointer_size);
+        let fill_color = alpha_src_const + pointer_size;
+        let dest_color_key = fill_color + pointer_size;
+        let src_color_key = dest_color_key + 8;
+        Self {
+            fill_color,
+            dest_color_key,
+            src_color_key,
+            size: align_up(src_color_key + 8, pointer_size),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PresentParameters {
+    pub width: u32,
+    pub height: u32,
+    pub format: u32,
+    pub back_buffer_count: u32,
+    pub multisample_type: u32,
+    pub multisample_quality: u32,
+    pub swap_effect: u32,
+    pub device_window: u64,
+    pub windowed: bool,
+    pub auto_depth_stencil: bool,
+    pub depth_stencil_format: u32,
+    pub flags: u32,
+    pub refresh_rate: u32,
+    pub presentation_interval: u32,
+}
+
+/// Offsets in D3DPRESENT_PARAMETERS from `hDeviceWindow` on, the only pointer in it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PresentParametersLayout {
+    pub device_window: usize,
+    pub windowed: usize,
+    pub size: usize,
+}
+
+impl PresentParametersLayout {
+    pub const fn for_pointer_size(pointer_size: usize) -> Self {
+        // Seven DWORDs (BackBufferWidth .. SwapEffect) precede hDeviceWindow.
+        let device_window = align_up(28, pointer_size);
+        let windowed = device_window + pointer_size;
+        Self {
+            device_window,
+            windowed,
+            // Windowed .. PresentationInterval: six DWORDs.
+            size: align_up(windowed + 24, pointer_size),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DxgiModeDesc {
+    pub width: u32,
+    pub height: u32,
+    pub refresh_numerator: u32,
+    pub refresh_denominator: u32,
+    pub format: u32,
+    pub scanline_ordering: u32,
+    pub scaling: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DxgiSwapChainDesc {
+    pub buffer: DxgiModeDesc,
+    pub sample_count: u32,
+    pub sample_quality: u32,
+    pub buffer_usage: u32,
+    pub buffer_count: u32,
+    pub output_window: u64,
+    pub windowed: bool,
+    pub swap_effect: u32,
+    pub flags: u32,
+}
+
+/// Offsets in DXGI_SWAP_CHAIN_DESC from `OutputWindow` on, the only pointer in it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DxgiSwapChainDescLayout {
+    pub output_window: usize,
+    pub windowed: usize,
+    pub size: usize,
+}
+
+impl DxgiSwapChainDescLayout {
+    pub const fn for_pointer_size(pointer_size: usize) -> Self {
+        // BufferDesc, SampleDesc, BufferUsage and BufferCount precede OutputWindow.
+        let output_window = align_up(DXGI_MODE_DESC_SIZE + 16, pointer_size);
+        let windowed = output_window + pointer_size;
+        Self {
+            output_window,
+            windowed,
+            // Windowed, SwapEffect, Flags.
+            size: align_up(windowed + 12, pointer_size),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BitmapInfoHeader {
+    pub width: i32,
+    /// Negative for top-down bitmaps.
+    pub height: i32,
+    pub bit_count: u16,
+    pub compression: u32,
+    pub colors_used: u32,
+}
+
+/// One RAWINPUTDEVICE entry passed to `RegisterRawInputDevices`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RawInputDevice {
+    pub usage_page: u16,
+    pub usage: u16,
+    pub flags: u32,
+    pub target: u64,
+}
+
+/// The parts of a PIXELFORMATDESCRIPTOR that decide which format a driver picks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelFormatDescriptor {
+    pub flags: u32,
+    pub pixel_type: u8,
+    pub color_bits: u8,
+    pub alpha_bits: u8,
+    pub accum_bits: u8,
+    pub depth_bits: u8,
+    pub stencil_bits: u8,
+    pub aux_buffers: u8,
+}
+
+pub fn parse_rect(bytes: &[u8]) -> Option<Rect> {
+    Some(Rect {
+        left: read_u32(bytes, 0)? as i32,
+        top: read_u32(bytes, 4)? as i32,
+        right: read_u32(bytes, 8)? as i32,
+        bottom: read_u32(bytes, 12)? as i32,
+    })
+}
+
+pub fn parse_devmode(bytes: &[u8]) -> Option<DevMode> {
+    Some(DevMode {
+        device_name: decode_utf16z(bytes.get(..DEVMODEW_DEVICE_NAME_BYTES)?),
+        fields: read_u32(bytes, DEVMODEW_FIELDS_OFFSET)?,
+        position: (
+            read_u32(bytes, DEVMODEW_POSITION_OFFSET)? as i32,
+            read_u32(bytes, DEVMODEW_POSITION_OFFSET + 4)? as i32,
+        ),
+        bits_per_pel: read_u32(bytes, DEVMODEW_BITS_PER_PEL_OFFSET)?,
+        width: read_u32(bytes, DEVMODEW_PELS_WIDTH_OFFSET)?,
+        height: read_u32(bytes, DEVMODEW_PELS_HEIGHT_OFFSET)?,
+        display_flags: read_u32(bytes, DEVMODEW_DISPLAY_FLAGS_OFFSET)?,
+        frequency: read_u32(bytes, DEVMODEW_DISPLAY_FREQUENCY_OFFSET)?,
+    })
+}
+
+pub fn parse_blt_fx(bytes: &[u8], pointer_size: usize) -> Option<BltFx> {
+    let layout = BltFxLayout::for_pointer_size(pointer_size);
+    let color_key = |offset: usize| -> Option<ColorKey> {
+        Some(ColorKey {
+            low: read_u32(bytes, offset)?,
+            high: read_u32(bytes, offset + 4)?,
+        })
+    };
+    Some(BltFx {
+        size: read_u32(bytes, 0)?,
+        ddfx: read_u32(bytes, 4)?,
+        rop: read_u32(bytes, 8)?,
+        fill_color: read_u32(bytes, layout.fill_color)?,
+        dest_color_key: color_key(layout.dest_color_key)?,
+        src_color_key: color_key(layout.src_color_key)?,
+    })
+}
+
+pub fn parse_present_parameters(bytes: &[u8], pointer_size: usize) -> Option<PresentParameters> {
+    let layout = PresentParametersLayout::for_pointer_size(pointer_size);
+    let device_window = match pointer_size {
+        8 => u64::from_le_bytes(
+            bytes
+                .get(layout.device_window..layout.device_window + 8)?
+                .try_into()
+                .ok()?,
+        ),
+        _ => u64::from(read_u32(bytes, layout.device_window)?),
+    };
+    let tail = |index: usize| read_u32(bytes, layout.windowed + 4 * index);
+    Some(PresentParameters {
+        width: read_u32(bytes, 0)?,
+        height: read_u32(bytes, 4)?,
+        format: read_u32(bytes, 8)?,
+        back_buffer_count: read_u32(bytes, 12)?,
+        multisample_type: read_u32(bytes, 16)?,
+        multisample_quality: read_u32(bytes, 20)?,
+        swap_effect: read_u32(bytes, 24)?,
+        device_window,
+        windowed: tail(0)? != 0,
+        auto_depth_stencil: tail(1)? != 0,
+        depth_stencil_format: tail(2)?,
+        flags: tail(3)?,
+        refresh_rate: tail(4)?,
+        presentation_interval: tail(5)?,
+    })
+}
+
+pub fn parse_dxgi_mode_desc(bytes: &[u8]) -> Option<DxgiModeDesc> {
+    Some(DxgiModeDesc {
+        width: read_u32(bytes, 0)?,
+        height: read_u32(bytes, 4)?,
+        refresh_numerator: read_u32(bytes, 8)?,
+        refresh_denominator: read_u32(bytes, 12)?,
+        format: read_u32(bytes, 16)?,
+        scanline_ordering: read_u32(bytes, 20)?,
+        scaling: read_u32(bytes, 24)?,
+    })
+}
+
+pub fn parse_dxgi_swap_chain_desc(bytes: &[u8], pointer_size: usize) -> Option<DxgiSwapChainDesc> {
+    let layout = DxgiSwapChainDescLayout::for_pointer_size(pointer_size);
+    let output_window = match pointer_size {
+        8 => u64::from_le_bytes(
+            bytes
+                .get(layout.output_window..layout.output_window + 8)?
+                .try_into()
+                .ok()?,
+        ),
+        _ => u64::from(read_u32(bytes, layout.output_window)?),
+    };
+    let tail = |index: usize| read_u32(bytes, layout.windowed + 4 * index);
+    Some(DxgiSwapChainDesc {
+        buffer: parse_dxgi_mode_desc(bytes)?,
+        sample_count: read_u32(bytes, DXGI_MODE_DESC_SIZE)?,
+        sample_quality: read_u32(bytes, DXGI_MODE_DESC_SIZE + 4)?,
+        buffer_usage: read_u32(bytes, DXGI_MODE_DESC_SIZE + 8)?,
+        buffer_count: read_u32(bytes, DXGI_MODE_DESC_SIZE + 12)?,
+        output_window,
+        windowed: tail(0)? != 0,
+        swap_effect: tail(1)?,
+        flags: tail(2)?,
+    })
+}
+
+/// Names a `D3D_FEATURE_LEVEL` array as `[11_0,10_1,10_0]`.
+pub fn describe_feature_levels(bytes: &[u8]) -> String {
+    let levels: Vec<String> = bytes
+        .chunks_exact(4)
+        .map(|chunk| {
+            let level = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            feature_level_name(level)
+        })
+        .collect();
+    format!("[{}]", levels.join(","))
+}
+
+pub fn feature_level_name(level: u32) -> String {
+    format_enum(level as i32, D3D_FEATURE_LEVEL_VALUES)
+        .map_or_else(|| format!("0x{level:X}"), str::to_owned)
+}
+
+pub fn parse_bitmap_info_header(bytes: &[u8]) -> Option<BitmapInfoHeader> {
+    let bit_count = bytes.get(14..16)?;
+    Some(BitmapInfoHeader {
+        width: read_u32(bytes, 4)? as i32,
+        height: read_u32(bytes, 8)? as i32,
+        bit_count: u16::from_le_bytes([bit_count[0], bit_count[1]]),
+        compression: read_u32(bytes, 16)?,
+        colors_used: read_u32(bytes, 32)?,
+    })
+}
+
+/// Decodes `count` RAWINPUTDEVICE entries laid out `stride` bytes apart (the caller's `cbSize`).
+pub fn parse_raw_input_devices(
+    bytes: &[u8],
+    count: usize,
+    stride: usize,
+    pointer_size: usize,
+) -> Option<Vec<RawInputDevice>> {
+    (0..count)
+        .map(|index| {
+            let entry = bytes.get(index * stride..)?;
+            let target = match pointer_size {
+                8 => u64::from_le_bytes(entry.get(8..16)?.try_into().ok()?),
+                _ => u64::from(read_u32(entry, 8)?),
+            };
+            let usage = read_u32(entry, 0)?;
+            Some(RawInputDevice {
+                usage_page: usage as u16,
+                usage: (usage >> 16) as u16,
+                flags: read_u32(entry, 4)?,
+                target,
+            })
+        })
+        .collect()
+}
+
+pub fn parse_pixel_format_descriptor(bytes: &[u8]) -> Option<PixelFormatDescriptor> {
+    let byte = |offset: usize| bytes.get(offset).copied();
+    Some(PixelFormatDescriptor {
+        flags: read_u32(bytes, 4)?,
+        pixel_type: byte(8)?,
+        color_bits: byte(9)?,
+        alpha_bits: byte(16)?,
+        accum_bits: byte(18)?,
+        depth_bits: byte(23)?,
+        stencil_bits: byte(24)?,
+        aux_buffers: byte(25)?,
+    })
+}
+
+/// Formats a GUID as `{XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX}`.
+pub fn format_guid(bytes: &[u8]) -> Option<String> {
+    let bytes: &[u8; GUID_SIZE] = bytes.get(..GUID_SIZE)?.try_into().ok()?;
+    let data1 = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let data2 = u16::from_le_bytes([bytes[4], bytes[5]]);
+    let data3 = u16::from_le_bytes([bytes[6], bytes[7]]);
+    let tail: String = bytes[10..].iter().map(|b| format!("{b:02X}")).collect();
+    Some(format!(
+        "{{{data1:08X}-{data2:04X}-{data3:04X}-{:02X}{:02X}-{tail}}}",
+        bytes[8], bytes[9]
+    ))
+}
+
+pub fn known_guid_name(guid: &str) -> Option<&'static str> {
+    KNOWN_GUIDS
+        .iter()
+        .find(|(text, _)| text.eq_ignore_ascii_case(guid))
+        .map(|(_, name)| *name)
+}
+
+/// Known GUIDs print by name, everything else in registry form.
+pub fn describe_guid(bytes: &[u8]) -> Option<String> {
+    let text = format_guid(bytes)?;
+    Some(known_guid_name(&text).map_or(text, str::to_owned))
+}
+
+/// Decodes a NUL-terminated (or buffer-terminated) little-endian UTF-16 string.
+pub fn decode_utf16z(bytes: &[u8]) -> String {
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    String::from_utf16_lossy(&units)
+}
+
+impl Rect {
+    pub fn width(&self) -> i64 {
+        i64::from(self.right) - i64::from(self.left)
+    }
+
+    pub fn height(&self) -> i64 {
+        i64::from(self.bottom) - i64::from(self.top)
+    }
+}
+
+impl fmt::Display for Rect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "({},{})-({},{}) {}x{}",
+            self.left,
+            self.top,
+            self.right,
+            self.bottom,
+            self.width(),
+            self.height()
+        )
+    }
+}
+
+/// Prints only the fields `dmFields` marks as valid.
+impl fmt::Display for DevMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fields={}", format_flags(self.fields, DM_FIELDS))?;
+        if !self.device_name.is_empty() {
+            write!(f, " name=\"{}\"", self.device_name)?;
+        }
+        if self.fields & DM_PELSWIDTH != 0 {
+            write!(f, " width={}", self.width)?;
+        }
+        if self.fields & DM_PELSHEIGHT != 0 {
+            write!(f, " height={}", self.height)?;
+        }
+        if self.fields & DM_BITSPERPEL != 0 {
+            write!(f, " bpp={}", self.bits_per_pel)?;
+        }
+        if self.fields & DM_DISPLAYFREQUENCY != 0 {
+            write!(f, " freq={}", self.frequency)?;
+        }
+        if self.fields & DM_DISPLAYFLAGS != 0 {
+            write!(f, " display_flags=0x{:08X}", self.display_flags)?;
+        }
+        if self.fields & DM_POSITION != 0 {
+            write!(f, " pos=({},{})", self.position.0, self.position.1)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for BltFx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "size={} ddfx=0x{:08X} rop=0x{:08X} fill_color=0x{:08X} ck_dest={} ck_src={}",
+            self.size,
+            self.ddfx,
+            self.rop,
+            self.fill_color,
+            self.dest_color_key,
+            self.src_color_key
+        )
+    }
+}
+
+impl fmt::Display for PresentParameters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "width={} height={} format={} back_buffers={} swap={} hwnd=0x{:016X} windowed={}",
+            self.width,
+            self.height,
+            enum_name(self.format, D3DFMT_VALUES),
+            self.back_buffer_count,
+            enum_name(self.swap_effect, D3DSWAPEFFECT_VALUES),
+            self.device_window,
+            if self.windowed { "TRUE" } else { "FALSE" }
+        )?;
+        if self.multisample_type != 0 {
+            write!(
+                f,
+                " multisample={} quality={}",
+                self.multisample_type, self.multisample_quality
+            )?;
+        }
+        if self.auto_depth_stencil {
+            write!(
+                f,
+                " depth_stencil={}",
+                enum_name(self.depth_stencil_format, D3DFMT_VALUES)
+            )?;
+        }
+        if self.flags != 0 {
+            write!(
+                f,
+                " pp_flags={}",
+                format_flags(self.flags, D3DPRESENTFLAG_FLAGS)
+            )?;
+        }
+        // The refresh rate must be zero when windowed.
+        if !self.windowed {
+            write!(f, " refresh={}", self.refresh_rate)?;
+        }
+        write!(
+            f,
+            " interval={}",
+            enum_name(self.presentation_interval, D3DPRESENT_INTERVAL_VALUES)
+        )
+    }
+}
+
+impl fmt::Display for DxgiModeDesc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "width={} height={} format={}",
+            self.width,
+            self.height,
+            enum_name(self.format, DXGI_FORMAT_VALUES)
+        )?;
+        if self.refresh_numerator != 0 {
+            write!(
+                f,
+                " refresh={}/{}",
+                self.refresh_numerator, self.refresh_denominator
+            )?;
+        }
+        if self.scaling != 0 {
+            write!(
+                f,
+                " scaling={}",
+                enum_name(self.scaling, DXGI_MODE_SCALING_VALUES)
+            )?;
+        }
+        if self.scanline_ordering != 0 {
+            write!(f, " scanline={}", self.scanline_ordering)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DxgiSwapChainDesc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} buffers={} usage={} swap={} hwnd=0x{:016X} windowed={}",
+            self.buffer,
+            self.buffer_count,
+            format_flags(self.buffer_usage, DXGI_USAGE_FLAGS),
+            enum_name(self.swap_effect, DXGI_SWAP_EFFECT_VALUES),
+            self.output_window,
+            if self.windowed { "TRUE" } else { "FALSE" }
+        )?;
+        if self.sample_count > 1 {
+            write!(
+                f,
+                " samples={} quality={}",
+                self.sample_count, self.sample_quality
+            )?;
+        }
+        if self.flags != 0 {
+            write!(
+                f,
+                " sc_flags={}",
+                format_flags(self.flags, DXGI_SWAP_CHAIN_FLAGS)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for BitmapInfoHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bmi={}x{} bpp={} compression={}",
+            self.width,
+            self.height.unsigned_abs(),
+            self.bit_count,
+            enum_name(self.compression, BI_COMPRESSION_VALUES)
+        )?;
+        if self.height < 0 {
+            f.write_str(" top_down")?;
+        }
+        if self.colors_used != 0 {
+            write!(f, " colors={}", self.colors_used)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RawInputDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Generic desktop controls (page 1) cover what games register for.
+        let device = match (self.usage_page, self.usage) {
+            (1, 2) => "mouse",
+            (1, 4) => "joystick",
+            (1, 5) => "gamepad",
+            (1, 6) => "keyboard",
+            _ => "other",
+        };
+        write!(
+            f,
+            "{device}(page=0x{:02X} usage=0x{:02X} flags={} target=0x{:016X})",
+            self.usage_page,
+            self.usage,
+            format_flags(self.flags, RIDEV_FLAGS),
+            self.target
+        )
+    }
+}
+
+impl fmt::Display for PixelFormatDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pfd_flags={} type={} color={} alpha={} depth={} stencil={}",
+            format_flags(self.flags, PFD_FLAGS),
+            enum_name(u32::from(self.pixel_type), PFD_PIXEL_TYPE_VALUES),
+            self.color_bits,
+            self.alpha_bits,
+            self.depth_bits,
+            self.stencil_bits
+        )?;
+        if self.accum_bits != 0 {
+            write!(f, " accum={}", self.accum_bits)?;
+        }
+        if self.aux_buffers != 0 {
+            write!(f, " aux={}", self.aux_buffers)?;
+        }
+        Ok(())
+    }
+}
+
+fn enum_name(value: u32, table: EnumTable) -> String {
+    format_enum(value as i32, table).map_or_else(|| value.to_string(), str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::test_bytes::{StructBytes, X64, X86};
+
+    #[test]
+    fn parses_rect() {
+        let bytes = StructBytes::new(RECT_SIZE, X86)
+            .i32(0, -10)
+            .i32(4, 20)
+            .i32(8, 630)
+            .i32(12, 500)
+            .into_bytes();
+        let rect = parse_rect(&bytes).unwrap();
+        assert_eq!(rect.to_string(), "(-10,20)-(630,500) 640x480");
+        assert_eq!(parse_rect(&bytes[..12]), None);
+    }
+
+    #[test]
+    fn parses_devmode() {
+        let bytes = StructBytes::new(DEVMODEW_DISPLAY_SIZE, X86)
+            .utf16(0, "\\\\.\\DISPLAY1")
+            .u32(72, DM_BITSPERPEL | DM_PELSWIDTH | DM_PELSHEIGHT)
+            .u32(168, 16)
+            .u32(172, 640)
+            .u32(176, 480)
+            .u32(184, 60)
+            .into_bytes();
+        let mode = parse_devmode(&bytes).unwrap();
+        assert_eq!(mode.device_name, "\\\\.\\DISPLAY1");
+        assert_eq!((mode.width, mode.height, mode.bits_per_pel), (640, 480, 16));
+        assert_eq!(mode.frequency, 60);
+    }
+
+    #[test]
+    fn blt_fx_layout_matches_ddraw_headers() {
+        let x86 = BltFxLayout::for_pointer_size(X86);
+        assert_eq!(
+            (
+                x86.fill_color,
+                x86.dest_color_key,
+                x86.src_color_key,
+                x86.size
+            ),
+            (80, 84, 92, 100)
+        );
+        let x64 = BltFxLayout::for_pointer_size(X64);
+        assert_eq!(
+            (
+                x64.fill_color,
+                x64.dest_color_key,
+                x64.src_color_key,
+                x64.size
+            ),
+            (104, 112, 120, 128)
+        );
+    }
+
+    #[test]
+    fn parses_blt_fx_for_both_pointer_sizes() {
+        for (pointer_size, size, fill_color, src_color_key) in
+            [(X86, 100, 80, 92), (X64, 128, 104, 120)]
+        {
+            let bytes = StructBytes::new(size, pointer_size)
+                .u32(0, size as u32)
+                .u32(8, 0x00CC_0020)
+                .u32(fill_color, 0xF800)
+                .u32(src_color_key, 0xF81F)
+                .u32(src_color_key + 4, 0xF81F)
+                .into_bytes();
+            let fx = parse_blt_fx(&bytes, pointer_size).unwrap();
+            assert_eq!(fx.size, size as u32);
+            assert_eq!(fx.rop, 0x00CC_0020);
+            assert_eq!(fx.fill_color, 0xF800);
+            assert_eq!(fx.dest_color_key, ColorKey { low: 0, high: 0 });
+            assert_eq!(
+                fx.src_color_key,
+                ColorKey {
+                    low: 0xF81F,
+                    high: 0xF81F
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn present_parameters_layout_matches_d3d9_headers() {
+        assert_eq!(
+            PresentParametersLayout::for_pointer_size(X86),
+            PresentParametersLayout {
+                device_window: 28,
+                windowed: 32,
+                size: 56
+            }
+        );
+        assert_eq!(
+            PresentParametersLayout::for_pointer_size(X64),
+            PresentParametersLayout {
+                device_window: 32,
+                windowed: 40,
+                size: 64
+            }
+        );
+    }
+
+    #[test]
+    fn parses_present_parameters_for_both_pointer_sizes() {
+        for (pointer_size, size, device_window, windowed) in [(X86, 56, 28, 32), (X64, 64, 32, 40)]
+        {
+            // D3DFMT_X8R8G8B8, one back buffer, D3DSWAPEFFECT_DISCARD.
+            let bytes = StructBytes::new(size, pointer_size)
+                .u32(0, 800)
+                .u32(4, 600)
+                .u32(8, 22)
+                .u32(12, 1)
+                .u32(24, 1)
+                .pointer(device_window, 0x0001_02A4)
+                .u32(windowed, 1)
+                .u32(windowed + 16, 60)
+                .u32(windowed + 20, 0x8000_0000)
+                .into_bytes();
+            let params = parse_present_parameters(&bytes, pointer_size).unwrap();
+            assert_eq!((params.width, params.height, params.format), (800, 600, 22));
+            assert_eq!(params.back_buffer_count, 1);
+            assert_eq!(params.swap_effect, 1);
+            assert_eq!(params.device_window, 0x0001_02A4);
+            assert!(params.windowed);
+            assert!(!params.auto_depth_stencil);
+            assert_eq!(params.refresh_rate, 60);
+            assert_eq!(params.presentation_interval, 0x8000_0000);
+        }
+    }
+
+    #[test]
+    fn dxgi_swap_chain_desc_layout_matches_dxgi_headers() {
+        assert_eq!(
+            DxgiSwapChainDescLayout::for_pointer_size(X86),
+            DxgiSwapChainDescLayout {
+                output_window: 44,
+                windowed: 48,
+                size: 60
+            }
+        );
+        assert_eq!(
+            DxgiSwapChainDescLayout::for_pointer_size(X64),
+            DxgiSwapChainDescLayout {
+                output_window: 48,
+                windowed: 56,
+                size: 72
+            }
+        );
+    }
+
+    #[test]
+    fn parses_dxgi_swap_chain_desc_for_both_pointer_sizes() {
+        for (pointer_size, size, output_window, windowed) in [(X86, 60, 44, 48), (X64, 72, 48, 56)]
+        {
+            // DXGI_FORMAT_R8G8B8A8_UNORM, one sample, two buffers, DXGI_SWAP_EFFECT_FLIP_DISCARD
+            // and DXGI_SWAP_CHAIN_FLAG_ALLOW_MODE_SWITCH.
+            let bytes = StructBytes::new(size, pointer_size)
+                .u32(0, 1920)
+                .u32(4, 1080)
+                .u32(8, 60)
+                .u32(12, 1)
+                .u32(16, 28)
+                .u32(28, 1)
+                .u32(40, 2)
+                .pointer(output_window, 0x0003_0BF2)
+                .u32(windowed, 1)
+                .u32(windowed + 4, 4)
+                .u32(windowed + 8, 2)
+                .into_bytes();
+            let desc = parse_dxgi_swap_chain_desc(&bytes, pointer_size).unwrap();
+            assert_eq!((desc.buffer.width, desc.buffer.height), (1920, 1080));
+            assert_eq!(desc.buffer.format, 28);
+            assert_eq!((desc.sample_count, desc.buffer_count), (1, 2));
+            assert_eq!(desc.output_window, 0x0003_0BF2);
+            assert!(desc.windowed);
+            assert_eq!((desc.swap_effect, desc.flags), (4, 2));
+        }
+    }
+
+    #[test]
+    fn formats_guids() {
+        let direct_draw = [
+            0xE0, 0x0E, 0xB7, 0xD7, 0x40, 0x43, 0xCF, 0x11, 0xB0, 0x63, 0x00, 0x20, 0xAF, 0xC2,
+            0xCD, 0x35,
+        ];
+        assert_eq!(
+            format_guid(&direct_draw).as_deref(),
+            Some("{D7B70EE0-4340-11CF-B063-0020AFC2CD35}")
+        );
+        assert_eq!(
+            describe_guid(&direct_draw).as_deref(),
+            Some("CLSID_DirectDraw")
+        );
+
+        let unknown = [
+            0x78, 0x56, 0x34, 0x12, 0xBC, 0x9A, 0xF0, 0xDE, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66,
+            0x77, 0x88,
+        ];
+        assert_eq!(
+            describe_guid(&unknown).as_deref(),
+            Some("{12345678-9ABC-DEF0-1122-334455667788}")
+        );
+        assert_eq!(format_guid(&unknown[..15]), None);
+    }
+}
@@ -0,0 +1,495 @@
+//! DDSURFACEDESC / DDSURFACEDESC2 decoding from raw bytes.
+//!
+//! Both structures share their first fields; DDSURFACEDESC2 widens `ddsCaps` to DDSCAPS2 and
+//! appends `dwTextureStage`. `lpSurface` sits in the middle, so every offset after it depends on
+//! the pointer size of the process the bytes came from.
+
+use super::flags::{DDSCAPS_FLAGS, FlagTable, format_flags};
+use std::fmt;
+
+pub const DDSD_CAPS: u32 = 0x0000_0001;
+pub const DDSD_HEIGHT: u32 = 0x0000_0002;
+pub const DDSD_WIDTH: u32 = 0x0000_0004;
+pub const DDSD_PITCH: u32 = 0x0000_0008;
+pub const DDSD_BACKBUFFERCOUNT: u32 = 0x0000_0020;
+pub const DDSD_ZBUFFERBITDEPTH: u32 = 0x0000_0040;
+pub const DDSD_ALPHABITDEPTH: u32 = 0x0000_0080;
+pub const DDSD_LPSURFACE: u32 = 0x0000_0800;
+pub const DDSD_PIXELFORMAT: u32 = 0x0000_1000;
+pub const DDSD_CKDESTOVERLAY: u32 = 0x0000_2000;
+pub const DDSD_CKDESTBLT: u32 = 0x0000_4000;
+pub const DDSD_CKSRCOVERLAY: u32 = 0x0000_8000;
+pub const DDSD_CKSRCBLT: u32 = 0x0001_0000;
+pub const DDSD_MIPMAPCOUNT: u32 = 0x0002_0000;
+pub const DDSD_REFRESHRATE: u32 = 0x0004_0000;
+pub const DDSD_LINEARSIZE: u32 = 0x0008_0000;
+pub const DDSD_TEXTURESTAGE: u32 = 0x0010_0000;
+pub const DDSD_FVF: u32 = 0x0020_0000;
+pub const DDSD_SRCVBHANDLE: u32 = 0x0040_0000;
+pub const DDSD_DEPTH: u32 = 0x0080_0000;
+
+pub const DDSD_FLAGS: FlagTable = &[
+    (DDSD_CAPS, "DDSD_CAPS"),
+    (DDSD_HEIGHT, "DDSD_HEIGHT"),
+    (DDSD_WIDTH, "DDSD_WIDTH"),
+    (DDSD_PITCH, "DDSD_PITCH"),
+    (DDSD_BACKBUFFERCOUNT, "DDSD_BACKBUFFERCOUNT"),
+    (DDSD_ZBUFFERBITDEPTH, "DDSD_ZBUFFERBITDEPTH"),
+    (DDSD_ALPHABITDEPTH, "DDSD_ALPHABITDEPTH"),
+    (DDSD_LPSURFACE, "DDSD_LPSURFACE"),
+    (DDSD_PIXELFORMAT, "DDSD_PIXELFORMAT"),
+    (DDSD_CKDESTOVERLAY, "DDSD_CKDESTOVERLAY"),
+    (DDSD_CKDESTBLT, "DDSD_CKDESTBLT"),
+    (DDSD_CKSRCOVERLAY, "DDSD_CKSRCOVERLAY"),
+    (DDSD_CKSRCBLT, "DDSD_CKSRCBLT"),
+    (DDSD_MIPMAPCOUNT, "DDSD_MIPMAPCOUNT"),
+    (DDSD_REFRESHRATE, "DDSD_REFRESHRATE"),
+    (DDSD_LINEARSIZE, "DDSD_LINEARSIZE"),
+    (DDSD_TEXTURESTAGE, "DDSD_TEXTURESTAGE"),
+    (DDSD_FVF, "DDSD_FVF"),
+    (DDSD_SRCVBHANDLE, "DDSD_SRCVBHANDLE"),
+    (DDSD_DEPTH, "DDSD_DEPTH"),
+];
+
+pub const DDSCAPS2_FLAGS: FlagTable = &[
+    (0x0000_0004, "DDSCAPS2_HINTDYNAMIC"),
+    (0x0000_0008, "DDSCAPS2_HINTSTATIC"),
+    (0x0000_0010, "DDSCAPS2_TEXTUREMANAGE"),
+    (0x0000_0080, "DDSCAPS2_OPAQUE"),
+    (0x0000_0100, "DDSCAPS2_HINTANTIALIASING"),
+    (0x0000_0200, "DDSCAPS2_CUBEMAP"),
+    (0x0000_0400, "DDSCAPS2_CUBEMAP_POSITIVEX"),
+    (0x0000_0800, "DDSCAPS2_CUBEMAP_NEGATIVEX"),
+    (0x0000_1000, "DDSCAPS2_CUBEMAP_POSITIVEY"),
+    (0x0000_2000, "DDSCAPS2_CUBEMAP_NEGATIVEY"),
+    (0x0000_4000, "DDSCAPS2_CUBEMAP_POSITIVEZ"),
+    (0x0000_8000, "DDSCAPS2_CUBEMAP_NEGATIVEZ"),
+    (0x0001_0000, "DDSCAPS2_MIPMAPSUBLEVEL"),
+    (0x0002_0000, "DDSCAPS2_D3DTEXTUREMANAGE"),
+    (0x0004_0000, "DDSCAPS2_DONOTPERSIST"),
+    (0x0008_0000, "DDSCAPS2_STEREOSURFACELEFT"),
+    (0x0020_0000, "DDSCAPS2_VOLUME"),
+    (0x0040_0000, "DDSCAPS2_NOTUSERLOCKABLE"),
+    (0x0080_0000, "DDSCAPS2_POINTS"),
+    (0x0100_0000, "DDSCAPS2_RTPATCHES"),
+    (0x0200_0000, "DDSCAPS2_NPATCHES"),
+    (0x1000_0000, "DDSCAPS2_DISCARDBACKBUFFER"),
+    (0x2000_0000, "DDSCAPS2_ENABLEALPHACHANNEL"),
+    (0x4000_0000, "DDSCAPS2_EXTENDEDFORMATPRIMARY"),
+    (0x8000_0000, "DDSCAPS2_ADDITIONALPRIMARY"),
+];
+
+pub const DDPF_ALPHAPIXELS: u32 = 0x0000_0001;
+pub const DDPF_FOURCC: u32 = 0x0000_0004;
+pub const DDPF_PALETTEINDEXED4: u32 = 0x0000_0008;
+pub const DDPF_PALETTEINDEXED8: u32 = 0x0000_0020;
+pub const DDPF_RGB: u32 = 0x0000_0040;
+
+pub const DDPF_FLAGS: FlagTable = &[
+    (DDPF_ALPHAPIXELS, "DDPF_ALPHAPIXELS"),
+    (0x0000_0002, "DDPF_ALPHA"),
+    (DDPF_FOURCC, "DDPF_FOURCC"),
+    (DDPF_PALETTEINDEXED4, "DDPF_PALETTEINDEXED4"),
+    (0x0000_0010, "DDPF_PALETTEINDEXEDTO8"),
+    (DDPF_PALETTEINDEXED8, "DDPF_PALETTEINDEXED8"),
+    (DDPF_RGB, "DDPF_RGB"),
+    (0x0000_0080, "DDPF_COMPRESSED"),
+    (0x0000_0100, "DDPF_RGBTOYUV"),
+    (0x0000_0200, "DDPF_YUV"),
+    (0x0000_0400, "DDPF_ZBUFFER"),
+    (0x0000_0800, "DDPF_PALETTEINDEXED1"),
+    (0x0000_1000, "DDPF_PALETTEINDEXED2"),
+    (0x0000_2000, "DDPF_ZPIXELS"),
+    (0x0000_4000, "DDPF_STENCILBUFFER"),
+    (0x0000_8000, "DDPF_ALPHAPREMULT"),
+    (0x0002_0000, "DDPF_LUMINANCE"),
+    (0x0004_0000, "DDPF_BUMPLUMINANCE"),
+    (0x0008_0000, "DDPF_BUMPDUDV"),
+];
+
+/// Size of DDPIXELFORMAT: eight DWORDs.
+pub const PIXEL_FORMAT_SIZE: usize = 32;
+const COLOR_KEY_SIZE: usize = 8;
+/// dwSize through dwReserved: the nine DWORDs in front of `lpSurface`.
+const HEADER_SIZE: usize = 36;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SurfaceDescLayout {
+    /// `lpSurface`, filled in by `Lock`.
+    pub surface: usize,
+    pub color_keys: usize,
+    pub pixel_format: usize,
+    pub caps: usize,
+    /// `sizeof(DDSURFACEDESC)`.
+    pub desc_size: usize,
+    /// `sizeof(DDSURFACEDESC2)`.
+    pub desc2_size: usize,
+}
+
+impl SurfaceDescLayout {
+    pub const fn for_pointer_size(pointer_size: usize) -> Self {
+        let surface = align_up(HEADER_SIZE, pointer_size);
+        let color_keys = surface + pointer_size;
+        let pixel_format = color_keys + 4 * COLOR_KEY_SIZE;
+        let caps = pixel_format + PIXEL_FORMAT_SIZE;
+        Self {
+            surface,
+            color_keys,
+            pixel_format,
+            caps,
+            desc_size: align_up(caps + 4, pointer_size),
+            desc2_size: align_up(caps + 16 + 4, pointer_size),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorKey {
+    pub low: u32,
+    pub high: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelFormat {
+    pub size: u32,
+    pub flags: u32,
+    pub fourcc: u32,
+    pub bit_count: u32,
+    pub red_mask: u32,
+    pub green_mask: u32,
+    pub blue_mask: u32,
+    pub alpha_mask: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SurfaceDesc {
+    /// True for DDSURFACEDESC2 (IDirectDraw4/7 and their surfaces).
+    pub is_desc2: bool,
+    pub size: u32,
+    pub flags: u32,
+    pub height: u32,
+    pub width: u32,
+    /// `lPitch`, or `dwLinearSize` when `DDSD_LINEARSIZE` is set.
+    pub pitch: u32,
+    /// `dwBackBufferCount`, or `dwDepth` when `DDSD_DEPTH` is set.
+    pub back_buffer_count: u32,
+    /// `dwRefreshRate`, `dwMipMapCount` or `dwZBufferBitDepth`, depending on `flags`.
+    pub refresh_rate: u32,
+    pub alpha_bit_depth: u32,
+    /// Destination overlay, destination blt, source overlay and source blt keys.
+    pub color_keys: [ColorKey; 4],
+    pub pixel_format: PixelFormat,
+    /// `dwCaps` plus, for DDSURFACEDESC2, `dwCaps2`..`dwCaps4`.
+    pub caps: [u32; 4],
+    pub texture_stage: u32,
+}
+
+pub fn parse_pixel_format(bytes: &[u8]) -> Option<PixelFormat> {
+    if bytes.len() < PIXEL_FORMAT_SIZE {
+        return None;
+    }
+
+    Some(PixelFormat {
+        size: read_u32(bytes, 0)?,
+        flags: read_u32(bytes, 4)?,
+        fourcc: read_u32(bytes, 8)?,
+        bit_count: read_u32(bytes, 12)?,
+        red_mask: read_u32(bytes, 16)?,
+        green_mask: read_u32(bytes, 20)?,
+        blue_mask: read_u32(bytes, 24)?,
+        alpha_mask: read_u32(bytes, 28)?,
+    })
+}
+
+/// Parses a DDSURFACEDESC or DDSURFACEDESC2, picked by `dwSize`, as laid out by a process with
+/// `pointer_size`-byte pointers. Returns `None` when `dwSize` matches neither structure or
+/// `bytes` is too short for it.
+pub fn parse_surface_desc(bytes: &[u8], pointer_size: usize) -> Option<SurfaceDesc> {
+    let layout = SurfaceDescLayout::for_pointer_size(pointer_size);
+    let size = read_u32(bytes, 0)?;
+    let is_desc2 = if size as usize == layout.desc2_size {
+        true
+    } else if size as usize == layout.desc_size {
+        false
+    } else {
+        return None;
+    };
+    if bytes.len() < size as usize {
+        return None;
+    }
+
+    let color_key = |index: usize| -> Option<ColorKey> {
+        let offset = layout.color_keys + index * COLOR_KEY_SIZE;
+        Some(ColorKey {
+            low: read_u32(bytes, offset)?,
+            high: read_u32(bytes, offset + 4)?,
+        })
+    };
+    let mut caps = [0u32; 4];
+    let caps_count = if is_desc2 { 4 } else { 1 };
+    for (i, cap) in caps.iter_mut().take(caps_count).enumerate() {
+        *cap = read_u32(bytes, layout.caps + i * 4)?;
+    }
+
+    Some(SurfaceDesc {
+        is_desc2,
+        size,
+        flags: read_u32(bytes, 4)?,
+        height: read_u32(bytes, 8)?,
+        width: read_u32(bytes, 12)?,
+        pitch: read_u32(bytes, 16)?,
+        back_buffer_count: read_u32(bytes, 20)?,
+        refresh_rate: read_u32(bytes, 24)?,
+        alpha_bit_depth: read_u32(bytes, 28)?,
+        color_keys: [color_key(0)?, color_key(1)?, color_key(2)?, color_key(3)?],
+        pixel_format: parse_pixel_format(bytes.get(layout.pixel_format..)?)?,
+        caps,
+        texture_stage: if is_desc2 {
+            read_u32(bytes, layout.caps + 16)?
+        } else {
+            0
+        },
+    })
+}
+
+impl fmt::Display for PixelFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "flags={}", format_flags(self.flags, DDPF_FLAGS))?;
+        if self.flags & DDPF_FOURCC != 0 {
+            write!(f, " fourcc={}", format_fourcc(self.fourcc))?;
+        }
+        write!(f, " bpp={}", self.bit_count)?;
+        if self.flags & DDPF_RGB != 0 {
+            write!(
+                f,
+                " r=0x{:08X} g=0x{:08X} b=0x{:08X}",
+                self.red_mask, self.green_mask, self.blue_mask
+            )?;
+        }
+        if self.flags & DDPF_ALPHAPIXELS != 0 {
+            write!(f, " a=0x{:08X}", self.alpha_mask)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ColorKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:08X}-0x{:08X}", self.low, self.high)
+    }
+}
+
+/// Prints only the fields `dwFlags` marks as valid.
+impl fmt::Display for SurfaceDesc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let has = |flag: u32| self.flags & flag != 0;
+
+        write!(
+            f,
+            "{} size={} flags={}",
+            if self.is_desc2 {
+                "DDSURFACEDESC2"
+            } else {
+                "DDSURFACEDESC"
+            },
+            self.size,
+            format_flags(self.flags, DDSD_FLAGS)
+        )?;
+        if has(DDSD_WIDTH) {
+            write!(f, " width={}", self.width)?;
+        }
+        if has(DDSD_HEIGHT) {
+            write!(f, " height={}", self.height)?;
+        }
+        if has(DDSD_PITCH) {
+            write!(f, " pitch={}", self.pitch as i32)?;
+        }
+        if has(DDSD_LINEARSIZE) {
+            write!(f, " linear_size={}", self.pitch)?;
+        }
+        if has(DDSD_BACKBUFFERCOUNT) {
+            write!(f, " back_buffers={}", self.back_buffer_count)?;
+        }
+        if self.is_desc2 && has(DDSD_DEPTH) {
+            write!(f, " depth={}", self.back_buffer_count)?;
+        }
+        if has(DDSD_REFRESHRATE) {
+            write!(f, " refresh={}", self.refresh_rate)?;
+        }
+        if has(DDSD_MIPMAPCOUNT) {
+            write!(f, " mipmaps={}", self.refresh_rate)?;
+        }
+        if !self.is_desc2 && has(DDSD_ZBUFFERBITDEPTH) {
+            write!(f, " zbuffer_bits={}", self.refresh_rate)?;
+        }
+        if has(DDSD_ALPHABITDEPTH) {
+            write!(f, " alpha_bits={}", self.alpha_bit_depth)?;
+        }
+        if has(DDSD_CAPS) {
+            write!(f, " caps={}", format_flags(self.caps[0], DDSCAPS_FLAGS))?;
+            if self.is_desc2 && self.caps[1] != 0 {
+                write!(f, " caps2={}", format_flags(self.caps[1], DDSCAPS2_FLAGS))?;
+            }
+        }
+        if has(DDSD_PIXELFORMAT) {
+            write!(f, " pf=({})", self.pixel_format)?;
+        }
+        let color_keys = [
+            (DDSD_CKDESTOVERLAY, "ck_dest_overlay"),
+            (DDSD_CKDESTBLT, "ck_dest_blt"),
+            (DDSD_CKSRCOVERLAY, "ck_src_overlay"),
+            (DDSD_CKSRCBLT, "ck_src_blt"),
+        ];
+        for (key, (flag, name)) in self.color_keys.iter().zip(color_keys) {
+            if has(flag) {
+                write!(f, " {name}={key}")?;
+            }
+        }
+        if self.is_desc2 && has(DDSD_TEXTURESTAGE) {
+            write!(f, " texture_stage={}", self.texture_stage)?;
+        }
+        Ok(())
+    }
+}
+
+/// Renders a FourCC as its four characters when printable, otherwise as hex.
+pub fn format_fourcc(fourcc: u32) -> String {
+    let bytes = fourcc.to_le_bytes();
+    if bytes.iter().all(|b| b.is_ascii_graphic() || *b == b' ') {
+        format!("'{}'", String::from_utf8_lossy(&bytes))
+    } else {
+        format!("0x{fourcc:08X}")
+    }
+}
+
+pub(super) fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let raw = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(raw.try_into().ok()?))
+}
+
+pub(super) const fn align_up(value: usize, align: usize) -> usize {
+    value.div_ceil(align) * align
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::test_bytes::{StructBytes, X64, X86};
+
+    /// A 640x480 RGB565 primary with a source blt color key, at the offsets the ddraw headers
+    /// give for one pointer size.
+    fn canned_desc(
+        size: usize,
+        pointer_size: usize,
+        color_keys: usize,
+        pixel_format: usize,
+        caps: usize,
+    ) -> StructBytes {
+        StructBytes::new(size, pointer_size)
+            .u32(0, size as u32)
+            .u32(
+                4,
+                DDSD_CAPS
+                    | DDSD_WIDTH
+                    | DDSD_HEIGHT
+                    | DDSD_PITCH
+                    | DDSD_PIXELFORMAT
+                    | DDSD_CKSRCBLT,
+            )
+            .u32(8, 480)
+            .u32(12, 640)
+            .u32(16, 1280)
+            // Source blt key: the fourth color key.
+            .u32(color_keys + 3 * 8, 0xF81F)
+            .u32(color_keys + 3 * 8 + 4, 0xF81F)
+            .u32(pixel_format, PIXEL_FORMAT_SIZE as u32)
+            .u32(pixel_format + 4, DDPF_RGB)
+            .u32(pixel_format + 12, 16)
+            .u32(pixel_format + 16, 0xF800)
+            .u32(pixel_format + 20, 0x07E0)
+            .u32(pixel_format + 24, 0x001F)
+            // DDSCAPS_PRIMARYSURFACE | DDSCAPS_VIDEOMEMORY.
+            .u32(caps, 0x0000_4200)
+    }
+
+    fn assert_rgb565_primary(desc: &SurfaceDesc) {
+        assert_eq!(desc.width, 640);
+        assert_eq!(desc.height, 480);
+        assert_eq!(desc.pitch, 1280);
+        assert_eq!(desc.caps[0], 0x0000_4200);
+        assert_eq!(
+            (desc.color_keys[3].low, desc.color_keys[3].high),
+            (0xF81F, 0xF81F)
+        );
+        assert_eq!(desc.pixel_format.flags, DDPF_RGB);
+        assert_eq!(desc.pixel_format.bit_count, 16);
+        assert_eq!(desc.pixel_format.red_mask, 0xF800);
+        assert_eq!(desc.pixel_format.green_mask, 0x07E0);
+        assert_eq!(desc.pixel_format.blue_mask, 0x001F);
+    }
+
+    #[test]
+    fn layout_sizes_match_ddraw_headers() {
+        let x86 = SurfaceDescLayout::for_pointer_size(X86);
+        assert_eq!((x86.color_keys, x86.pixel_format, x86.caps), (40, 72, 104));
+        assert_eq!((x86.desc_size, x86.desc2_size), (108, 124));
+
+        let x64 = SurfaceDescLayout::for_pointer_size(X64);
+        assert_eq!((x64.color_keys, x64.pixel_format, x64.caps), (48, 80, 112));
+        assert_eq!((x64.desc_size, x64.desc2_size), (120, 136));
+    }
+
+    #[test]
+    fn parses_x86_desc() {
+        let bytes = canned_desc(108, X86, 40, 72, 104).into_bytes();
+        let desc = parse_surface_desc(&bytes, X86).unwrap();
+        assert!(!desc.is_desc2);
+        assert_eq!(desc.size, 108);
+        assert_rgb565_primary(&desc);
+    }
+
+    #[test]
+    fn parses_x86_desc2() {
+        let bytes = canned_desc(124, X86, 40, 72, 104)
+            .u32(108, 0x0000_0010)
+            .u32(120, 2)
+            .into_bytes();
+        let desc = parse_surface_desc(&bytes, X86).unwrap();
+        assert!(desc.is_desc2);
+        assert_eq!(desc.size, 124);
+        assert_rgb565_primary(&desc);
+        assert_eq!(desc.caps[1], 0x0000_0010);
+        assert_eq!(desc.texture_stage, 2);
+    }
+
+    #[test]
+    fn parses_x64_desc() {
+        let bytes = canned_desc(120, X64, 48, 80, 112).into_bytes();
+        let desc = parse_surface_desc(&bytes, X64).unwrap();
+        assert!(!desc.is_desc2);
+        assert_eq!(desc.size, 120);
+        assert_rgb565_primary(&desc);
+    }
+
+    #[test]
+    fn parses_x64_desc2() {
+        let bytes = canned_desc(136, X64, 48, 80, 112)
+            .u32(116, 0x0000_0010)
+            .u32(128, 2)
+            .into_bytes();
+        let desc = parse_surface_desc(&bytes, X64).unwrap();
+        assert!(desc.is_desc2);
+        assert_eq!(desc.size, 136);
+        assert_rgb565_primary(&desc);
+        assert_eq!(desc.caps[1], 0x0000_0010);
+        assert_eq!(desc.texture_stage, 2);
+    }
+
+    #[test]
+    fn rejects_other_pointer_size_and_short_buffers() {
+        // An x86 DDSURFACEDESC2 is 124 bytes, which is neither x64 size.
+        let bytes = canned_desc(124, X86, 40, 72, 104).into_bytes();
+        assert_eq!(parse_surface_desc(&bytes, X64), None);
+        assert_eq!(parse_surface_desc(&bytes[..100], X86), None);
+    }
+}
@@ -0,0 +1,48 @@
+//! Structure bytes for the decoding tests, written field by field at explicit offsets so the
+//! tests pin layouts down independently of the layout code they check.
+
+pub const X86: usize = 4;
+pub const X64: usize = 8;
+
+/// A zeroed little-endian structure as laid out by a process with `pointer_size`-byte pointers.
+pub struct StructBytes {
+    bytes: Vec<u8>,
+    pointer_size: usize,
+}
+
+impl StructBytes {
+    pub fn new(size: usize, pointer_size: usize) -> Self {
+        Self {
+            bytes: vec![0; size],
+            pointer_size,
+        }
+    }
+
+    pub fn u32(mut self, offset: usize, value: u32) -> Self {
+        self.bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn i32(self, offset: usize, value: i32) -> Self {
+        self.u32(offset, value as u32)
+    }
+
+    /// A pointer or handle, truncated to the structure's pointer size.
+    pub fn pointer(mut self, offset: usize, value: u64) -> Self {
+        let size = self.pointer_size;
+        self.bytes[offset..offset + size].copy_from_slice(&value.to_le_bytes()[..size]);
+        self
+    }
+
+    pub fn utf16(mut self, offset: usize, text: &str) -> Self {
+        for (i, unit) in text.encode_utf16().enumerate() {
+            let at = offset + i * 2;
+            self.bytes[at..at + 2].copy_from_slice(&unit.to_le_bytes());
+        }
+        self
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
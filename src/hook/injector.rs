@@ -1,4 +1,5 @@
 use std::cmp::max;
+use std::ffi::c_void;
 use std::fs;
 use std::os::windows::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
@@ -7,7 +8,7 @@ use std::thread;
 use std::time::{Duration, UNIX_EPOCH};
 use windows_sys::Win32::Foundation::{
     CloseHandle, ERROR_BAD_LENGTH, ERROR_NO_MORE_FILES, ERROR_PARTIAL_COPY, GetLastError, HANDLE,
-    HMODULE, INVALID_HANDLE_VALUE, WAIT_OBJECT_0,
+    HMODULE, INVALID_HANDLE_VALUE, WAIT_OBJECT_0, WAIT_TIMEOUT,
 };
 use windows_sys::Win32::System::Diagnostics::Debug::WriteProcessMemory;
 use windows_sys::Win32::System::Diagnostics::ToolHelp::{
@@ -31,6 +32,9 @@ use windows_sys::Win32::System::Threading::{
     PROCESS_VM_OPERATION, PROCESS_VM_READ, PROCESS_VM_WRITE, WaitForSingleObject,
 };
 
+/// How long the UI waits for an export of an already running agent before giving up on it.
+const AGENT_EXPORT_TIMEOUT_MS: u32 = 5_000;
+
 pub fn inject_agent_dll(pid: u32) -> Result<String, String> {
     unsafe {
         let process = OpenProcess(
@@ -155,6 +159,8 @@ pub fn inject_agent_dll(pid: u32) -> Result<String, String> {
             remote_module_base,
             &dll_path,
             "InitializeAgent",
+            0,
+            INFINITE,
         )?;
 
         Ok(format!(
@@ -360,6 +366,42 @@ fn find_i686_libunwind_from_where() -> Option<PathBuf> {
     None
 }
 
+/// Runs `export_name` of the agent already injected into `pid` on a remote thread, passing
+/// `param` as its thread parameter. Returns the export's exit code, which is never 0, or an error
+/// if the export has not returned within `AGENT_EXPORT_TIMEOUT_MS`.
+pub fn call_agent_export(pid: u32, export_name: &str, param: usize) -> Result<u32, String> {
+    let process = unsafe {
+        OpenProcess(
+            PROCESS_CREATE_THREAD | PROCESS_QUERY_INFORMATION | PROCESS_VM_READ,
+            0,
+            pid,
+        )
+    };
+    if process.is_null() {
+        return Err(format!(
+            "OpenProcess failed for PID {pid} (GetLastError={})",
+            last_error_code()
+        ));
+    }
+    let process_handle = HandleGuard(process);
+
+    let target_machine = detect_process_machine(process_handle.0)?;
+    let dll_path = resolve_agent_dll_path(target_machine)?;
+    let module_name = dll_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| format!("Invalid DLL file name: {}", dll_path.display()))?;
+    let agent = find_remote_module(process_handle.0, pid, module_name, target_machine)?;
+    run_remote_export(
+        process_handle.0,
+        agent.base_addr,
+        &dll_path,
+        export_name,
+        param,
+        AGENT_EXPORT_TIMEOUT_MS,
+    )
+}
+
 fn resolve_remote_loadlibraryw(
     process: HANDLE,
     pid: u32,
@@ -375,7 +417,9 @@ fn run_remote_export(
     remote_module_base: usize,
     local_module_path: &Path,
     export_name: &str,
-) -> Result<(), String> {
+    param: usize,
+    timeout_ms: u32,
+) -> Result<u32, String> {
     let export_rva = find_export_rva(local_module_path, export_name)? as usize;
     let remote_export_addr = remote_module_base + export_rva;
     let thread_start: LPTHREAD_START_ROUTINE =
@@ -387,7 +431,7 @@ fn run_remote_export(
             std::ptr::null(),
             0,
             thread_start,
-            std::ptr::null_mut(),
+            param as *const c_void,
             0,
             std::ptr::null_mut(),
         )
@@ -401,7 +445,14 @@ fn run_remote_export(
     }
     let thread_handle = HandleGuard(remote_thread);
 
-    let wait_result = unsafe { WaitForSingleObject(thread_handle.0, INFINITE) };
+    let wait_result = unsafe { WaitForSingleObject(thread_handle.0, timeout_ms) };
+    if wait_result == WAIT_TIMEOUT {
+        // The remote thread keeps running; only the UI stops waiting for it.
+        return Err(format!(
+            "agent did not respond to {export_name} within {} s",
+            timeout_ms / 1000
+        ));
+    }
     if wait_result != WAIT_OBJECT_0 {
         return Err(format!(
             "WaitForSingleObject failed for {} with code {}",
@@ -423,7 +474,7 @@ fn run_remote_export(
         ));
     }
 
-    Ok(())
+    Ok(remote_return_code)
 }
 
 struct RemoteModule {
@@ -10,7 +10,8 @@ pub fn start_udp_event_listener(sender: Sender<Event>) -> Result<(), String> {
     std::thread::Builder::new()
         .name("udp-event-listener".to_owned())
         .spawn(move || {
-            let mut buffer = [0u8; 8192];
+            // Surface captures carry a hex-encoded thumbnail; allow a full datagram.
+            let mut buffer = vec![0u8; 65536];
             loop {
                 let Ok((size, _peer)) = socket.recv_from(&mut buffer) else {
                     continue;
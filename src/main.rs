@@ -1,14 +1,34 @@
 mod app;
+mod decode {
+    pub mod annotate;
+    // The DirectDraw caps tables are only used by the agent's surface decoding.
+    #[allow(dead_code)]
+    pub mod flags;
+    pub mod hresult;
+    pub mod messages;
+    pub mod pixels;
+}
 mod hook;
 mod model {
+    pub mod config_sources;
+    pub mod ddraw_objects;
+    pub mod display_modes;
     pub mod dll;
     pub mod event;
     pub mod filters;
+    pub mod hook_inventory;
     pub mod ipc;
+    pub mod palettes;
     pub mod process;
+    pub mod refcounts;
+    pub mod surface_captures;
+    pub mod surface_loss;
+    pub mod timing;
+    pub mod windows;
 }
 mod util {
     pub mod ascii;
+    pub mod fields;
     pub mod process_launch;
     pub mod time_format;
 }
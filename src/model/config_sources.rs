@@ -0,0 +1,247 @@
+use crate::model::event::Event;
+use crate::util::fields::{field_value, int_field};
+use std::collections::{BTreeMap, HashMap};
+
+const ERROR_MORE_DATA: i64 = 234;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConfigSourceKind {
+    File,
+    IniFile,
+    RegistryKey,
+}
+
+impl ConfigSourceKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::File => "File",
+            Self::IniFile => "INI",
+            Self::RegistryKey => "Registry",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigSource {
+    pub kind: ConfigSourceKind,
+    pub path: String,
+    pub first_seen_ms: u64,
+    pub last_seen_ms: u64,
+    pub reads: u32,
+    pub failures: u32,
+    /// `[section] key` for INI files and value names for registry keys, with the last value read.
+    pub entries: BTreeMap<String, String>,
+}
+
+/// Files, INI files and registry keys the traced process touched, de-duplicated by path.
+#[derive(Debug, Default)]
+pub struct ConfigSources {
+    by_key: HashMap<(ConfigSourceKind, String), ConfigSource>,
+}
+
+impl ConfigSources {
+    pub fn observe_event(&mut self, event: &Event) {
+        match event.api.as_str() {
+            "CreateFileW" | "CreateFileA" => {
+                let Some(path) = field_value(&event.summary, "path") else {
+                    return;
+                };
+                // Devices, pipes and consoles are not configuration.
+                if path.starts_with(r"\\.\")
+                    || ["CON", "CONIN$", "CONOUT$", "NUL"]
+                        .iter()
+                        .any(|device| path.eq_ignore_ascii_case(device))
+                {
+                    return;
+                }
+                let failed = field_value(&event.result, "handle") == Some("INVALID");
+                self.touch(ConfigSourceKind::File, path, event, failed);
+            }
+            "GetPrivateProfileStringW" | "GetPrivateProfileStringA" => {
+                let Some(file) = field_value(&event.summary, "file") else {
+                    return;
+                };
+                let section = field_value(&event.summary, "section").unwrap_or("null");
+                let key = field_value(&event.summary, "key").unwrap_or("null");
+                let value = field_value(&event.result, "value").unwrap_or_default();
+                let source = self.touch(ConfigSourceKind::IniFile, file, event, false);
+                source
+                    .entries
+                    .insert(format!("[{section}] {key}"), value.to_owned());
+            }
+            "RegOpenKeyExW" | "RegOpenKeyExA" => {
+                // Keys opened below a handle the agent never saw have no path.
+                let Some(path) = field_value(&event.result, "path") else {
+                    return;
+                };
+                let failed = int_field(&event.result, "status") != Some(0);
+                self.touch(ConfigSourceKind::RegistryKey, path, event, failed);
+            }
+            "RegQueryValueExW" | "RegQueryValueExA" => {
+                let path = field_value(&event.summary, "path")
+                    .map(str::to_owned)
+                    .or_else(|| {
+                        field_value(&event.summary, "hkey").map(|hkey| format!("<key {hkey}>"))
+                    });
+                let Some(path) = path else {
+                    return;
+                };
+                let status = int_field(&event.result, "status");
+                // ERROR_MORE_DATA only means the caller is sizing its buffer.
+                let failed = status.is_some_and(|status| status != 0 && status != ERROR_MORE_DATA);
+                let name = match field_value(&event.summary, "value") {
+                    None | Some("" | "null") => "(Default)".to_owned(),
+                    Some(name) => name.to_owned(),
+                };
+                let value = match field_value(&event.result, "data") {
+                    Some(data) => Some(data.to_owned()),
+                    None if failed => Some(format!("<status={}>", status.unwrap_or_default())),
+                    None => None,
+                };
+                let source = self.touch(ConfigSourceKind::RegistryKey, &path, event, failed);
+                if let Some(value) = value {
+                    source.entries.insert(name, value);
+                } else {
+                    source.entries.entry(name).or_default();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn touch(
+        &mut self,
+        kind: ConfigSourceKind,
+        path: &str,
+        event: &Event,
+        failed: bool,
+    ) -> &mut ConfigSource {
+        // Windows paths and registry keys are case-insensitive.
+        let source = self
+            .by_key
+            .entry((kind, path.to_ascii_lowercase()))
+            .or_insert_with(|| ConfigSource {
+                kind,
+                path: path.to_owned(),
+                first_seen_ms: event.timestamp_ms,
+                last_seen_ms: event.timestamp_ms,
+                reads: 0,
+                failures: 0,
+                entries: BTreeMap::new(),
+            });
+        source.last_seen_ms = event.timestamp_ms;
+        source.reads += 1;
+        if failed {
+            source.failures += 1;
+        }
+        source
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_key.len()
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &ConfigSource> {
+        self.by_key.values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY_PATH: &str = r"HKEY_CURRENT_USER\Software\Game";
+
+    fn source(sources: &ConfigSources, kind: ConfigSourceKind) -> &ConfigSource {
+        sources.values().find(|source| source.kind == kind).unwrap()
+    }
+
+    #[test]
+    fn merges_files_and_ini_reads_by_path() {
+        let mut sources = ConfigSources::default();
+        sources.observe_event(&Event::traced(
+            100,
+            "CreateFileW",
+            r#"path="C:\Games\My Game\settings.cfg" access=0x80000000 share=0x00000001 disposition=3 flags=0x00000080"#,
+            "handle=INVALID error=2",
+        ));
+        sources.observe_event(&Event::traced(
+            200,
+            "CreateFileA",
+            r#"path="c:\games\my game\SETTINGS.CFG" access=0x80000000 share=0x00000001 disposition=3 flags=0x00000080"#,
+            "handle=0x1A4",
+        ));
+        sources.observe_event(&Event::traced(
+            250,
+            "CreateFileW",
+            r#"path="\\.\DISPLAY1" access=0x00000000 share=0x00000003 disposition=3 flags=0x00000000"#,
+            "handle=0x1A8",
+        ));
+        sources.observe_event(&Event::traced(
+            300,
+            "GetPrivateProfileStringW",
+            r#"file="C:\Games\My Game\game.ini" section="Video" key="Width" default="640""#,
+            r#"chars=4 value="1024""#,
+        ));
+        sources.observe_event(&Event::traced(
+            400,
+            "GetPrivateProfileStringW",
+            r#"file="C:\Games\My Game\game.ini" section="Video" key="Width" default="640""#,
+            r#"chars=4 value="1600""#,
+        ));
+
+        assert_eq!(sources.len(), 2);
+        let file = source(&sources, ConfigSourceKind::File);
+        assert_eq!(file.path, r"C:\Games\My Game\settings.cfg");
+        assert_eq!((file.reads, file.failures), (2, 1));
+        assert_eq!((file.first_seen_ms, file.last_seen_ms), (100, 200));
+
+        let ini = source(&sources, ConfigSourceKind::IniFile);
+        assert_eq!(ini.reads, 2);
+        assert_eq!(
+            ini.entries.get("[Video] Width").map(String::as_str),
+            Some("1600")
+        );
+    }
+
+    #[test]
+    fn records_registry_values_and_failures() {
+        let mut sources = ConfigSources::default();
+        sources.observe_event(&Event::traced(
+            100,
+            "RegOpenKeyExW",
+            r#"hkey=HKEY_CURRENT_USER subkey="Software\Game" options=0x0 sam=0x00020019"#,
+            &format!(r#"status=0 key=0x2C0 path="{KEY_PATH}""#),
+        ));
+        for (timestamp_ms, value, result) in [
+            (200, "Windowed", "status=234 type=4 size=4"),
+            (210, "Windowed", "status=0 type=4 size=4 data=0x00000001"),
+            (220, "Gamma", "status=2"),
+            (230, "", r#"status=0 type=1 size=10 data="Game""#),
+        ] {
+            sources.observe_event(&Event::traced(
+                timestamp_ms,
+                "RegQueryValueExW",
+                &format!(r#"hkey=0x2C0 path="{KEY_PATH}" value="{value}""#),
+                result,
+            ));
+        }
+
+        assert_eq!(sources.len(), 1);
+        let key = source(&sources, ConfigSourceKind::RegistryKey);
+        assert_eq!((key.reads, key.failures), (5, 1));
+        let entries: Vec<_> = key
+            .entries
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("(Default)", "Game"),
+                ("Gamma", "<status=2>"),
+                ("Windowed", "0x00000001"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,510 @@
+use crate::decode::hresult::{find_hresult, hresult_succeeded};
+use crate::model::event::Event;
+use crate::util::fields::{field_value, hex_field, int_field};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceRole {
+    Primary,
+    BackBuffer,
+    Offscreen,
+    Texture,
+    ZBuffer,
+    Overlay,
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DdObjectKind {
+    DirectDraw,
+    Surface(SurfaceRole),
+    Clipper,
+    Palette,
+    /// Non-DirectDraw interfaces handed out by `QueryInterface`, e.g. `IDirect3D7`.
+    Other,
+}
+
+/// The call that produced an interface pointer.
+#[derive(Debug, Clone)]
+pub struct CreationRecord {
+    pub timestamp_ms: u64,
+    pub api: String,
+    pub caller: String,
+    pub thread_id: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct DdObject {
+    pub pointer: u64,
+    pub kind: DdObjectKind,
+    pub interface: String,
+    /// The object this one was created, queried or attached from.
+    pub parent: Option<u64>,
+    pub relation: String,
+    /// `None` when the pointer was first seen as a `this` of some call.
+    pub created: Option<CreationRecord>,
+    pub released_ms: Option<u64>,
+    pub call_count: u32,
+    pub clipper: Option<u64>,
+    pub palette: Option<u64>,
+    pub attachments: Vec<u64>,
+}
+
+/// DirectDraw objects and how they were derived from one another, rebuilt from events.
+#[derive(Debug, Default)]
+pub struct DdObjectGraph {
+    objects: HashMap<u64, DdObject>,
+    order: Vec<u64>,
+}
+
+impl DdObjectGraph {
+    pub fn observe_event(&mut self, event: &Event) {
+        if !is_directdraw_event(&event.api) {
+            return;
+        }
+
+        let this = hex_field(&event.summary, "this").filter(|this| *this != 0);
+        if let Some(this) = this {
+            let kind = kind_from_api(&event.api);
+            let object = self.object_mut(this, kind, interface_from_api(&event.api));
+            object.call_count = object.call_count.saturating_add(1);
+        }
+
+        if !find_hresult(&event.result).is_none_or(hresult_succeeded) {
+            return;
+        }
+
+        match event.api.as_str() {
+            "DirectDrawCreate" => {
+                self.created(
+                    event,
+                    "directdraw",
+                    None,
+                    DdObjectKind::DirectDraw,
+                    "IDirectDraw",
+                );
+            }
+            "DirectDrawCreateEx" | "CoCreateInstance(DirectDraw)" => {
+                let interface = field_value(&event.summary, "iid")
+                    .or_else(|| field_value(&event.summary, "riid"))
+                    .and_then(|name| name.strip_prefix("IID_"))
+                    .unwrap_or("IDirectDraw")
+                    .to_owned();
+                self.created(
+                    event,
+                    "directdraw",
+                    None,
+                    DdObjectKind::DirectDraw,
+                    &interface,
+                );
+            }
+            "DirectDrawCreateClipper" => {
+                self.created(
+                    event,
+                    "clipper",
+                    None,
+                    DdObjectKind::Clipper,
+                    "IDirectDrawClipper",
+                );
+            }
+            "IDirectDraw::CreateClipper" => {
+                self.created(
+                    event,
+                    "clipper",
+                    this,
+                    DdObjectKind::Clipper,
+                    "IDirectDrawClipper",
+                );
+            }
+            "IDirectDraw::CreatePalette" => {
+                self.created(
+                    event,
+                    "palette",
+                    this,
+                    DdObjectKind::Palette,
+                    "IDirectDrawPalette",
+                );
+            }
+            "IDirectDraw::CreateSurface" | "IDirectDraw::DuplicateSurface" => {
+                let role = match field_value(&event.summary, "source") {
+                    Some(_) => self
+                        .source_kind(&event.summary)
+                        .unwrap_or(DdObjectKind::Surface(SurfaceRole::Other)),
+                    None => DdObjectKind::Surface(surface_role(
+                        field_value(&event.summary, "caps").unwrap_or_default(),
+                    )),
+                };
+                let interface = this
+                    .and_then(|this| self.objects.get(&this))
+                    .map_or("IDirectDrawSurface", |parent| {
+                        created_surface_interface(&parent.interface)
+                    })
+                    .to_owned();
+                self.created(event, "surface", this, role, &interface);
+            }
+            "IDirectDraw::QueryInterface" | "IDirectDrawSurface::QueryInterface" => {
+                let Some(out) = hex_field(&event.summary, "out").filter(|out| *out != 0) else {
+                    return;
+                };
+                let riid = field_value(&event.summary, "riid").unwrap_or_default();
+                let interface = riid.strip_prefix("IID_").unwrap_or(riid).to_owned();
+                let kind = if interface.starts_with("IDirectDrawSurface") {
+                    this.and_then(|this| self.objects.get(&this))
+                        .map_or(DdObjectKind::Surface(SurfaceRole::Other), |source| {
+                            source.kind
+                        })
+                } else if interface.starts_with("IDirectDraw") {
+                    DdObjectKind::DirectDraw
+                } else {
+                    DdObjectKind::Other
+                };
+                // QI may hand back `this` or an interface we already know; keep its origin.
+                if Some(out) != this && !self.is_known(out) {
+                    self.derived(event, out, this, kind, interface, format!("QI {riid}"));
+                }
+            }
+            "IDirectDrawSurface::GetAttachedSurface" => {
+                let Some(out) = hex_field(&event.summary, "out").filter(|out| *out != 0) else {
+                    return;
+                };
+                if self.is_known(out) {
+                    self.attach(this, out);
+                    return;
+                }
+                let role = surface_role(field_value(&event.summary, "caps").unwrap_or_default());
+                let interface = this.and_then(|this| self.objects.get(&this)).map_or_else(
+                    || "IDirectDrawSurface".to_owned(),
+                    |source| source.interface.clone(),
+                );
+                self.derived(
+                    event,
+                    out,
+                    this,
+                    DdObjectKind::Surface(role),
+                    interface,
+                    "attached".to_owned(),
+                );
+                self.attach(this, out);
+            }
+            "IDirectDrawSurface::AddAttachedSurface" => {
+                if let Some(attached) =
+                    hex_field(&event.summary, "attached").filter(|ptr| *ptr != 0)
+                {
+                    self.attach(this, attached);
+                }
+            }
+            "IDirectDrawSurface::SetClipper" => {
+                let clipper = hex_field(&event.summary, "clipper").filter(|ptr| *ptr != 0);
+                if let Some(surface) = this.and_then(|this| self.objects.get_mut(&this)) {
+                    surface.clipper = clipper;
+                }
+            }
+            "IDirectDrawSurface::SetPalette" => {
+                let palette = hex_field(&event.summary, "palette").filter(|ptr| *ptr != 0);
+                if let Some(surface) = this.and_then(|this| self.objects.get_mut(&this)) {
+                    surface.palette = palette;
+                }
+            }
+            api if api.ends_with("::Release") => {
+                if int_field(&event.result, "refcount") != Some(0) {
+                    return;
+                }
+                if let Some(object) = this.and_then(|this| self.objects.get_mut(&this)) {
+                    object.released_ms = Some(event.timestamp_ms);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &DdObject> {
+        self.in_order()
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Objects without a known parent, in the order they were first seen.
+    pub fn roots(&self) -> impl Iterator<Item = &DdObject> {
+        self.in_order().filter(|object| {
+            object
+                .parent
+                .is_none_or(|parent| !self.objects.contains_key(&parent))
+        })
+    }
+
+    pub fn children(&self, pointer: u64) -> impl Iterator<Item = &DdObject> {
+        self.in_order()
+            .filter(move |object| object.parent == Some(pointer))
+    }
+
+    fn in_order(&self) -> impl Iterator<Item = &DdObject> {
+        self.order
+            .iter()
+            .filter_map(|pointer| self.objects.get(pointer))
+    }
+
+    fn object_mut(&mut self, pointer: u64, kind: DdObjectKind, interface: &str) -> &mut DdObject {
+        if !self.objects.contains_key(&pointer) {
+            self.order.push(pointer);
+        }
+        self.objects.entry(pointer).or_insert_with(|| DdObject {
+            pointer,
+            kind,
+            interface: interface.to_owned(),
+            parent: None,
+            relation: String::new(),
+            created: None,
+            released_ms: None,
+            call_count: 0,
+            clipper: None,
+            palette: None,
+            attachments: Vec::new(),
+        })
+    }
+
+    fn created(
+        &mut self,
+        event: &Event,
+        result_key: &str,
+        parent: Option<u64>,
+        kind: DdObjectKind,
+        interface: &str,
+    ) {
+        let Some(pointer) = hex_field(&event.result, result_key).filter(|ptr| *ptr != 0) else {
+            return;
+        };
+        let relation = event
+            .api
+            .rsplit("::")
+            .next()
+            .unwrap_or(&event.api)
+            .to_owned();
+        self.derived(event, pointer, parent, kind, interface.to_owned(), relation);
+    }
+
+    fn derived(
+        &mut self,
+        event: &Event,
+        pointer: u64,
+        parent: Option<u64>,
+        kind: DdObjectKind,
+        interface: String,
+        relation: String,
+    ) {
+        // A recycled pointer belongs to a new object once the old one has been released.
+        if self
+            .objects
+            .get(&pointer)
+            .is_some_and(|object| object.released_ms.is_some())
+        {
+            self.objects.remove(&pointer);
+            self.order.retain(|existing| *existing != pointer);
+        }
+
+        let object = self.object_mut(pointer, kind, &interface);
+        object.kind = kind;
+        object.interface = interface;
+        object.parent = parent;
+        object.relation = relation;
+        object.created = Some(CreationRecord {
+            timestamp_ms: event.timestamp_ms,
+            api: event.api.clone(),
+            caller: event.caller.clone(),
+            thread_id: event.thread_id,
+        });
+    }
+
+    /// Whether `pointer` already has a recorded origin and is still alive.
+    fn is_known(&self, pointer: u64) -> bool {
+        self.objects
+            .get(&pointer)
+            .is_some_and(|object| object.created.is_some() && object.released_ms.is_none())
+    }
+
+    fn attach(&mut self, surface: Option<u64>, attached: u64) {
+        if let Some(source) = surface.and_then(|surface| self.objects.get_mut(&surface))
+            && !source.attachments.contains(&attached)
+        {
+            source.attachments.push(attached);
+        }
+    }
+
+    fn source_kind(&self, summary: &str) -> Option<DdObjectKind> {
+        let source = hex_field(summary, "source")?;
+        self.objects.get(&source).map(|object| object.kind)
+    }
+}
+
+fn is_directdraw_event(api: &str) -> bool {
+    api.starts_with("IDirectDraw")
+        || api.starts_with("DirectDrawCreate")
+        || api == "CoCreateInstance(DirectDraw)"
+}
+
+fn kind_from_api(api: &str) -> DdObjectKind {
+    if api.starts_with("IDirectDrawSurface") {
+        DdObjectKind::Surface(SurfaceRole::Other)
+    } else if api.starts_with("IDirectDrawClipper") {
+        DdObjectKind::Clipper
+    } else if api.starts_with("IDirectDrawPalette") {
+        DdObjectKind::Palette
+    } else {
+        DdObjectKind::DirectDraw
+    }
+}
+
+fn interface_from_api(api: &str) -> &str {
+    api.split("::").next().unwrap_or(api)
+}
+
+/// Mirrors the agent: IDirectDraw7 hands out IDirectDrawSurface7, IDirectDraw4 hands out
+/// IDirectDrawSurface4 and everything older hands out IDirectDrawSurface.
+fn created_surface_interface(directdraw_interface: &str) -> &'static str {
+    match directdraw_interface {
+        "IDirectDraw7" => "IDirectDrawSurface7",
+        "IDirectDraw4" => "IDirectDrawSurface4",
+        _ => "IDirectDrawSurface",
+    }
+}
+
+fn surface_role(caps: &str) -> SurfaceRole {
+    let has = |name: &str| caps.split(['|', ')', ' ']).any(|flag| flag == name);
+    if has("DDSCAPS_PRIMARYSURFACE") {
+        SurfaceRole::Primary
+    } else if has("DDSCAPS_BACKBUFFER") {
+        SurfaceRole::BackBuffer
+    } else if has("DDSCAPS_ZBUFFER") {
+        SurfaceRole::ZBuffer
+    } else if has("DDSCAPS_OVERLAY") {
+        SurfaceRole::Overlay
+    } else if has("DDSCAPS_TEXTURE") {
+        SurfaceRole::Texture
+    } else if has("DDSCAPS_OFFSCREENPLAIN") {
+        SurfaceRole::Offscreen
+    } else {
+        SurfaceRole::Other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIMARY_DESC: &str = "(flags=DDSD_CAPS|DDSD_BACKBUFFERCOUNT caps=DDSCAPS_PRIMARYSURFACE|DDSCAPS_FLIP|DDSCAPS_COMPLEX)";
+
+    fn object(graph: &DdObjectGraph, pointer: u64) -> &DdObject {
+        graph
+            .values()
+            .find(|object| object.pointer == pointer)
+            .unwrap()
+    }
+
+    fn create_directdraw7(graph: &mut DdObjectGraph) {
+        graph.observe_event(&Event::traced(
+            100,
+            "DirectDrawCreateEx",
+            "guid=null out_ptr=0x19fe2c iid=IID_IDirectDraw7 outer_ptr=0x0",
+            "HRESULT=0x00000000 directdraw=0x1000",
+        ));
+    }
+
+    fn create_surface(graph: &mut DdObjectGraph, timestamp_ms: u64, result: &str) {
+        graph.observe_event(&Event::traced(
+            timestamp_ms,
+            "IDirectDraw::CreateSurface",
+            &format!("this=0x1000 desc_ptr=0x19fd80 {PRIMARY_DESC} out_ptr=0x19fe28 outer_ptr=0x0"),
+            result,
+        ));
+    }
+
+    #[test]
+    fn builds_graph_from_creation_sequence() {
+        let mut graph = DdObjectGraph::default();
+        create_directdraw7(&mut graph);
+        graph.observe_event(&Event::traced(
+            110,
+            "IDirectDraw::CreateClipper",
+            "this=0x1000 flags=0x00000000 outer_ptr=0x0",
+            "HRESULT=0x00000000 clipper=0x3000",
+        ));
+        create_surface(&mut graph, 120, "HRESULT=0x00000000 surface=0x2000");
+        graph.observe_event(&Event::traced(
+            130,
+            "IDirectDrawSurface::GetAttachedSurface",
+            "this=0x2000 caps=DDSCAPS_BACKBUFFER out_ptr=0x19fe24 out=0x2100",
+            "HRESULT=0x00000000",
+        ));
+        graph.observe_event(&Event::traced(
+            140,
+            "IDirectDrawSurface::SetClipper",
+            "this=0x2000 clipper=0x3000",
+            "HRESULT=0x00000000",
+        ));
+        graph.observe_event(&Event::traced(
+            150,
+            "IDirectDrawSurface::Release",
+            "this=0x2100",
+            "refcount=0",
+        ));
+
+        let roots: Vec<_> = graph.roots().map(|object| object.pointer).collect();
+        assert_eq!(roots, [0x1000]);
+        let children: Vec<_> = graph
+            .children(0x1000)
+            .map(|object| object.pointer)
+            .collect();
+        assert_eq!(children, [0x3000, 0x2000]);
+
+        let directdraw = object(&graph, 0x1000);
+        assert_eq!(directdraw.interface, "IDirectDraw7");
+        assert_eq!(directdraw.call_count, 2);
+
+        let primary = object(&graph, 0x2000);
+        assert_eq!(primary.kind, DdObjectKind::Surface(SurfaceRole::Primary));
+        assert_eq!(primary.interface, "IDirectDrawSurface7");
+        assert_eq!(primary.relation, "CreateSurface");
+        assert_eq!(
+            (primary.clipper, primary.attachments.as_slice()),
+            (Some(0x3000), &[0x2100][..])
+        );
+
+        let back_buffer = object(&graph, 0x2100);
+        assert_eq!(
+            back_buffer.kind,
+            DdObjectKind::Surface(SurfaceRole::BackBuffer)
+        );
+        assert_eq!(
+            (back_buffer.parent, back_buffer.relation.as_str()),
+            (Some(0x2000), "attached")
+        );
+        assert_eq!(back_buffer.released_ms, Some(150));
+    }
+
+    #[test]
+    fn failed_creation_adds_nothing_and_released_pointers_are_reused() {
+        let mut graph = DdObjectGraph::default();
+        create_directdraw7(&mut graph);
+        create_surface(&mut graph, 110, "HRESULT=0x887601C2 surface=0x0");
+        assert_eq!(graph.len(), 1);
+
+        create_surface(&mut graph, 120, "HRESULT=0x00000000 surface=0x2000");
+        graph.observe_event(&Event::traced(
+            130,
+            "IDirectDrawSurface::Release",
+            "this=0x2000",
+            "refcount=0",
+        ));
+        create_surface(&mut graph, 140, "HRESULT=0x00000000 surface=0x2000");
+
+        let surface = object(&graph, 0x2000);
+        assert_eq!(surface.released_ms, None);
+        assert_eq!(
+            surface.created.as_ref().map(|created| created.timestamp_ms),
+            Some(140)
+        );
+        assert_eq!(surface.call_count, 0);
+        assert_eq!(graph.len(), 2);
+    }
+}
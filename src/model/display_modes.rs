@@ -0,0 +1,214 @@
+use crate::decode::hresult::{find_hresult, hresult_succeeded};
+use crate::model::event::Event;
+use crate::model::refcounts::ExitTrigger;
+use crate::util::fields::{field_value, hex_field, int_field};
+use std::fmt;
+
+const CDS_TEST: u32 = 0x0000_0002;
+const DISP_CHANGE_SUCCESSFUL: i64 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModeChangeKind {
+    Set,
+    /// `ChangeDisplaySettingsExW` with `CDS_TEST`; never applied.
+    Test,
+    Restore,
+}
+
+/// Only the fields the caller actually specified are set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DisplayMode {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub bpp: Option<u32>,
+    pub refresh: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModeChange {
+    pub timestamp_ms: u64,
+    pub api: String,
+    pub thread_id: u32,
+    pub caller: String,
+    pub kind: ModeChangeKind,
+    pub mode: DisplayMode,
+    pub succeeded: bool,
+    pub result: String,
+}
+
+/// Every display mode request seen in the event stream, in order.
+#[derive(Debug, Default)]
+pub struct DisplayModeHistory {
+    changes: Vec<ModeChange>,
+    exit: Option<ExitTrigger>,
+}
+
+impl DisplayModeHistory {
+    pub fn observe_event(&mut self, event: &Event) {
+        if let Some(exit) = ExitTrigger::from_event(event) {
+            self.exit = Some(exit);
+            return;
+        }
+        let (kind, mode, succeeded) = match event.api.as_str() {
+            "ChangeDisplaySettingsExW" => {
+                let flags = hex_field(&event.summary, "flags").unwrap_or(0) as u32;
+                // A null DEVMODE switches back to the mode stored in the registry.
+                let kind = if field_value(&event.summary, "mode") == Some("[null]") {
+                    ModeChangeKind::Restore
+                } else if flags & CDS_TEST != 0 {
+                    ModeChangeKind::Test
+                } else {
+                    ModeChangeKind::Set
+                };
+                let mode = DisplayMode {
+                    width: u32_field(&event.summary, "width"),
+                    height: u32_field(&event.summary, "height"),
+                    bpp: u32_field(&event.summary, "bpp"),
+                    refresh: u32_field(&event.summary, "freq"),
+                };
+                let succeeded =
+                    int_field(&event.result, "DISP_CHANGE") == Some(DISP_CHANGE_SUCCESSFUL);
+                (kind, mode, succeeded)
+            }
+            "IDirectDraw::SetDisplayMode" | "IDirectDraw7::SetDisplayMode" => {
+                let mode = DisplayMode {
+                    width: u32_field(&event.summary, "width"),
+                    height: u32_field(&event.summary, "height"),
+                    bpp: u32_field(&event.summary, "bpp"),
+                    // Zero asks DirectDraw for the adapter's default rate.
+                    refresh: u32_field(&event.summary, "refresh").filter(|rate| *rate != 0),
+                };
+                (ModeChangeKind::Set, mode, hresult_call_succeeded(event))
+            }
+            "IDirectDraw::RestoreDisplayMode" => (
+                ModeChangeKind::Restore,
+                DisplayMode::default(),
+                hresult_call_succeeded(event),
+            ),
+            _ => return,
+        };
+
+        self.changes.push(ModeChange {
+            timestamp_ms: event.timestamp_ms,
+            api: event.api.clone(),
+            thread_id: event.thread_id,
+            caller: event.caller.clone(),
+            kind,
+            mode,
+            succeeded,
+            result: event.result.clone(),
+        });
+    }
+
+    pub fn changes(&self) -> &[ModeChange] {
+        &self.changes
+    }
+
+    pub fn exit(&self) -> Option<&ExitTrigger> {
+        self.exit.as_ref()
+    }
+
+    /// The last successfully applied mode switch if no successful restore followed it.
+    pub fn unrestored_change(&self) -> Option<&ModeChange> {
+        let mut active = None;
+        for change in self.changes.iter().filter(|change| change.succeeded) {
+            match change.kind {
+                ModeChangeKind::Set => active = Some(change),
+                ModeChangeKind::Restore => active = None,
+                ModeChangeKind::Test => {}
+            }
+        }
+        active
+    }
+}
+
+impl fmt::Display for DisplayMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let part = |value: Option<u32>| value.map_or_else(|| "?".to_owned(), |v| v.to_string());
+        write!(
+            f,
+            "{}x{}x{}",
+            part(self.width),
+            part(self.height),
+            part(self.bpp)
+        )?;
+        if let Some(refresh) = self.refresh {
+            write!(f, "@{refresh}")?;
+        }
+        Ok(())
+    }
+}
+
+fn u32_field(text: &str, key: &str) -> Option<u32> {
+    int_field(text, key).and_then(|value| u32::try_from(value).ok())
+}
+
+fn hresult_call_succeeded(event: &Event) -> bool {
+    find_hresult(&event.result).is_some_and(hresult_succeeded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DD7_SET_640X480: &str =
+        "this=0x2a41f30 width=640 height=480 bpp=16 refresh=0 flags=0x00000000";
+
+    fn change_display_settings(timestamp_ms: u64, flags: u32, mode: &str, result: i32) -> Event {
+        Event::traced(
+            timestamp_ms,
+            "ChangeDisplaySettingsExW",
+            &format!("hwnd=0x0000000000000000 flags=0x{flags:08X} device=null mode=[{mode}]"),
+            &format!("DISP_CHANGE={result}"),
+        )
+    }
+
+    #[test]
+    fn flags_mode_switch_left_active_at_exit() {
+        let mut history = DisplayModeHistory::default();
+        history.observe_event(&change_display_settings(
+            100,
+            CDS_TEST,
+            "fields=DM_BITSPERPEL|DM_PELSWIDTH|DM_PELSHEIGHT width=640 height=480 bpp=16",
+            0,
+        ));
+        history.observe_event(&Event::traced(
+            200,
+            "IDirectDraw7::SetDisplayMode",
+            DD7_SET_640X480,
+            "HRESULT=0x00000000",
+        ));
+        history.observe_event(&Event::traced(300, "ExitProcess", "exit_code=0", ""));
+
+        let kinds: Vec<_> = history.changes().iter().map(|change| change.kind).collect();
+        assert_eq!(kinds, [ModeChangeKind::Test, ModeChangeKind::Set]);
+        let unrestored = history.unrestored_change().unwrap();
+        assert_eq!(unrestored.api, "IDirectDraw7::SetDisplayMode");
+        assert_eq!(unrestored.mode.to_string(), "640x480x16");
+        assert_eq!(history.exit().map(|exit| exit.timestamp_ms), Some(300));
+    }
+
+    #[test]
+    fn restore_clears_switch_and_failures_are_ignored() {
+        let mut history = DisplayModeHistory::default();
+        history.observe_event(&Event::traced(
+            100,
+            "IDirectDraw7::SetDisplayMode",
+            DD7_SET_640X480,
+            "HRESULT=0x00000000",
+        ));
+        history.observe_event(&change_display_settings(200, 0, "null", 0));
+        history.observe_event(&Event::traced(
+            300,
+            "IDirectDraw7::SetDisplayMode",
+            "this=0x2a41f30 width=800 height=600 bpp=32 refresh=60 flags=0x00000000",
+            "HRESULT=0x88760078",
+        ));
+
+        assert_eq!(history.changes().len(), 3);
+        assert_eq!(history.changes()[1].kind, ModeChangeKind::Restore);
+        assert_eq!(history.changes()[2].mode.to_string(), "800x600x32@60");
+        assert!(!history.changes()[2].succeeded);
+        assert!(history.unrestored_change().is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use crate::model::event::Event;
+use crate::util::fields::field_value;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
@@ -9,6 +10,8 @@ pub struct LoadedDll {
     pub last_seen_ms: u64,
     pub count: u32,
     pub last_summary: String,
+    /// Hook groups the agent installed from this module, in install order.
+    pub hook_groups: Vec<String>,
 }
 
 #[derive(Debug, Default)]
@@ -18,6 +21,10 @@ pub struct LoadedDlls {
 
 impl LoadedDlls {
     pub fn observe_event(&mut self, event: &Event) {
+        if event.api == "HookGroupInstalled" {
+            self.observe_hook_group(event);
+            return;
+        }
         if event.api != "DllLoad" {
             return;
         }
@@ -42,6 +49,7 @@ impl LoadedDlls {
             last_seen_ms: event.timestamp_ms,
             count: 0,
             last_summary: String::new(),
+            hook_groups: Vec::new(),
         });
 
         entry.last_seen_ms = event.timestamp_ms;
@@ -52,6 +60,38 @@ impl LoadedDlls {
         }
     }
 
+    fn observe_hook_group(&mut self, event: &Event) {
+        if event.result != "OK" {
+            return;
+        }
+        let (Some(group), Some(path)) = (
+            field_value(&event.summary, "group"),
+            field_value(&event.summary, "module"),
+        ) else {
+            return;
+        };
+        if path.is_empty() {
+            return;
+        }
+
+        // Modules loaded before the agent and left out of the snapshot only show up here.
+        let entry = self
+            .by_key
+            .entry(path.to_owned())
+            .or_insert_with(|| LoadedDll {
+                name: basename(path).to_owned(),
+                path: path.to_owned(),
+                first_seen_ms: event.timestamp_ms,
+                last_seen_ms: event.timestamp_ms,
+                count: 0,
+                last_summary: String::new(),
+                hook_groups: Vec::new(),
+            });
+        if !entry.hook_groups.iter().any(|known| known == group) {
+            entry.hook_groups.push(group.to_owned());
+        }
+    }
+
     pub fn values(&self) -> impl Iterator<Item = &LoadedDll> {
         self.by_key.values()
     }
@@ -116,3 +116,11 @@ impl Event {
         ]
     }
 }
+
+#[cfg(test)]
+impl Event {
+    /// An event from the game's main thread, for the model tests.
+    pub fn traced(timestamp_ms: u64, api: &str, summary: &str, result: &str) -> Self {
+        Self::new(timestamp_ms, api, summary, "game.exe+0x1A20", 1884, result)
+    }
+}
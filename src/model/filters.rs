@@ -1,33 +1,70 @@
+use crate::decode::hresult::{find_hresult, hresult_succeeded};
+use crate::decode::messages::{MessageClass, message_class};
 use crate::model::event::Event;
+use crate::model::ipc::{AGENT_DETACHED_API, HOOK_INVENTORY_API, HOOK_INVENTORY_ENTRY_API};
 use crate::util::ascii::contains_ignore_ascii_case;
+use crate::util::fields::hex_field;
 
-const WINDOW_DISPLAY_APIS: [&str; 5] = [
+const WINDOW_DISPLAY_APIS: [&str; 13] = [
     "CreateWindowExW",
+    "WindowMessage",
+    "DestroyWindow",
+    "ShowWindow",
+    "SetForegroundWindow",
     "SetWindowPos",
     "MoveWindow",
+    "SetWindowLongW",
+    "SetWindowLongPtrW",
     "ChangeDisplaySettingsExW",
     "AdjustWindowRectEx",
+    "ExitProcess",
+    AGENT_DETACHED_API,
 ];
 
-const OTHER_GRAPHICS_APIS: [&str; 8] = [
+const OTHER_GRAPHICS_APIS: [&str; 7] = [
     "Direct3DCreate9",
     "Direct3DCreate9Ex",
-    "CreateDXGIFactory",
-    "CreateDXGIFactory1",
-    "D3D11CreateDevice",
-    "D3D11CreateDeviceAndSwapChain",
     // These are useful when troubleshooting hook setup, but they are not DirectDraw API calls.
     "DirectDrawHookStatus",
     "DirectDrawHookInstall",
+    "HookGroupInstalled",
+    HOOK_INVENTORY_API,
+    HOOK_INVENTORY_ENTRY_API,
+];
+
+const GDI_APIS: [&str; 8] = [
+    "BitBlt",
+    "StretchBlt",
+    "StretchDIBits",
+    "SetDIBitsToDevice",
+    "CreateDIBSection",
+    "GetDC",
+    "ReleaseDC",
+    "GetDeviceCaps",
 ];
 
 const DIRECTDRAW_NONCALL_EVENTS: [&str; 2] = ["DirectDrawHookStatus", "DirectDrawHookInstall"];
+const DIRECT3D9_NONCALL_EVENTS: [&str; 1] = ["Direct3D9HookInstall"];
+const DXGI_NONCALL_EVENTS: [&str; 1] = ["DXGIHookInstall"];
+const OPENGL_NONCALL_EVENTS: [&str; 1] = ["OpenGLHookInstall"];
+
+const INPUT_APIS: [&str; 5] = [
+    "ClipCursor",
+    "SetCursorPos",
+    "ShowCursor",
+    "SetCapture",
+    "RegisterRawInputDevices",
+];
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ApiScope {
     All,
     WindowDisplayAndGraphics,
     DirectDrawCallsOnly,
+    Direct3D9CallsOnly,
+    DxgiD3D11CallsOnly,
+    OpenGlCallsOnly,
+    InputOnly,
 }
 
 impl Default for ApiScope {
@@ -62,6 +99,11 @@ impl Default for EventSort {
 pub struct EventFilters {
     pub text_query: String,
     pub api_scope: ApiScope,
+    pub failures_only: bool,
+    /// Only calls made on this COM interface pointer (the `this=` field).
+    pub this_pointer: Option<u64>,
+    /// Only `WindowMessage` events of this class; other events are unaffected.
+    pub message_class: Option<MessageClass>,
     pub sort: EventSort,
 }
 
@@ -70,6 +112,9 @@ impl Default for EventFilters {
         Self {
             text_query: String::new(),
             api_scope: ApiScope::DirectDrawCallsOnly,
+            failures_only: false,
+            this_pointer: None,
+            message_class: None,
             sort: EventSort::default(),
         }
     }
@@ -89,6 +134,43 @@ impl EventFilters {
                     return false;
                 }
             }
+            ApiScope::Direct3D9CallsOnly => {
+                if !is_direct3d9_call_api(&event.api) {
+                    return false;
+                }
+            }
+            ApiScope::DxgiD3D11CallsOnly => {
+                if !is_dxgi_d3d11_call_api(&event.api) {
+                    return false;
+                }
+            }
+            ApiScope::OpenGlCallsOnly => {
+                if !is_opengl_call_api(&event.api) {
+                    return false;
+                }
+            }
+            ApiScope::InputOnly => {
+                if !is_input_api(&event.api) {
+                    return false;
+                }
+            }
+        }
+
+        if self.failures_only && !is_failed_call(event) {
+            return false;
+        }
+
+        if let Some(pointer) = self.this_pointer
+            && hex_field(&event.summary, "this") != Some(pointer)
+        {
+            return false;
+        }
+
+        if let Some(class) = self.message_class
+            && event.api == "WindowMessage"
+            && hex_field(&event.summary, "msg").map(|msg| message_class(msg as u32)) != Some(class)
+        {
+            return false;
         }
 
         let query = self.text_query.trim();
@@ -110,6 +192,12 @@ impl EventFilters {
     }
 }
 
+/// A call failed when its result carries an HRESULT with the severity bit set; results without
+/// an HRESULT never count as failures.
+fn is_failed_call(event: &Event) -> bool {
+    find_hresult(&event.result).is_some_and(|value| !hresult_succeeded(value))
+}
+
 fn is_window_display_api(api: &str) -> bool {
     WINDOW_DISPLAY_APIS
         .iter()
@@ -123,7 +211,13 @@ fn is_other_graphics_api(api: &str) -> bool {
 }
 
 fn is_window_display_or_graphics_api(api: &str) -> bool {
-    is_window_display_api(api) || is_other_graphics_api(api) || is_directdraw_api(api)
+    is_window_display_api(api)
+        || is_other_graphics_api(api)
+        || is_directdraw_api(api)
+        || is_direct3d9_api(api)
+        || is_dxgi_d3d11_api(api)
+        || is_opengl_api(api)
+        || GDI_APIS.contains(&api)
 }
 
 fn is_directdraw_api(api: &str) -> bool {
@@ -143,3 +237,45 @@ fn is_directdraw_call_api(api: &str) -> bool {
 
     is_directdraw_api(api)
 }
+
+fn is_direct3d9_api(api: &str) -> bool {
+    [
+        "Direct3D9",
+        "Direct3DCreate9",
+        "IDirect3D9",
+        "IDirect3DDevice9",
+    ]
+    .iter()
+    .any(|prefix| api.starts_with(prefix))
+}
+
+fn is_direct3d9_call_api(api: &str) -> bool {
+    !DIRECT3D9_NONCALL_EVENTS.contains(&api) && is_direct3d9_api(api)
+}
+
+fn is_dxgi_d3d11_api(api: &str) -> bool {
+    ["DXGI", "CreateDXGIFactory", "IDXGI", "D3D11", "ID3D11"]
+        .iter()
+        .any(|prefix| api.starts_with(prefix))
+}
+
+fn is_dxgi_d3d11_call_api(api: &str) -> bool {
+    !DXGI_NONCALL_EVENTS.contains(&api) && is_dxgi_d3d11_api(api)
+}
+
+fn is_opengl_api(api: &str) -> bool {
+    // The pixel format and SwapBuffers entry points live in gdi32 but only matter to GL.
+    api.starts_with("wgl")
+        || api.starts_with("OpenGL")
+        || ["ChoosePixelFormat", "SetPixelFormat", "SwapBuffers"].contains(&api)
+}
+
+fn is_opengl_call_api(api: &str) -> bool {
+    !OPENGL_NONCALL_EVENTS.contains(&api) && is_opengl_api(api)
+}
+
+fn is_input_api(api: &str) -> bool {
+    // DirectInput hook install reports stay in: capture problems often come down to a device
+    // whose interface was never hooked.
+    INPUT_APIS.contains(&api) || api.starts_with("DirectInput") || api.starts_with("IDirectInput")
+}
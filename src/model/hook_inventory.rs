@@ -0,0 +1,195 @@
+use crate::model::event::Event;
+use crate::model::ipc::{HOOK_INVENTORY_API, HOOK_INVENTORY_ENTRY_API};
+use crate::util::fields::{field_value, hex_field, int_field};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookState {
+    Active,
+    /// Still enabled, but something else has since rewritten the patched bytes.
+    Overwritten,
+    Disabled,
+    /// Disabled, and the target no longer holds its original bytes either.
+    Modified,
+}
+
+impl HookState {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "active" => Some(Self::Active),
+            "overwritten" => Some(Self::Overwritten),
+            "disabled" => Some(Self::Disabled),
+            "modified" => Some(Self::Modified),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Active => "Active",
+            Self::Overwritten => "Overwritten",
+            Self::Disabled => "Disabled",
+            Self::Modified => "Modified",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HookEntry {
+    pub name: String,
+    /// `inline`, `vtable` or `vtable_copy`.
+    pub method: String,
+    pub target: u64,
+    /// Empty for targets outside any module, such as a vtable copy's interface.
+    pub module: String,
+    pub state: HookState,
+    pub calls: u64,
+    /// Hex bytes at the target when the hook was placed and when the inventory was taken.
+    pub original: String,
+    pub current: String,
+}
+
+impl HookEntry {
+    pub fn module_name(&self) -> &str {
+        self.module.rsplit(['\\', '/']).next().unwrap_or_default()
+    }
+}
+
+/// The latest hook inventory reported by the agent.
+#[derive(Debug, Default)]
+pub struct HookInventory {
+    hooks: Vec<HookEntry>,
+    reported_ms: Option<u64>,
+    /// Entry count announced by the header; more than arrived means datagrams were lost.
+    expected: usize,
+}
+
+impl HookInventory {
+    pub fn observe_event(&mut self, event: &Event) {
+        match event.api.as_str() {
+            HOOK_INVENTORY_API => {
+                self.hooks.clear();
+                self.reported_ms = Some(event.timestamp_ms);
+                self.expected = int_field(&event.summary, "hooks")
+                    .and_then(|hooks| usize::try_from(hooks).ok())
+                    .unwrap_or(0);
+            }
+            HOOK_INVENTORY_ENTRY_API => {
+                if let Some(entry) = parse_entry(event) {
+                    self.hooks.push(entry);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn hooks(&self) -> &[HookEntry] {
+        &self.hooks
+    }
+
+    pub fn reported_ms(&self) -> Option<u64> {
+        self.reported_ms
+    }
+
+    pub fn expected(&self) -> usize {
+        self.expected
+    }
+
+    pub fn count(&self, state: HookState) -> usize {
+        self.hooks.iter().filter(|hook| hook.state == state).count()
+    }
+}
+
+fn parse_entry(event: &Event) -> Option<HookEntry> {
+    Some(HookEntry {
+        name: field_value(&event.summary, "name")?.to_owned(),
+        method: field_value(&event.summary, "method")?.to_owned(),
+        target: hex_field(&event.summary, "target")?,
+        module: field_value(&event.summary, "module")
+            .unwrap_or_default()
+            .to_owned(),
+        state: HookState::parse(field_value(&event.result, "state")?)?,
+        calls: int_field(&event.result, "calls")
+            .and_then(|calls| u64::try_from(calls).ok())
+            .unwrap_or(0),
+        original: field_value(&event.result, "original")
+            .unwrap_or_default()
+            .to_owned(),
+        current: field_value(&event.result, "current")
+            .unwrap_or_default()
+            .to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp_ms: u64, name: &str, module: &str, result: &str) -> Event {
+        Event::traced(
+            timestamp_ms,
+            HOOK_INVENTORY_ENTRY_API,
+            &format!("name=\"{name}\" method=inline target=0x7FF812340000 module=\"{module}\""),
+            result,
+        )
+    }
+
+    #[test]
+    fn each_report_replaces_the_previous_one() {
+        let mut inventory = HookInventory::default();
+        inventory.observe_event(&Event::traced(
+            100,
+            HOOK_INVENTORY_API,
+            "hooks=1 overwritten=0",
+            "OK",
+        ));
+        inventory.observe_event(&entry(
+            100,
+            "DirectDrawCreate",
+            r"C:\Windows\System32\ddraw.dll",
+            "state=active enabled=TRUE calls=3 original=8BFF55 current=E90000",
+        ));
+        assert_eq!(inventory.hooks().len(), 1);
+
+        inventory.observe_event(&Event::traced(
+            200,
+            HOOK_INVENTORY_API,
+            "hooks=3 overwritten=1",
+            "OK",
+        ));
+        inventory.observe_event(&entry(
+            200,
+            "DirectDrawCreate",
+            r"C:\Windows\System32\ddraw.dll",
+            "state=overwritten enabled=TRUE calls=5 original=8BFF55 current=FF2500",
+        ));
+        inventory.observe_event(&entry(
+            200,
+            "ShowWindow",
+            r"C:\Windows\System32\user32.dll",
+            "state=modified enabled=FALSE calls=0 original=4883EC current=E91111",
+        ));
+
+        assert_eq!(inventory.reported_ms(), Some(200));
+        assert_eq!((inventory.hooks().len(), inventory.expected()), (2, 3));
+        assert_eq!(inventory.count(HookState::Overwritten), 1);
+        assert_eq!(inventory.count(HookState::Modified), 1);
+        assert_eq!(inventory.count(HookState::Active), 0);
+        let hook = &inventory.hooks()[0];
+        assert_eq!(
+            (hook.method.as_str(), hook.target, hook.calls),
+            ("inline", 0x7FF8_1234_0000, 5)
+        );
+        assert_eq!(hook.module_name(), "ddraw.dll");
+        assert_eq!(
+            (hook.original.as_str(), hook.current.as_str()),
+            ("8BFF55", "FF2500")
+        );
+    }
+
+    #[test]
+    fn skips_entries_with_an_unknown_state() {
+        let mut inventory = HookInventory::default();
+        inventory.observe_event(&entry(100, "Sleep", "", "state=pending calls=1"));
+        assert!(inventory.hooks().is_empty());
+    }
+}
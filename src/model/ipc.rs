@@ -1 +1,13 @@
 pub const TRACE_UDP_BIND_ADDR: &str = "127.0.0.1:38473";
+
+/// Sent by the agent once `ShutdownAgent` has undone its hooks; ends the trace like `ExitProcess`.
+pub const AGENT_DETACHED_API: &str = "AgentDetached";
+
+/// `ShutdownAgent` exit codes. When agent code may still be running, the hooks are off but the
+/// agent stays loaded, and another `ShutdownAgent` retries the unload.
+pub const SHUTDOWN_AGENT_UNLOADED: u32 = 1;
+pub const SHUTDOWN_AGENT_LEFT_LOADED: u32 = 2;
+
+/// Sent by the agent's `ReportHookInventory` export ahead of one `HookInventoryEntry` per hook.
+pub const HOOK_INVENTORY_API: &str = "HookInventory";
+pub const HOOK_INVENTORY_ENTRY_API: &str = "HookInventoryEntry";
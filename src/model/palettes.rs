@@ -0,0 +1,350 @@
+use crate::decode::hresult::{find_hresult, hresult_succeeded};
+use crate::model::event::Event;
+use crate::util::fields::{field_value, hex_field, int_field};
+use std::collections::HashMap;
+
+const DDPCAPS_4BIT: u32 = 0x0000_0001;
+const DDPCAPS_1BIT: u32 = 0x0000_0100;
+const DDPCAPS_2BIT: u32 = 0x0000_0200;
+/// Bytes per PALETTEENTRY: red, green, blue, flags.
+const PALETTE_ENTRY_SIZE: usize = 4;
+/// Palette animation can call `SetEntries` every frame; keep the most recent changes only.
+const MAX_CHANGES_PER_PALETTE: usize = 2048;
+
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone)]
+pub struct PaletteChange {
+    pub timestamp_ms: u64,
+    pub api: String,
+    pub start: usize,
+    pub count: usize,
+    pub thread_id: u32,
+    pub caller: String,
+    /// The whole palette after this change.
+    pub colors: Vec<Rgb>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackedPalette {
+    pub pointer: u64,
+    /// `None` for palettes created before the agent was injected.
+    pub created_ms: Option<u64>,
+    pub flags: Option<u32>,
+    pub colors: Vec<Rgb>,
+    /// Surfaces the palette is currently attached to through `SetPalette`.
+    pub surfaces: Vec<u64>,
+    pub changes: Vec<PaletteChange>,
+    pub released_ms: Option<u64>,
+}
+
+/// DirectDraw palettes and their contents over time, rebuilt from events.
+#[derive(Debug, Default)]
+pub struct PaletteTracker {
+    by_pointer: HashMap<u64, TrackedPalette>,
+    order: Vec<u64>,
+}
+
+impl PaletteTracker {
+    /// Also replaces the hex `colors=` payload of palette events with an entry count so the
+    /// event list stays readable.
+    pub fn observe_event(&mut self, event: &mut Event) {
+        let succeeded = find_hresult(&event.result).is_some_and(hresult_succeeded);
+        match event.api.as_str() {
+            "IDirectDraw::CreatePalette" if succeeded => {
+                let Some(pointer) = hex_field(&event.result, "palette").filter(|ptr| *ptr != 0)
+                else {
+                    return;
+                };
+                let flags = hex_field(&event.summary, "flags").map(|flags| flags as u32);
+                let size = flags.map_or(256, entry_count);
+                let colors = parse_colors(field_value(&event.summary, "colors"));
+
+                // A recycled pointer is a new palette once the old one was released.
+                if self
+                    .by_pointer
+                    .get(&pointer)
+                    .is_some_and(|palette| palette.released_ms.is_some())
+                {
+                    self.by_pointer.remove(&pointer);
+                    self.order.retain(|existing| *existing != pointer);
+                }
+                let palette = self.palette_mut(pointer);
+                palette.created_ms = Some(event.timestamp_ms);
+                palette.flags = flags;
+                palette.colors = vec![[0; 3]; size];
+                apply_change(palette, event, 0, &colors);
+            }
+            "IDirectDrawPalette::SetEntries" | "IDirectDrawPalette::GetEntries" if succeeded => {
+                let Some(pointer) = hex_field(&event.summary, "this").filter(|ptr| *ptr != 0)
+                else {
+                    return;
+                };
+                let start_key = if event.api.ends_with("SetEntries") {
+                    "start"
+                } else {
+                    "base"
+                };
+                let start = int_field(&event.summary, start_key).unwrap_or(0).max(0) as usize;
+                let colors = if event.api.ends_with("SetEntries") {
+                    parse_colors(field_value(&event.summary, "colors"))
+                } else {
+                    parse_colors(field_value(&event.result, "colors"))
+                };
+                let palette = self.palette_mut(pointer);
+                // GetEntries only tells us what the palette holds; it is recorded as a change
+                // when that differs from what was seen so far.
+                let changed = colors
+                    .iter()
+                    .enumerate()
+                    .any(|(i, color)| palette.colors.get(start + i) != Some(color));
+                if event.api.ends_with("SetEntries") || changed {
+                    apply_change(palette, event, start, &colors);
+                }
+            }
+            "IDirectDrawSurface::SetPalette" if succeeded => {
+                let Some(surface) = hex_field(&event.summary, "this") else {
+                    return;
+                };
+                for palette in self.by_pointer.values_mut() {
+                    palette.surfaces.retain(|existing| *existing != surface);
+                }
+                if let Some(pointer) = hex_field(&event.summary, "palette").filter(|ptr| *ptr != 0)
+                {
+                    self.palette_mut(pointer).surfaces.push(surface);
+                }
+            }
+            "IDirectDrawPalette::Release" => {
+                if int_field(&event.result, "refcount") != Some(0) {
+                    return;
+                }
+                if let Some(palette) = hex_field(&event.summary, "this")
+                    .and_then(|this| self.by_pointer.get_mut(&this))
+                {
+                    palette.released_ms = Some(event.timestamp_ms);
+                }
+            }
+            _ => {}
+        }
+
+        if event.api == "IDirectDraw::CreatePalette" || event.api.starts_with("IDirectDrawPalette")
+        {
+            event.summary = compact_colors(&event.summary);
+            event.result = compact_colors(&event.result);
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &TrackedPalette> {
+        self.order
+            .iter()
+            .filter_map(|pointer| self.by_pointer.get(pointer))
+    }
+
+    pub fn get(&self, pointer: u64) -> Option<&TrackedPalette> {
+        self.by_pointer.get(&pointer)
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_pointer.len()
+    }
+
+    fn palette_mut(&mut self, pointer: u64) -> &mut TrackedPalette {
+        if !self.by_pointer.contains_key(&pointer) {
+            self.order.push(pointer);
+        }
+        self.by_pointer
+            .entry(pointer)
+            .or_insert_with(|| TrackedPalette {
+                pointer,
+                created_ms: None,
+                flags: None,
+                colors: vec![[0; 3]; 256],
+                surfaces: Vec::new(),
+                changes: Vec::new(),
+                released_ms: None,
+            })
+    }
+}
+
+fn apply_change(palette: &mut TrackedPalette, event: &Event, start: usize, colors: &[Rgb]) {
+    for (i, color) in colors.iter().enumerate() {
+        if let Some(slot) = palette.colors.get_mut(start + i) {
+            *slot = *color;
+        }
+    }
+    if palette.changes.len() == MAX_CHANGES_PER_PALETTE {
+        palette.changes.remove(0);
+    }
+    palette.changes.push(PaletteChange {
+        timestamp_ms: event.timestamp_ms,
+        api: event.api.clone(),
+        start,
+        count: colors.len(),
+        thread_id: event.thread_id,
+        caller: event.caller.clone(),
+        colors: palette.colors.clone(),
+    });
+}
+
+fn entry_count(flags: u32) -> usize {
+    if flags & DDPCAPS_4BIT != 0 {
+        16
+    } else if flags & DDPCAPS_2BIT != 0 {
+        4
+    } else if flags & DDPCAPS_1BIT != 0 {
+        2
+    } else {
+        256
+    }
+}
+
+/// Parses the agent's hex PALETTEENTRY list; anything else (`null`, `indexed`, a pointer)
+/// yields no colors.
+fn parse_colors(value: Option<&str>) -> Vec<Rgb> {
+    let Some(value) = value else {
+        return Vec::new();
+    };
+    let bytes: Option<Vec<u8>> = (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect();
+    bytes.map_or_else(Vec::new, |bytes| {
+        bytes
+            .chunks_exact(PALETTE_ENTRY_SIZE)
+            .map(|entry| [entry[0], entry[1], entry[2]])
+            .collect()
+    })
+}
+
+fn compact_colors(text: &str) -> String {
+    text.split(' ')
+        .map(|token| match token.strip_prefix("colors=") {
+            Some(value) if value.len() >= 2 * PALETTE_ENTRY_SIZE && !value.starts_with("0x") => {
+                format!("colors=<{} entries>", parse_colors(Some(value)).len())
+            }
+            _ => token.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: &str = "FF000000";
+    const GREEN: &str = "00FF0000";
+    const BLUE: &str = "0000FF00";
+
+    fn observe(
+        tracker: &mut PaletteTracker,
+        timestamp_ms: u64,
+        api: &str,
+        summary: &str,
+        result: &str,
+    ) -> Event {
+        let mut event = Event::traced(timestamp_ms, api, summary, result);
+        tracker.observe_event(&mut event);
+        event
+    }
+
+    #[test]
+    fn tracks_palette_contents_attachment_and_release() {
+        let mut tracker = PaletteTracker::default();
+        let created = observe(
+            &mut tracker,
+            100,
+            "IDirectDraw::CreatePalette",
+            &format!(
+                "this=0x1000 flags=0x00000001 entries=0x19fa00 colors={RED}{GREEN} outer_ptr=0x0"
+            ),
+            "HRESULT=0x00000000 palette=0x4000",
+        );
+        observe(
+            &mut tracker,
+            110,
+            "IDirectDrawSurface::SetPalette",
+            "this=0x2000 palette=0x4000",
+            "HRESULT=0x00000000",
+        );
+        observe(
+            &mut tracker,
+            120,
+            "IDirectDrawPalette::SetEntries",
+            &format!("this=0x4000 flags=0x00000000 start=1 count=1 colors={BLUE}"),
+            "HRESULT=0x00000000",
+        );
+        // Reads back what is already known, so it is not a change.
+        observe(
+            &mut tracker,
+            130,
+            "IDirectDrawPalette::GetEntries",
+            "this=0x4000 flags=0x00000000 base=0 count=2",
+            &format!("HRESULT=0x00000000 colors={RED}{BLUE}"),
+        );
+        observe(
+            &mut tracker,
+            140,
+            "IDirectDrawPalette::Release",
+            "this=0x4000",
+            "refcount=0",
+        );
+
+        assert!(created.summary.contains(" colors=<2 entries> "));
+        let palette = tracker.get(0x4000).unwrap();
+        assert_eq!(
+            (palette.created_ms, palette.flags),
+            (Some(100), Some(0x0000_0001))
+        );
+        assert_eq!(palette.colors.len(), 16);
+        assert_eq!(
+            &palette.colors[..3],
+            &[[0xFF, 0, 0], [0, 0, 0xFF], [0, 0, 0]]
+        );
+        let changes: Vec<_> = palette
+            .changes
+            .iter()
+            .map(|change| (change.api.as_str(), change.start, change.count))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("IDirectDraw::CreatePalette", 0, 2),
+                ("IDirectDrawPalette::SetEntries", 1, 1)
+            ]
+        );
+        assert_eq!(palette.changes[0].colors[1], [0, 0xFF, 0]);
+        assert_eq!(palette.surfaces, [0x2000]);
+        assert_eq!(palette.released_ms, Some(140));
+    }
+
+    #[test]
+    fn moves_surfaces_between_palettes_and_learns_existing_contents() {
+        let mut tracker = PaletteTracker::default();
+        for (timestamp_ms, palette) in [(100, "0x4000"), (200, "0x5000")] {
+            observe(
+                &mut tracker,
+                timestamp_ms,
+                "IDirectDrawSurface::SetPalette",
+                &format!("this=0x2000 palette={palette}"),
+                "HRESULT=0x00000000",
+            );
+        }
+        observe(
+            &mut tracker,
+            300,
+            "IDirectDrawPalette::GetEntries",
+            "this=0x5000 flags=0x00000000 base=4 count=1",
+            &format!("HRESULT=0x00000000 colors={GREEN}"),
+        );
+
+        assert_eq!(tracker.len(), 2);
+        assert!(tracker.get(0x4000).unwrap().surfaces.is_empty());
+        let palette = tracker.get(0x5000).unwrap();
+        assert_eq!(palette.surfaces, [0x2000]);
+        assert_eq!(palette.created_ms, None);
+        assert_eq!(palette.colors.len(), 256);
+        assert_eq!(palette.colors[4], [0, 0xFF, 0]);
+        assert_eq!(palette.changes.len(), 1);
+    }
+}
@@ -0,0 +1,214 @@
+use crate::model::ddraw_objects::{DdObject, DdObjectGraph};
+use crate::model::event::Event;
+use crate::model::ipc::AGENT_DETACHED_API;
+use crate::util::fields::{hex_field, int_field};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct RefcountEntry {
+    pub pointer: u64,
+    pub interface: String,
+    pub add_refs: u32,
+    pub releases: u32,
+    /// The count returned by the most recent `AddRef` or `Release`.
+    pub refcount: Option<u32>,
+    pub last_change_ms: u64,
+}
+
+/// When the traced process went away: `ExitProcess` or the agent detaching.
+#[derive(Debug, Clone)]
+pub struct ExitTrigger {
+    pub api: String,
+    pub timestamp_ms: u64,
+}
+
+impl ExitTrigger {
+    pub fn from_event(event: &Event) -> Option<Self> {
+        (event.api == "ExitProcess" || event.api == AGENT_DETACHED_API).then(|| Self {
+            api: event.api.clone(),
+            timestamp_ms: event.timestamp_ms,
+        })
+    }
+
+    /// The process may still be running, so what is left open is not necessarily leaked.
+    pub fn detached(&self) -> bool {
+        self.api == AGENT_DETACHED_API
+    }
+}
+
+/// Reference counts per interface pointer, rebuilt from traced `AddRef`/`Release` calls.
+#[derive(Debug, Default)]
+pub struct RefcountLedger {
+    entries: HashMap<u64, RefcountEntry>,
+    order: Vec<u64>,
+    exit: Option<ExitTrigger>,
+}
+
+/// An object that was created but never released to zero.
+#[derive(Debug, Clone, Copy)]
+pub struct LeakedObject<'a> {
+    pub object: &'a DdObject,
+    pub refcount: Option<u32>,
+}
+
+impl RefcountLedger {
+    pub fn observe_event(&mut self, event: &Event) {
+        if let Some(exit) = ExitTrigger::from_event(event) {
+            self.exit = Some(exit);
+            return;
+        }
+
+        let Some((interface, method)) = event.api.rsplit_once("::") else {
+            return;
+        };
+        if method != "AddRef" && method != "Release" {
+            return;
+        }
+        let Some(pointer) = hex_field(&event.summary, "this").filter(|this| *this != 0) else {
+            return;
+        };
+        let refcount = int_field(&event.result, "refcount").and_then(|n| u32::try_from(n).ok());
+
+        // A pointer that dropped to zero may be handed out again for a new object.
+        if self
+            .entries
+            .get(&pointer)
+            .is_some_and(|entry| entry.refcount == Some(0))
+        {
+            self.entries.remove(&pointer);
+            self.order.retain(|existing| *existing != pointer);
+        }
+        if !self.entries.contains_key(&pointer) {
+            self.order.push(pointer);
+        }
+        let entry = self
+            .entries
+            .entry(pointer)
+            .or_insert_with(|| RefcountEntry {
+                pointer,
+                interface: interface.to_owned(),
+                add_refs: 0,
+                releases: 0,
+                refcount: None,
+                last_change_ms: event.timestamp_ms,
+            });
+        if method == "AddRef" {
+            entry.add_refs = entry.add_refs.saturating_add(1);
+        } else {
+            entry.releases = entry.releases.saturating_add(1);
+        }
+        entry.refcount = refcount;
+        entry.last_change_ms = event.timestamp_ms;
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &RefcountEntry> {
+        self.order
+            .iter()
+            .filter_map(|pointer| self.entries.get(pointer))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn exit(&self) -> Option<&ExitTrigger> {
+        self.exit.as_ref()
+    }
+
+    /// Objects with a recorded creation that never reached a refcount of zero.
+    pub fn leak_report<'a>(&self, graph: &'a DdObjectGraph) -> Vec<LeakedObject<'a>> {
+        graph
+            .values()
+            .filter(|object| object.created.is_some() && object.released_ms.is_none())
+            .map(|object| LeakedObject {
+                object,
+                refcount: self
+                    .entries
+                    .get(&object.pointer)
+                    .and_then(|entry| entry.refcount),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observe(ledger: &mut RefcountLedger, graph: &mut DdObjectGraph, events: &[Event]) {
+        for event in events {
+            graph.observe_event(event);
+            ledger.observe_event(event);
+        }
+    }
+
+    fn count(timestamp_ms: u64, api: &str, this: &str, refcount: u32) -> Event {
+        Event::traced(
+            timestamp_ms,
+            api,
+            &format!("this={this}"),
+            &format!("refcount={refcount}"),
+        )
+    }
+
+    #[test]
+    fn reports_objects_never_released_to_zero() {
+        let mut ledger = RefcountLedger::default();
+        let mut graph = DdObjectGraph::default();
+        observe(
+            &mut ledger,
+            &mut graph,
+            &[
+                Event::traced(
+                    100,
+                    "DirectDrawCreate",
+                    "guid=null out_ptr=0x19fe2c outer_ptr=0x0",
+                    "HRESULT=0x00000000 directdraw=0x1000",
+                ),
+                Event::traced(
+                    110,
+                    "IDirectDraw::CreateSurface",
+                    "this=0x1000 desc_ptr=0x19fd80 (caps=DDSCAPS_OFFSCREENPLAIN) out_ptr=0x19fe28",
+                    "HRESULT=0x00000000 surface=0x2000",
+                ),
+                count(120, "IDirectDraw::AddRef", "0x1000", 2),
+                count(130, "IDirectDrawSurface::AddRef", "0x2000", 2),
+                count(140, "IDirectDrawSurface::Release", "0x2000", 1),
+                count(150, "IDirectDrawSurface::Release", "0x2000", 0),
+                count(160, "IDirectDraw::Release", "0x1000", 1),
+                Event::traced(170, "ExitProcess", "exit_code=0", ""),
+            ],
+        );
+
+        let surface = ledger
+            .values()
+            .find(|entry| entry.pointer == 0x2000)
+            .unwrap();
+        assert_eq!((surface.add_refs, surface.releases), (1, 2));
+        assert_eq!(surface.refcount, Some(0));
+
+        let leaks = ledger.leak_report(&graph);
+        assert_eq!(leaks.len(), 1);
+        assert_eq!(leaks[0].object.pointer, 0x1000);
+        assert_eq!(leaks[0].refcount, Some(1));
+        let exit = ledger.exit().unwrap();
+        assert!(!exit.detached());
+    }
+
+    #[test]
+    fn reused_pointer_starts_a_new_entry() {
+        let mut ledger = RefcountLedger::default();
+        ledger.observe_event(&count(100, "IDirectDrawSurface::Release", "0x2000", 0));
+        ledger.observe_event(&count(200, "IDirectDrawSurface::AddRef", "0x2000", 2));
+        ledger.observe_event(&Event::traced(300, AGENT_DETACHED_API, "", ""));
+
+        assert_eq!(ledger.len(), 1);
+        let entry = ledger.values().next().unwrap();
+        assert_eq!(entry.interface, "IDirectDrawSurface");
+        assert_eq!(
+            (entry.add_refs, entry.releases, entry.refcount),
+            (1, 0, Some(2))
+        );
+        assert!(ledger.exit().unwrap().detached());
+    }
+}
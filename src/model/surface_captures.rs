@@ -0,0 +1,214 @@
+use crate::decode::pixels::{PixelFormat, to_rgba};
+use crate::model::event::Event;
+use crate::util::fields::{field_value, hex_field, int_field};
+use std::collections::VecDeque;
+
+const SURFACE_CAPTURE_API: &str = "SurfaceCapture";
+/// Older captures are dropped so periodic capture cannot grow without bound.
+const MAX_CAPTURES: usize = 240;
+
+#[derive(Debug, Clone)]
+pub struct SurfaceCapture {
+    /// Unique for the session, unlike `frame` which restarts with the traced process.
+    pub id: u64,
+    pub timestamp_ms: u64,
+    pub frame: u64,
+    pub surface: u64,
+    pub trigger: String,
+    pub source_width: u32,
+    pub source_height: u32,
+    pub bpp: u32,
+    pub width: usize,
+    pub height: usize,
+    pub rgba: Vec<u8>,
+}
+
+/// Thumbnails of the primary surface sent by the agent while surface capture is on.
+#[derive(Debug, Default)]
+pub struct SurfaceCaptures {
+    captures: VecDeque<SurfaceCapture>,
+    next_id: u64,
+    last_failure: Option<String>,
+}
+
+impl SurfaceCaptures {
+    /// Moves the pixel payload out of capture events, leaving its size in the result so the
+    /// event list stays readable.
+    pub fn observe_event(&mut self, event: &mut Event) {
+        if event.api != SURFACE_CAPTURE_API {
+            return;
+        }
+        let Some(pixels) = field_value(&event.result, "pixels").and_then(decode_hex) else {
+            self.last_failure = Some(format!("frame {}: {}", frame(event), event.result));
+            return;
+        };
+        let palette = field_value(&event.result, "palette")
+            .and_then(decode_hex)
+            .unwrap_or_default();
+        let status = event.result.split(' ').next().unwrap_or_default();
+        let mut note = format!("{status} pixels=<{} bytes>", pixels.len());
+        if !palette.is_empty() {
+            note.push_str(&format!(" palette=<{} entries>", palette.len() / 4));
+        }
+
+        match self.decode_capture(event, &pixels, &palette) {
+            Some(capture) => {
+                if self.captures.len() == MAX_CAPTURES {
+                    self.captures.pop_front();
+                }
+                self.captures.push_back(capture);
+            }
+            None => {
+                self.last_failure = Some(format!(
+                    "frame {}: could not decode {}",
+                    frame(event),
+                    event.summary
+                ));
+            }
+        }
+        event.result = note;
+    }
+
+    pub fn captures(&self) -> &VecDeque<SurfaceCapture> {
+        &self.captures
+    }
+
+    pub fn last_failure(&self) -> Option<&str> {
+        self.last_failure.as_deref()
+    }
+
+    fn decode_capture(
+        &mut self,
+        event: &Event,
+        pixels: &[u8],
+        palette: &[u8],
+    ) -> Option<SurfaceCapture> {
+        let summary = &event.summary;
+        let bpp = u32_field(summary, "bpp")?;
+        let format = match field_value(summary, "format")? {
+            "pal8" => PixelFormat::Palettized8,
+            "rgb" => PixelFormat::from_masks(
+                bpp,
+                hex_field(summary, "r")? as u32,
+                hex_field(summary, "g")? as u32,
+                hex_field(summary, "b")? as u32,
+                hex_field(summary, "a").unwrap_or(0) as u32,
+            )?,
+            _ => return None,
+        };
+        let width = u32_field(summary, "thumb_width")? as usize;
+        let height = u32_field(summary, "thumb_height")? as usize;
+        let rgba = to_rgba(
+            format,
+            width,
+            height,
+            width * format.bytes_per_pixel(),
+            pixels,
+            palette,
+        )?;
+
+        self.next_id += 1;
+        Some(SurfaceCapture {
+            id: self.next_id,
+            timestamp_ms: event.timestamp_ms,
+            frame: frame(event),
+            surface: hex_field(summary, "surface").unwrap_or(0),
+            trigger: field_value(summary, "trigger")
+                .unwrap_or_default()
+                .to_owned(),
+            source_width: u32_field(summary, "width").unwrap_or(0),
+            source_height: u32_field(summary, "height").unwrap_or(0),
+            bpp,
+            width,
+            height,
+            rgba,
+        })
+    }
+}
+
+fn frame(event: &Event) -> u64 {
+    int_field(&event.summary, "frame").map_or(0, |frame| frame as u64)
+}
+
+fn u32_field(text: &str, key: &str) -> Option<u32> {
+    int_field(text, key).and_then(|value| u32::try_from(value).ok())
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture(frame: u64, geometry: &str, result: &str) -> Event {
+        Event::traced(
+            frame * 16,
+            SURFACE_CAPTURE_API,
+            &format!("surface=0x2000 trigger=Flip frame={frame} width=640 height=480 {geometry}"),
+            result,
+        )
+    }
+
+    #[test]
+    fn decodes_captures_and_strips_pixel_payload() {
+        let mut captures = SurfaceCaptures::default();
+        let mut rgb565 = capture(
+            1,
+            "thumb_width=2 thumb_height=1 bpp=16 format=rgb r=0x0000F800 g=0x000007E0 \
+             b=0x0000001F a=0x00000000",
+            "HRESULT=0x00000000 pixels=00F81F00",
+        );
+        captures.observe_event(&mut rgb565);
+        let mut pal8 = capture(
+            2,
+            "thumb_width=1 thumb_height=1 bpp=8 format=pal8",
+            "HRESULT=0x00000000 pixels=00 palette=10203000",
+        );
+        captures.observe_event(&mut pal8);
+
+        assert_eq!(rgb565.result, "HRESULT=0x00000000 pixels=<4 bytes>");
+        assert_eq!(
+            pal8.result,
+            "HRESULT=0x00000000 pixels=<1 bytes> palette=<1 entries>"
+        );
+        let ids: Vec<_> = captures
+            .captures()
+            .iter()
+            .map(|capture| capture.id)
+            .collect();
+        assert_eq!(ids, [1, 2]);
+
+        let first = &captures.captures()[0];
+        assert_eq!((first.surface, first.trigger.as_str()), (0x2000, "Flip"));
+        assert_eq!((first.source_width, first.source_height), (640, 480));
+        assert_eq!((first.width, first.height, first.bpp), (2, 1, 16));
+        assert_eq!(&first.rgba[..4], &[0xFF, 0x00, 0x00, 0xFF]);
+        assert_eq!(&first.rgba[4..], &[0x00, 0x00, 0xFF, 0xFF]);
+        assert_eq!(captures.captures()[1].rgba, [0x10, 0x20, 0x30, 0xFF]);
+        assert_eq!(captures.last_failure(), None);
+    }
+
+    #[test]
+    fn records_failed_captures() {
+        let mut captures = SurfaceCaptures::default();
+        let mut lost = Event::traced(
+            64,
+            SURFACE_CAPTURE_API,
+            "surface=0x2000 trigger=Blt frame=4",
+            "HRESULT=0x887601C2",
+        );
+        captures.observe_event(&mut lost);
+
+        assert!(captures.captures().is_empty());
+        assert_eq!(captures.last_failure(), Some("frame 4: HRESULT=0x887601C2"));
+        assert_eq!(lost.result, "HRESULT=0x887601C2");
+    }
+}
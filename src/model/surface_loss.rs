@@ -0,0 +1,338 @@
+use crate::decode::hresult::{describe_hresult, find_hresult, hresult_succeeded};
+use crate::decode::messages::{WM_ACTIVATE, WM_ACTIVATEAPP, WM_DISPLAYCHANGE, message_name};
+use crate::model::event::Event;
+use crate::model::refcounts::ExitTrigger;
+use crate::util::fields::{field_value, hex_field, int_field};
+use std::collections::{BTreeMap, HashMap};
+
+const DDERR_SURFACELOST: i32 = 0x8876_01C2_u32 as i32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextKind {
+    CooperativeLevel,
+    Activation,
+    DisplayMode,
+}
+
+/// A call that commonly precedes or triggers surface loss.
+#[derive(Debug, Clone)]
+pub struct ContextEvent {
+    pub timestamp_ms: u64,
+    pub kind: ContextKind,
+    pub api: String,
+    pub summary: String,
+    pub result: String,
+}
+
+/// One surface from the first `DDERR_SURFACELOST` until it is restored or released.
+#[derive(Debug, Clone)]
+pub struct LossEpisode {
+    pub surface: u64,
+    pub lost_ms: u64,
+    /// The call that first reported the loss.
+    pub detected_by: String,
+    /// Index into [`SurfaceLossAnalysis::context`] of the last context event before the loss.
+    pub preceding_context: Option<usize>,
+    pub restore_attempts: u32,
+    pub last_restore_error: Option<String>,
+    pub restored_ms: Option<u64>,
+    pub restored_by: Option<String>,
+    pub released_ms: Option<u64>,
+    /// Calls other than `IsLost`/`Restore` made on the surface while it was lost, per API.
+    pub calls_while_lost: BTreeMap<String, u32>,
+}
+
+impl LossEpisode {
+    pub fn is_open(&self) -> bool {
+        self.restored_ms.is_none() && self.released_ms.is_none()
+    }
+
+    pub fn restore_delay_ms(&self) -> Option<u64> {
+        self.restored_ms
+            .map(|restored| restored.saturating_sub(self.lost_ms))
+    }
+
+    pub fn total_calls_while_lost(&self) -> u32 {
+        self.calls_while_lost.values().sum()
+    }
+}
+
+/// Lost-surface episodes and the cooperative-level, activation and display mode calls around
+/// them, rebuilt from events.
+#[derive(Debug, Default)]
+pub struct SurfaceLossAnalysis {
+    episodes: Vec<LossEpisode>,
+    /// Surface pointer to its open episode.
+    open: HashMap<u64, usize>,
+    context: Vec<ContextEvent>,
+    exit: Option<ExitTrigger>,
+}
+
+impl SurfaceLossAnalysis {
+    pub fn observe_event(&mut self, event: &Event) {
+        let api = event.api.as_str();
+        let hresult = find_hresult(&event.result);
+        if let Some(kind) = context_kind(event) {
+            self.context.push(ContextEvent {
+                timestamp_ms: event.timestamp_ms,
+                kind,
+                api: event.api.clone(),
+                summary: event.summary.clone(),
+                result: event.result.clone(),
+            });
+        }
+
+        if let Some(exit) = ExitTrigger::from_event(event) {
+            self.exit = Some(exit);
+        }
+        match api {
+            "IDirectDraw::RestoreAllSurfaces" if hresult.is_some_and(hresult_succeeded) => {
+                for (_, index) in self.open.drain() {
+                    let episode = &mut self.episodes[index];
+                    episode.restore_attempts += 1;
+                    episode.restored_ms = Some(event.timestamp_ms);
+                    episode.restored_by = Some(event.api.clone());
+                }
+            }
+            _ => {}
+        }
+
+        let Some(method) = api.strip_prefix("IDirectDrawSurface::") else {
+            return;
+        };
+        let Some(surface) = hex_field(&event.summary, "this").filter(|this| *this != 0) else {
+            return;
+        };
+        match method {
+            "Restore" => {
+                let Some(episode) = self.open_episode_mut(surface) else {
+                    return;
+                };
+                episode.restore_attempts += 1;
+                if hresult.is_some_and(hresult_succeeded) {
+                    episode.restored_ms = Some(event.timestamp_ms);
+                    episode.restored_by = Some(event.api.clone());
+                    self.open.remove(&surface);
+                } else {
+                    episode.last_restore_error =
+                        Some(hresult.map_or_else(|| event.result.clone(), describe_hresult));
+                }
+            }
+            "IsLost" => match hresult {
+                Some(DDERR_SURFACELOST) => self.mark_lost(surface, event),
+                // Restored by something we did not see, e.g. before the hooks were installed.
+                Some(value) if hresult_succeeded(value) => {
+                    if let Some(index) = self.open.remove(&surface) {
+                        let episode = &mut self.episodes[index];
+                        episode.restored_ms = Some(event.timestamp_ms);
+                        episode.restored_by = Some(event.api.clone());
+                    }
+                }
+                _ => {}
+            },
+            "AddRef" => {}
+            "Release" => {
+                if int_field(&event.result, "refcount") == Some(0)
+                    && let Some(index) = self.open.remove(&surface)
+                {
+                    self.episodes[index].released_ms = Some(event.timestamp_ms);
+                }
+            }
+            _ => {
+                // A call that succeeds shows the surface is usable again even though we did not
+                // see it restored. It also clears losses that were really the Blt source's.
+                if hresult.is_some_and(hresult_succeeded)
+                    && let Some(index) = self.open.remove(&surface)
+                {
+                    let episode = &mut self.episodes[index];
+                    episode.restored_ms = Some(event.timestamp_ms);
+                    episode.restored_by = Some(format!("{} succeeding", event.api));
+                } else if let Some(episode) = self.open_episode_mut(surface) {
+                    *episode
+                        .calls_while_lost
+                        .entry(event.api.clone())
+                        .or_default() += 1;
+                } else if hresult == Some(DDERR_SURFACELOST) {
+                    self.mark_lost(surface, event);
+                }
+            }
+        }
+    }
+
+    pub fn episodes(&self) -> &[LossEpisode] {
+        &self.episodes
+    }
+
+    pub fn context(&self) -> &[ContextEvent] {
+        &self.context
+    }
+
+    pub fn exit(&self) -> Option<&ExitTrigger> {
+        self.exit.as_ref()
+    }
+
+    pub fn unrestored(&self) -> impl Iterator<Item = &LossEpisode> {
+        self.episodes.iter().filter(|episode| episode.is_open())
+    }
+
+    fn open_episode_mut(&mut self, surface: u64) -> Option<&mut LossEpisode> {
+        let index = *self.open.get(&surface)?;
+        self.episodes.get_mut(index)
+    }
+
+    fn mark_lost(&mut self, surface: u64, event: &Event) {
+        if self.open.contains_key(&surface) {
+            return;
+        }
+        let preceding_context = self
+            .context
+            .iter()
+            .rposition(|context| context.timestamp_ms <= event.timestamp_ms);
+        self.open.insert(surface, self.episodes.len());
+        self.episodes.push(LossEpisode {
+            surface,
+            lost_ms: event.timestamp_ms,
+            detected_by: event.api.clone(),
+            preceding_context,
+            restore_attempts: 0,
+            last_restore_error: None,
+            restored_ms: None,
+            restored_by: None,
+            released_ms: None,
+            calls_while_lost: BTreeMap::new(),
+        });
+    }
+}
+
+fn context_kind(event: &Event) -> Option<ContextKind> {
+    match event.api.as_str() {
+        "IDirectDraw::SetCooperativeLevel" | "IDirectDraw::TestCooperativeLevel" => {
+            Some(ContextKind::CooperativeLevel)
+        }
+        "ShowWindow" | "SetForegroundWindow" => Some(ContextKind::Activation),
+        "IDirectDraw::SetDisplayMode"
+        | "IDirectDraw7::SetDisplayMode"
+        | "IDirectDraw::RestoreDisplayMode"
+        | "ChangeDisplaySettingsExW" => Some(ContextKind::DisplayMode),
+        "WindowMessage" => match hex_field(&event.summary, "msg")? as u32 {
+            WM_ACTIVATEAPP | WM_ACTIVATE => Some(ContextKind::Activation),
+            WM_DISPLAYCHANGE => Some(ContextKind::DisplayMode),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Short form of a context event for the report, e.g. `ShowWindow cmd=6` or
+/// `WM_ACTIVATEAPP active=FALSE`.
+pub fn describe_context(context: &ContextEvent) -> String {
+    if context.api == "WindowMessage" {
+        return describe_message_context(&context.summary);
+    }
+    let detail = match context.kind {
+        ContextKind::CooperativeLevel => field_value(&context.summary, "flags")
+            .map(|flags| format!("flags={flags}"))
+            .unwrap_or_default(),
+        ContextKind::Activation => field_value(&context.summary, "cmd")
+            .map(|cmd| format!("cmd={cmd}"))
+            .unwrap_or_default(),
+        ContextKind::DisplayMode => ["width", "height", "bpp"]
+            .iter()
+            .filter_map(|key| field_value(&context.summary, key))
+            .collect::<Vec<_>>()
+            .join("x"),
+    };
+    format!("{} {detail}", context.api).trim_end().to_owned()
+}
+
+fn describe_message_context(summary: &str) -> String {
+    let name = hex_field(summary, "msg")
+        .and_then(|msg| message_name(msg as u32))
+        .unwrap_or_else(|| "WindowMessage".to_owned());
+    let detail = ["active", "state", "bpp", "size"]
+        .iter()
+        .filter_map(|key| field_value(summary, key).map(|value| format!("{key}={value}")))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("{name} {detail}").trim_end().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SURFACE_LOST: &str = "HRESULT=0x887601C2";
+    const OK: &str = "HRESULT=0x00000000";
+
+    fn surface_call(timestamp_ms: u64, method: &str, this: &str, result: &str) -> Event {
+        Event::traced(
+            timestamp_ms,
+            &format!("IDirectDrawSurface::{method}"),
+            &format!("this={this}"),
+            result,
+        )
+    }
+
+    #[test]
+    fn tracks_loss_failed_restore_and_recovery() {
+        let mut analysis = SurfaceLossAnalysis::default();
+        analysis.observe_event(&Event::traced(
+            100,
+            "IDirectDraw::SetCooperativeLevel",
+            "this=0x1000 hwnd=0x00000000001204F0 flags=0x00000011",
+            OK,
+        ));
+        analysis.observe_event(&surface_call(200, "Blt", "0x2000", SURFACE_LOST));
+        analysis.observe_event(&surface_call(210, "Flip", "0x2000", SURFACE_LOST));
+        analysis.observe_event(&surface_call(220, "Restore", "0x2000", SURFACE_LOST));
+        analysis.observe_event(&surface_call(300, "IsLost", "0x2000", SURFACE_LOST));
+        analysis.observe_event(&surface_call(400, "Restore", "0x2000", OK));
+
+        let [episode] = analysis.episodes() else {
+            panic!("expected one episode, got {:?}", analysis.episodes());
+        };
+        assert_eq!(episode.surface, 0x2000);
+        assert_eq!(episode.detected_by, "IDirectDrawSurface::Blt");
+        assert_eq!(episode.preceding_context, Some(0));
+        assert_eq!(
+            describe_context(&analysis.context()[0]),
+            "IDirectDraw::SetCooperativeLevel flags=0x00000011"
+        );
+        assert_eq!(episode.restore_attempts, 2);
+        assert!(episode.last_restore_error.is_some());
+        assert_eq!(episode.total_calls_while_lost(), 1);
+        assert_eq!(episode.restore_delay_ms(), Some(200));
+        assert_eq!(
+            episode.restored_by.as_deref(),
+            Some("IDirectDrawSurface::Restore")
+        );
+        assert_eq!(analysis.unrestored().count(), 0);
+    }
+
+    #[test]
+    fn release_and_restore_all_close_open_episodes() {
+        let mut analysis = SurfaceLossAnalysis::default();
+        analysis.observe_event(&surface_call(100, "IsLost", "0x2000", SURFACE_LOST));
+        analysis.observe_event(&surface_call(100, "IsLost", "0x3000", SURFACE_LOST));
+        assert_eq!(analysis.unrestored().count(), 2);
+
+        analysis.observe_event(&surface_call(150, "Release", "0x3000", "refcount=0"));
+        analysis.observe_event(&Event::traced(
+            200,
+            "IDirectDraw::RestoreAllSurfaces",
+            "this=0x1000",
+            OK,
+        ));
+        analysis.observe_event(&Event::traced(300, "ExitProcess", "exit_code=0", ""));
+
+        let episodes = analysis.episodes();
+        assert_eq!(episodes[0].restored_ms, Some(200));
+        assert_eq!(episodes[0].restore_attempts, 1);
+        assert_eq!(
+            (episodes[1].released_ms, episodes[1].restored_ms),
+            (Some(150), None)
+        );
+        assert_eq!(analysis.unrestored().count(), 0);
+        assert_eq!(analysis.exit().map(|exit| exit.timestamp_ms), Some(300));
+    }
+}
@@ -0,0 +1,214 @@
+use crate::model::event::Event;
+use crate::util::fields::{field_value, int_field};
+use std::collections::{HashMap, VecDeque};
+
+const TIMING_STATS_API: &str = "TimingStats";
+/// One report per second for ten minutes at the default window.
+const MAX_REPORTS: usize = 600;
+/// Calls that end a frame. `SwapBuffers` and `wglSwapBuffers` call each other, so each API is
+/// counted on its own and the busiest one is taken as the frame count.
+const FRAME_BOUNDARY_APIS: [&str; 5] = [
+    "IDirectDrawSurface::Flip",
+    "IDirect3DDevice9::Present",
+    "IDXGISwapChain::Present",
+    "SwapBuffers",
+    "wglSwapBuffers",
+];
+
+/// One aggregation window reported by the agent's timing group.
+#[derive(Debug, Clone)]
+pub struct TimingReport {
+    pub timestamp_ms: u64,
+    pub window_ms: u64,
+    pub time_get_time: u64,
+    pub query_performance_counter: u64,
+    pub get_tick_count: u64,
+    pub sleep_calls: u64,
+    pub sleep_total_ms: u64,
+    /// `Sleep` argument buckets as `ms:count` pairs, e.g. `0:3,1:57,101+:1`.
+    pub sleep_buckets: Vec<(String, u64)>,
+    /// `timeBeginPeriod` arguments as `period:count` pairs.
+    pub timer_periods: Vec<(u32, u64)>,
+    /// Frames presented during the window, and the API they were counted from.
+    pub frames: u64,
+    pub frame_api: Option<&'static str>,
+}
+
+impl TimingReport {
+    pub fn frames_per_second(&self) -> Option<f64> {
+        (self.window_ms != 0).then(|| self.frames as f64 * 1_000.0 / self.window_ms as f64)
+    }
+
+    /// `calls` spread over the frames of the window; `None` without frames.
+    pub fn per_frame(&self, calls: u64) -> Option<f64> {
+        (self.frames != 0).then(|| calls as f64 / self.frames as f64)
+    }
+}
+
+/// Timing reports with the frames presented between them.
+#[derive(Debug, Default)]
+pub struct TimingHistory {
+    reports: VecDeque<TimingReport>,
+    /// Frame boundaries seen since the last report, per API.
+    pending_frames: HashMap<&'static str, u64>,
+    /// Report window last confirmed by the agent; zero once turned off.
+    interval_ms: Option<u64>,
+}
+
+impl TimingHistory {
+    pub fn observe_event(&mut self, event: &Event) {
+        if let Some(api) = FRAME_BOUNDARY_APIS
+            .iter()
+            .find(|candidate| **candidate == event.api)
+        {
+            *self.pending_frames.entry(api).or_default() += 1;
+            return;
+        }
+        match event.api.as_str() {
+            "TimingTraceConfig" => {
+                self.interval_ms = u64_field(&event.summary, "interval_ms");
+                self.pending_frames.clear();
+            }
+            TIMING_STATS_API => {
+                let (frame_api, frames) = self
+                    .pending_frames
+                    .drain()
+                    .max_by_key(|(_, frames)| *frames)
+                    .map_or((None, 0), |(api, frames)| (Some(api), frames));
+                let summary = &event.summary;
+                let report = TimingReport {
+                    timestamp_ms: event.timestamp_ms,
+                    window_ms: u64_field(summary, "window_ms").unwrap_or(0),
+                    time_get_time: u64_field(summary, "timeGetTime").unwrap_or(0),
+                    query_performance_counter: u64_field(summary, "QueryPerformanceCounter")
+                        .unwrap_or(0),
+                    get_tick_count: u64_field(summary, "GetTickCount").unwrap_or(0),
+                    sleep_calls: u64_field(summary, "Sleep").unwrap_or(0),
+                    sleep_total_ms: u64_field(summary, "sleep_total_ms").unwrap_or(0),
+                    sleep_buckets: parse_pairs(summary, "sleep_ms"),
+                    timer_periods: parse_pairs(summary, "timeBeginPeriod")
+                        .into_iter()
+                        .filter_map(|(period, count)| Some((period.parse().ok()?, count)))
+                        .collect(),
+                    frames,
+                    frame_api,
+                };
+                if self.reports.len() == MAX_REPORTS {
+                    self.reports.pop_front();
+                }
+                self.reports.push_back(report);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn reports(&self) -> &VecDeque<TimingReport> {
+        &self.reports
+    }
+
+    pub fn interval_ms(&self) -> Option<u64> {
+        self.interval_ms
+    }
+}
+
+fn u64_field(text: &str, key: &str) -> Option<u64> {
+    int_field(text, key).and_then(|value| u64::try_from(value).ok())
+}
+
+/// Parses `key=a:1,b:2`; the agent writes `-` for an empty list.
+fn parse_pairs(text: &str, key: &str) -> Vec<(String, u64)> {
+    field_value(text, key)
+        .filter(|value| *value != "-")
+        .map(|value| {
+            value
+                .split(',')
+                .filter_map(|pair| {
+                    let (label, count) = pair.split_once(':')?;
+                    Some((label.to_owned(), count.parse().ok()?))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn present(timestamp_ms: u64) -> Event {
+        Event::traced(
+            timestamp_ms,
+            "IDirect3DDevice9::Present",
+            "this=0x1000 src=null dst=null hwnd=0x0000000000000000 dirty=0x0",
+            "HRESULT=0x00000000",
+        )
+    }
+
+    #[test]
+    fn reports_take_the_frames_presented_since_the_last_one() {
+        let mut history = TimingHistory::default();
+        history.observe_event(&Event::traced(
+            0,
+            "TimingTraceConfig",
+            "interval_ms=1000",
+            "OK",
+        ));
+        for frame in 0..30 {
+            history.observe_event(&present(10 + frame * 33));
+        }
+        history.observe_event(&Event::traced(
+            1_000,
+            TIMING_STATS_API,
+            "window_ms=1000 timeGetTime=120 QueryPerformanceCounter=0 GetTickCount=4 Sleep=31 \
+             sleep_total_ms=40 sleep_ms=0:1,1:30 timeBeginPeriod=1:1",
+            "OK",
+        ));
+        history.observe_event(&Event::traced(
+            2_000,
+            TIMING_STATS_API,
+            "window_ms=500 timeGetTime=0 QueryPerformanceCounter=0 GetTickCount=0 Sleep=0 \
+             sleep_total_ms=0 sleep_ms=- timeBeginPeriod=-",
+            "OK",
+        ));
+
+        assert_eq!(history.interval_ms(), Some(1_000));
+        let reports = history.reports();
+        assert_eq!(reports.len(), 2);
+        let first = &reports[0];
+        assert_eq!(first.frames, 30);
+        assert_eq!(first.frame_api, Some("IDirect3DDevice9::Present"));
+        assert_eq!(first.frames_per_second(), Some(30.0));
+        assert_eq!(first.per_frame(first.time_get_time), Some(4.0));
+        assert_eq!(
+            first.sleep_buckets,
+            [("0".to_owned(), 1), ("1".to_owned(), 30)]
+        );
+        assert_eq!(first.timer_periods, [(1, 1)]);
+        assert_eq!((first.sleep_calls, first.sleep_total_ms), (31, 40));
+
+        let second = &reports[1];
+        assert_eq!((second.frames, second.frame_api), (0, None));
+        assert_eq!(second.per_frame(10), None);
+        assert!(second.sleep_buckets.is_empty() && second.timer_periods.is_empty());
+    }
+
+    #[test]
+    fn turning_reports_off_drops_pending_frames() {
+        let mut history = TimingHistory::default();
+        history.observe_event(&present(10));
+        history.observe_event(&Event::traced(
+            20,
+            "TimingTraceConfig",
+            "interval_ms=0",
+            "OK",
+        ));
+        history.observe_event(&Event::traced(
+            30,
+            TIMING_STATS_API,
+            "window_ms=1000 sleep_ms=- timeBeginPeriod=-",
+            "OK",
+        ));
+        assert_eq!(history.interval_ms(), Some(0));
+        assert_eq!(history.reports()[0].frames, 0);
+    }
+}
@@ -0,0 +1,334 @@
+use crate::model::event::Event;
+use crate::util::fields::{field_value, hex_field, int_field};
+use std::collections::HashMap;
+
+const SWP_NOSIZE: u32 = 0x0001;
+const SWP_NOMOVE: u32 = 0x0002;
+const GWL_STYLE: i64 = -16;
+const GWL_EXSTYLE: i64 = -20;
+/// `CreateWindowEx` position or size left for the system to choose.
+const CW_USEDEFAULT: i32 = i32::MIN;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[derive(Debug, Clone)]
+pub struct GeometryChange {
+    pub timestamp_ms: u64,
+    pub api: String,
+    pub rect: WindowRect,
+}
+
+#[derive(Debug, Clone)]
+pub struct StyleChange {
+    pub timestamp_ms: u64,
+    pub api: String,
+    pub style: u32,
+    pub ex_style: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct TrackedWindow {
+    pub hwnd: u64,
+    pub class_name: String,
+    pub title: String,
+    pub parent: u64,
+    /// `None` when the window already existed before the agent attached.
+    pub created_ms: Option<u64>,
+    pub destroyed_ms: Option<u64>,
+    pub creation_summary: String,
+    pub thread_id: u32,
+    pub rect: Option<WindowRect>,
+    pub style: u32,
+    pub ex_style: u32,
+    pub geometry: Vec<GeometryChange>,
+    pub styles: Vec<StyleChange>,
+}
+
+/// Per-HWND state rebuilt from window management events alone, so saved traces replay the same
+/// way live ones do.
+#[derive(Debug, Default)]
+pub struct TrackedWindows {
+    by_hwnd: HashMap<u64, TrackedWindow>,
+}
+
+impl TrackedWindow {
+    fn new(hwnd: u64, thread_id: u32) -> Self {
+        Self {
+            hwnd,
+            class_name: String::new(),
+            title: String::new(),
+            parent: 0,
+            created_ms: None,
+            destroyed_ms: None,
+            creation_summary: String::new(),
+            thread_id,
+            rect: None,
+            style: 0,
+            ex_style: 0,
+            geometry: Vec::new(),
+            styles: Vec::new(),
+        }
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.destroyed_ms.is_none()
+    }
+
+    fn record_geometry(&mut self, event: &Event, rect: WindowRect) {
+        self.rect = Some(rect);
+        self.geometry.push(GeometryChange {
+            timestamp_ms: event.timestamp_ms,
+            api: event.api.clone(),
+            rect,
+        });
+    }
+
+    fn record_style(&mut self, event: &Event) {
+        self.styles.push(StyleChange {
+            timestamp_ms: event.timestamp_ms,
+            api: event.api.clone(),
+            style: self.style,
+            ex_style: self.ex_style,
+        });
+    }
+}
+
+impl TrackedWindows {
+    pub fn observe_event(&mut self, event: &Event) {
+        match event.api.as_str() {
+            "CreateWindowExW" => self.observe_create(event),
+            "DestroyWindow" => {
+                if event.result != "TRUE" {
+                    return;
+                }
+                if let Some(window) = self.window_for(event) {
+                    window.destroyed_ms = Some(event.timestamp_ms);
+                }
+            }
+            "SetWindowPos" => {
+                if event.result != "TRUE" {
+                    return;
+                }
+                let flags = hex_field(&event.summary, "flags").unwrap_or(0) as u32;
+                let moved = (flags & SWP_NOMOVE == 0)
+                    .then(|| position_fields(&event.summary, "x", "y"))
+                    .flatten();
+                let sized = (flags & SWP_NOSIZE == 0)
+                    .then(|| position_fields(&event.summary, "w", "h"))
+                    .flatten();
+                if moved.is_none() && sized.is_none() {
+                    return;
+                }
+                if let Some(window) = self.window_for(event) {
+                    let previous = window.rect.unwrap_or(WindowRect {
+                        x: 0,
+                        y: 0,
+                        width: 0,
+                        height: 0,
+                    });
+                    let (x, y) = moved.unwrap_or((previous.x, previous.y));
+                    let (width, height) = sized.unwrap_or((previous.width, previous.height));
+                    window.record_geometry(
+                        event,
+                        WindowRect {
+                            x,
+                            y,
+                            width,
+                            height,
+                        },
+                    );
+                }
+            }
+            "MoveWindow" => {
+                if event.result != "TRUE" {
+                    return;
+                }
+                let Some(rect) = rect_fields(&event.summary, "w", "h") else {
+                    return;
+                };
+                if let Some(window) = self.window_for(event) {
+                    window.record_geometry(event, rect);
+                }
+            }
+            "SetWindowLongW" | "SetWindowLongPtrW" => {
+                let index = int_field(&event.summary, "index");
+                if index != Some(GWL_STYLE) && index != Some(GWL_EXSTYLE) {
+                    return;
+                }
+                let Some(value) = hex_field(&event.summary, "value") else {
+                    return;
+                };
+                if let Some(window) = self.window_for(event) {
+                    if index == Some(GWL_STYLE) {
+                        window.style = value as u32;
+                    } else {
+                        window.ex_style = value as u32;
+                    }
+                    window.record_style(event);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &TrackedWindow> {
+        self.by_hwnd.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.by_hwnd.len()
+    }
+
+    fn observe_create(&mut self, event: &Event) {
+        let Some(hwnd) = hex_field(&event.result, "HWND").filter(|hwnd| *hwnd != 0) else {
+            return;
+        };
+
+        // HWND values are recycled, so a creation always starts a fresh record.
+        let mut window = TrackedWindow::new(hwnd, event.thread_id);
+        window.class_name = field_value(&event.summary, "class")
+            .unwrap_or_default()
+            .to_owned();
+        window.title = field_value(&event.summary, "title")
+            .unwrap_or_default()
+            .to_owned();
+        window.parent = hex_field(&event.summary, "parent").unwrap_or(0);
+        window.created_ms = Some(event.timestamp_ms);
+        window.creation_summary = event.summary.clone();
+        window.style = hex_field(&event.summary, "style").unwrap_or(0) as u32;
+        window.ex_style = hex_field(&event.summary, "ex").unwrap_or(0) as u32;
+        window.record_style(event);
+        // The system picks defaulted coordinates, so the rect waits for the first move or resize.
+        if let Some(rect) = rect_fields(&event.summary, "width", "height")
+            && rect.x != CW_USEDEFAULT
+            && rect.width != CW_USEDEFAULT
+        {
+            window.record_geometry(event, rect);
+        }
+        self.by_hwnd.insert(hwnd, window);
+    }
+
+    fn window_for(&mut self, event: &Event) -> Option<&mut TrackedWindow> {
+        let hwnd = hex_field(&event.summary, "hwnd").filter(|hwnd| *hwnd != 0)?;
+        Some(
+            self.by_hwnd
+                .entry(hwnd)
+                .or_insert_with(|| TrackedWindow::new(hwnd, event.thread_id)),
+        )
+    }
+}
+
+fn position_fields(text: &str, first: &str, second: &str) -> Option<(i32, i32)> {
+    Some((
+        int_field(text, first)? as i32,
+        int_field(text, second)? as i32,
+    ))
+}
+
+fn rect_fields(text: &str, width_key: &str, height_key: &str) -> Option<WindowRect> {
+    let (x, y) = position_fields(text, "x", "y")?;
+    let (width, height) = position_fields(text, width_key, height_key)?;
+    Some(WindowRect {
+        x,
+        y,
+        width,
+        height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CREATE_SUMMARY: &str = "class=\"GameWindow\" title=\"Game\" parent=0x0000000000000000";
+
+    fn create(timestamp_ms: u64, geometry: &str) -> Event {
+        Event::traced(
+            timestamp_ms,
+            "CreateWindowExW",
+            &format!("{CREATE_SUMMARY} {geometry} style=0x10CF0000 ex=0x00000000"),
+            "HWND=0x00000000001204F0",
+        )
+    }
+
+    fn tracked(windows: &TrackedWindows) -> &TrackedWindow {
+        windows.values().next().unwrap()
+    }
+
+    #[test]
+    fn tracks_create_resize_and_destroy() {
+        let mut windows = TrackedWindows::default();
+        windows.observe_event(&create(100, "x=0 y=0 width=640 height=480"));
+        windows.observe_event(&Event::traced(
+            200,
+            "SetWindowPos",
+            "hwnd=0x00000000001204F0 x=0 y=0 w=1600 h=900 flags=0x00000002",
+            "TRUE",
+        ));
+        windows.observe_event(&Event::traced(
+            300,
+            "SetWindowLongPtrW",
+            "hwnd=0x00000000001204F0 index=-16 value=0x0000000090000000",
+            "previous=0x0000000010CF0000",
+        ));
+        windows.observe_event(&Event::traced(
+            400,
+            "DestroyWindow",
+            "hwnd=0x00000000001204F0",
+            "TRUE",
+        ));
+
+        let window = tracked(&windows);
+        assert_eq!(windows.len(), 1);
+        assert_eq!(
+            (window.class_name.as_str(), window.title.as_str()),
+            ("GameWindow", "Game")
+        );
+        assert_eq!(
+            window.rect,
+            Some(WindowRect {
+                x: 0,
+                y: 0,
+                width: 1600,
+                height: 900
+            })
+        );
+        assert_eq!(window.geometry.len(), 2);
+        assert_eq!((window.style, window.styles.len()), (0x9000_0000, 2));
+        assert_eq!(
+            (window.created_ms, window.destroyed_ms),
+            (Some(100), Some(400))
+        );
+        assert!(!window.is_alive());
+    }
+
+    #[test]
+    fn defaulted_create_geometry_waits_for_first_move() {
+        let mut windows = TrackedWindows::default();
+        windows.observe_event(&create(100, "x=-2147483648 y=0 width=-2147483648 height=0"));
+        assert_eq!(tracked(&windows).rect, None);
+        assert!(tracked(&windows).geometry.is_empty());
+
+        windows.observe_event(&Event::traced(
+            200,
+            "MoveWindow",
+            "hwnd=0x00000000001204F0 x=40 y=30 w=800 h=600 repaint=1",
+            "TRUE",
+        ));
+        assert_eq!(
+            tracked(&windows).rect,
+            Some(WindowRect {
+                x: 40,
+                y: 30,
+                width: 800,
+                height: 600
+            })
+        );
+    }
+}
@@ -0,0 +1,36 @@
+//! Lookup of the `key=value` fields agent events are formatted with.
+
+/// Returns the value of `key` in `text`. Values run to the next space unless they start with a
+/// double quote, in which case they run to the closing quote (which is not included).
+pub fn field_value<'a>(text: &'a str, key: &str) -> Option<&'a str> {
+    let mut rest = text;
+    loop {
+        let start = rest.find(key)?;
+        let at_token_start = start == 0 || rest.as_bytes()[start - 1] == b' ';
+        let after_key = &rest[start + key.len()..];
+        if at_token_start && let Some(value) = after_key.strip_prefix('=') {
+            return Some(match value.strip_prefix('"') {
+                Some(quoted) => quoted.split_once('"').map_or(quoted, |(inner, _)| inner),
+                None => {
+                    let value = value.split(' ').next().unwrap_or(value);
+                    // The last field of a bracketed group such as `mode=[... freq=60]`.
+                    value
+                        .strip_suffix(']')
+                        .filter(|v| !v.contains('['))
+                        .unwrap_or(value)
+                }
+            });
+        }
+        rest = after_key;
+    }
+}
+
+/// Parses a `0x`-prefixed hex field such as `hwnd=0x00000000001204F0`.
+pub fn hex_field(text: &str, key: &str) -> Option<u64> {
+    let value = field_value(text, key)?.strip_prefix("0x")?;
+    u64::from_str_radix(value, 16).ok()
+}
+
+pub fn int_field(text: &str, key: &str) -> Option<i64> {
+    field_value(text, key)?.parse().ok()
+}